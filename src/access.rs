@@ -0,0 +1,113 @@
+//---------------------------------------------------------------------------------------------------- ReadOnly
+/// Mark a type's file as read-only.
+///
+/// If your type implements this trait _and_ one of the `disk` traits via macro,
+/// calling any function that writes or removes its file fails to compile, e.g:
+/// - All `save*()` functions
+/// - All `rm*()` functions
+/// - `open_write()`, `open_append()`, `create_if_missing()`, `restore_from()`, etc
+/// - `snapshot()`, `prune_history()`, `append_audit()` and `rotate_audit()`
+///
+/// Reading (`from_file*()`, `exists()`, `absolute_path()`, etc) works as normal.
+///
+/// This is meant for files that your program should never touch, like vendor-shipped data files.
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::toml!(Vendor, Dir::Data, "disk_test", "read_only", "vendor");
+/// #[derive(Serialize,Deserialize)]
+/// struct Vendor {
+///     string: String,
+/// }
+///
+/// impl disk::ReadOnly for Vendor {}
+///
+/// assert!(Vendor::READ_ONLY);
+/// assert!(Vendor::from_file().is_err());
+/// ```
+///
+/// ```rust,compile_fail
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// # disk::toml!(Vendor, Dir::Data, "disk_test", "read_only", "vendor");
+/// # #[derive(Serialize,Deserialize)]
+/// # struct Vendor {
+/// #     string: String,
+/// # }
+/// # impl disk::ReadOnly for Vendor {}
+/// // This does not compile.
+/// Vendor { string: "hello".into() }.save();
+/// ```
+///
+/// Manual `unsafe impl`'s must set `const READ_ONLY: bool = true` themselves.
+pub trait ReadOnly {}
+
+//---------------------------------------------------------------------------------------------------- WriteOnce
+/// Mark a type's file as write-once.
+///
+/// If your type implements this trait _and_ one of the `disk` traits via macro,
+/// all `save*()` functions (and other functions that would overwrite the file,
+/// like `open_write()` and `restore_from()`) return an error if the file already exists.
+///
+/// The file is checked right before writing, so a file created
+/// by another process in-between is still overwritten.
+///
+/// `rm*()` functions work as normal, so the file can be written again after removing it.
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::toml!(Receipt, Dir::Data, "disk_test", "write_once", "receipt");
+/// #[derive(Serialize,Deserialize)]
+/// struct Receipt {
+///     id: u64,
+/// }
+///
+/// impl disk::WriteOnce for Receipt {}
+///
+/// # Receipt::rm().unwrap();
+/// // The first save works...
+/// Receipt { id: 1 }.save().unwrap();
+/// // ...but the file can not be overwritten.
+/// assert!(Receipt { id: 2 }.save().is_err());
+/// assert_eq!(Receipt::from_file().unwrap().id, 1);
+/// # Receipt::rm().unwrap();
+/// ```
+///
+/// Manual `unsafe impl`'s must set `const WRITE_ONCE: bool = true` themselves.
+pub trait WriteOnce {}
+
+//---------------------------------------------------------------------------------------------------- Internals
+// Inherent associated consts take priority over trait ones,
+// so the macros can set `READ_ONLY`/`WRITE_ONCE` at compile-time.
+//
+// `__ReadOnlyProbe::<T>::YES` resolves to the inherent `true`
+// if `ReadOnly` is implemented, else `__ReadOnlyNo::YES` (`false`).
+#[doc(hidden)]
+pub struct __ReadOnlyProbe<T>(std::marker::PhantomData<T>);
+
+impl<T: ReadOnly> __ReadOnlyProbe<T> {
+	pub const YES: bool = true;
+}
+
+#[doc(hidden)]
+pub trait __ReadOnlyNo {
+	const YES: bool = false;
+}
+
+impl<T> __ReadOnlyNo for __ReadOnlyProbe<T> {}
+
+#[doc(hidden)]
+pub struct __WriteOnceProbe<T>(std::marker::PhantomData<T>);
+
+impl<T: WriteOnce> __WriteOnceProbe<T> {
+	pub const YES: bool = true;
+}
+
+#[doc(hidden)]
+pub trait __WriteOnceNo {
+	const YES: bool = false;
+}
+
+impl<T> __WriteOnceNo for __WriteOnceProbe<T> {}
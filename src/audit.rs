@@ -0,0 +1,311 @@
+//---------------------------------------------------------------------------------------------------- Use
+use anyhow::bail;
+use serde::{Serialize,de::DeserializeOwned};
+use std::io::{BufReader,Read,Seek,SeekFrom,Write};
+use std::marker::PhantomData;
+use std::path::{Path,PathBuf};
+use crate::{common,record,Format};
+
+//---------------------------------------------------------------------------------------------------- AuditLog
+/// Append-only audit logging.
+///
+/// If your type implements this trait _and_ one of the `disk` traits via macro:
+/// - `append_audit(&record)` appends the record to an audit log next to the file, e.g. `config.toml.audit`
+/// - `rotate_audit(max_size)` moves a full audit log aside to a timestamped file, e.g. `config.toml-2024-06-01T12-00-00.audit`
+/// - `iter_audit()` replays all records, oldest first, across rotated logs and the current one
+///
+/// Records are never modified or removed by `disk`, removing old rotated logs is up to you.
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::json!(Config, Dir::Data, "disk_test", "audit", "config");
+/// #[derive(Serialize,Deserialize)]
+/// struct Config {
+///     volume: u8,
+/// }
+///
+/// #[derive(Serialize,Deserialize,PartialEq,Debug)]
+/// struct Change {
+///     user: String,
+///     volume: u8,
+/// }
+///
+/// impl disk::AuditLog for Config {
+///     type Record = Change;
+///     const FORMAT: Format = Format::Json;
+/// }
+///
+/// # let _ = Config::rm_sub();
+/// Config::append_audit(&Change { user: "alice".into(), volume: 50 }).unwrap();
+///
+/// // Roll the log over once it reaches 1 byte (it always will).
+/// assert!(Config::rotate_audit(1).unwrap().is_some());
+/// Config::append_audit(&Change { user: "bob".into(), volume: 80 }).unwrap();
+///
+/// let records: Vec<Change> = Config::iter_audit().unwrap().collect::<Result<_, _>>().unwrap();
+/// assert_eq!(records, [
+///     Change { user: "alice".into(), volume: 50 },
+///     Change { user: "bob".into(), volume: 80 },
+/// ]);
+/// # Config::rm_sub().unwrap();
+/// ```
+///
+/// ## Durability
+/// Each record is checksummed and `fsync()`'ed before `append_audit()` returns.
+///
+/// A record torn by a crash is ignored when reading, and removed by the next `append_audit()`.
+/// Any other corrupted record is returned as an error by `iter_audit()`.
+///
+/// `append_audit()` and `rotate_audit()` hold an exclusive lock on the log while writing,
+/// so multiple threads and processes can append to the same log at the same time.
+pub trait AuditLog: Sized {
+	/// A single audit record.
+	type Record: Serialize + DeserializeOwned;
+
+	/// The [`Format`] records are written in.
+	const FORMAT: Format;
+}
+
+//---------------------------------------------------------------------------------------------------- AuditRecords
+/// Iterator over all audit records of `T`, oldest first.
+///
+/// Created with `iter_audit()`, see [`AuditLog`].
+///
+/// Each item is a single record, or an error if a log could not be read or a record is corrupted.
+pub struct AuditRecords<T: AuditLog> {
+	// Logs left to read, oldest first.
+	files: std::vec::IntoIter<PathBuf>,
+	// The log currently being read, its PATH, length and the current offset.
+	current: Option<(BufReader<std::fs::File>, PathBuf, u64, u64)>,
+	done: bool,
+	_marker: PhantomData<fn() -> T>,
+}
+
+impl<T: AuditLog> AuditRecords<T> {
+	pub(crate) fn new(files: Vec<PathBuf>) -> Self {
+		Self { files: files.into_iter(), current: None, done: false, _marker: PhantomData }
+	}
+
+	// Open the next log, returns `false` if there are none left.
+	fn open_next(&mut self) -> Result<bool, anyhow::Error> {
+		let Some(path) = self.files.next() else { return Ok(false) };
+
+		let file = match std::fs::File::open(&path) {
+			Ok(f) => f,
+			// Rotated in-between listing and opening.
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(true),
+			Err(e) => return Err(e.into()),
+		};
+		let len = file.metadata()?.len();
+		let mut reader = BufReader::new(file);
+
+		if len != 0 {
+			let mut magic = [0; MAGIC_LEN as usize];
+			if len < MAGIC_LEN || reader.read_exact(&mut magic).is_err() || magic != MAGIC {
+				bail!("{:?} is not an audit log", path);
+			}
+		}
+
+		self.current = Some((reader, path, len, MAGIC_LEN.min(len)));
+		Ok(true)
+	}
+
+	// Read the next record of the current log, `None` at its end.
+	fn read_record(&mut self) -> Result<Option<T::Record>, anyhow::Error> {
+		let Some((reader, path, len, offset)) = self.current.as_mut() else { return Ok(None) };
+		let (len, start) = (*len, *offset);
+
+		// End of the log, or a record header torn by a crash.
+		if start + record::OVERHEAD > len {
+			return Ok(None);
+		}
+
+		let mut header = [0; 8];
+		reader.read_exact(&mut header)?;
+		let (record_len, crc) = record::split_header(header);
+		let end = start + record::OVERHEAD + u64::from(record_len);
+
+		// A record torn by a crash.
+		if end > len {
+			return Ok(None);
+		}
+
+		let mut bytes = vec![0; record_len as usize];
+		reader.read_exact(&mut bytes)?;
+		let mut trailer = [0; 4];
+		reader.read_exact(&mut trailer)?;
+
+		if common::crc32(&bytes) != crc || u32::from_le_bytes(trailer) != record_len {
+			// Only the last record can be torn by a crash.
+			if end == len {
+				return Ok(None);
+			}
+			bail!("{:?} has a corrupted record at offset {}", path, start);
+		}
+
+		*offset = end;
+		Ok(Some(T::FORMAT.from_bytes(&bytes)?))
+	}
+}
+
+impl<T: AuditLog> Iterator for AuditRecords<T> {
+	type Item = Result<T::Record, anyhow::Error>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while !self.done {
+			if self.current.is_none() {
+				match self.open_next() {
+					Ok(true) => continue,
+					Ok(false) => { self.done = true; return None; },
+					Err(e) => { self.done = true; return Some(Err(e)); },
+				}
+			}
+
+			match self.read_record() {
+				Ok(Some(record)) => return Some(Ok(record)),
+				Ok(None) => self.current = None,
+				Err(e) => { self.done = true; return Some(Err(e)); },
+			}
+		}
+
+		None
+	}
+}
+
+impl<T: AuditLog> std::fmt::Debug for AuditRecords<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("AuditRecords")
+			.field("files", &self.files.as_slice())
+			.field("current", &self.current.as_ref().map(|(_, path, _, offset)| (path, offset)))
+			.field("done", &self.done)
+			.finish()
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Private
+// The audit log file layout:
+//
+// | `MAGIC` |
+// | record | record | ...
+//
+// Records are framed with `crate::record`.
+const MAGIC: [u8; 8] = *b"diskaud\0";
+const MAGIC_LEN: u64 = 8;
+
+// The extension of audit logs.
+pub(crate) const EXT: &str = "audit";
+
+// The audit log PATH for the file at `path`.
+pub(crate) fn audit_path(path: &Path) -> PathBuf {
+	let mut audit = path.to_path_buf().into_os_string();
+	audit.push(".");
+	audit.push(EXT);
+	PathBuf::from(audit)
+}
+
+// Open (or create) the audit log at `path` and lock it exclusively.
+//
+// `rotate()` renames the log while holding the same lock, if that
+// happened while waiting for it, the new log at `path` is opened instead.
+fn open_locked(path: &Path) -> Result<std::fs::File, anyhow::Error> {
+	loop {
+		let file = std::fs::OpenOptions::new()
+			.read(true)
+			.write(true)
+			.create(true)
+			.truncate(false)
+			.open(path)?;
+		file.lock()?;
+
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::MetadataExt;
+			let (locked, current) = match (file.metadata(), std::fs::metadata(path)) {
+				(Ok(l), Ok(c)) => (l, c),
+				(_, Err(e)) if e.kind() == std::io::ErrorKind::NotFound => continue,
+				(Err(e), _) | (_, Err(e)) => return Err(e.into()),
+			};
+			if (locked.dev(), locked.ino()) != (current.dev(), current.ino()) {
+				continue;
+			}
+		}
+
+		return Ok(file);
+	}
+}
+
+// Append a record to the audit log at `path`.
+//
+// A record torn by a crash is removed first.
+//
+// Returns the audit log size in bytes.
+pub(crate) fn append<T: AuditLog>(path: &Path, record: &T::Record) -> Result<u64, anyhow::Error> {
+	let frame = record::encode(&T::FORMAT.to_bytes(record)?)?;
+
+	// Released when `file` is dropped.
+	let mut file = open_locked(path)?;
+	let len = file.metadata()?.len();
+
+	let offset = if len == 0 {
+		file.write_all(&MAGIC)?;
+		MAGIC_LEN
+	} else {
+		let mut magic = [0; MAGIC_LEN as usize];
+		if len < MAGIC_LEN || file.read_exact(&mut magic).is_err() || magic != MAGIC {
+			bail!("{:?} is not an audit log", path);
+		}
+
+		// Drop the torn record.
+		let offset = record::valid_end(&mut file, MAGIC_LEN, len)?;
+		if offset != len {
+			file.set_len(offset)?;
+		}
+		offset
+	};
+
+	file.seek(SeekFrom::Start(offset))?;
+	file.write_all(&frame)?;
+	file.sync_data()?;
+
+	Ok(offset + frame.len() as u64)
+}
+
+// Move the audit log at `path` (of the file `file_name` in `dir`)
+// to a timestamped file if it is at least `max_size` bytes.
+//
+// Returns the size and PATH of the rotated log.
+pub(crate) fn rotate(dir: &Path, file_name: &str, path: &Path, max_size: u64) -> Result<Option<(u64, PathBuf)>, anyhow::Error> {
+	if !path.try_exists()? {
+		return Ok(None);
+	}
+
+	// Don't rename the log while a record is being appended.
+	let file = open_locked(path)?;
+	let len  = file.metadata()?.len();
+
+	// Nothing worth rotating.
+	if len <= MAGIC_LEN || len < max_size {
+		return Ok(None);
+	}
+
+	let rotated = crate::history::free_history_path(dir, file_name, EXT, std::time::SystemTime::now());
+	std::fs::rename(path, &rotated)?;
+	Ok(Some((len, rotated)))
+}
+
+// All audit logs of the file `file_name` in `dir`, oldest first.
+//
+// This is the rotated logs, then the current one at `path` (if it exists).
+pub(crate) fn files(dir: &Path, file_name: &str, path: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+	let mut files: Vec<PathBuf> = crate::history::history(dir, file_name, EXT)?
+		.into_iter()
+		.map(|(_, path, _)| path)
+		.collect();
+
+	if path.try_exists()? {
+		files.push(path.to_path_buf());
+	}
+
+	Ok(files)
+}
@@ -36,44 +36,103 @@ pub unsafe trait Bincode: serde::Serialize + serde::de::DeserializeOwned {
 	#[inline(always)]
 	/// Internal function. Most efficient `from_file()` impl.
 	fn __from_file() -> Result <Self, anyhow::Error> {
-		let path = Self::absolute_path()?;
-		let mut file = std::fs::File::open(path)?;
-		Self::from_reader(&mut file)
+		let file = common::file_read(&Self::absolute_path()?)?;
+		Self::raw_from_reader(&mut BufReader::new(file))
 	}
 
 	#[doc(hidden)]
 	#[inline(always)]
 	/// Internal function. Most efficient `from_path()` impl.
 	fn __from_path(path: &std::path::Path) -> Result <Self, anyhow::Error> {
-		let mut file = std::fs::File::open(path)?;
-		Self::from_reader(&mut file)
+		let file = common::file_read(path)?;
+		Self::raw_from_reader(&mut BufReader::new(file))
 	}
 
 	#[inline(always)]
 	/// Create a [`Self`] from bytes.
 	fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
 		ensure_header!(bytes);
-		Ok(ENCODING_OPTIONS.deserialize(&bytes[25..])?)
+		match crate::decode_limit() {
+			Some(limit) => Ok(ENCODING_OPTIONS.with_limit(limit).deserialize(&bytes[Self::header_len()..])?),
+			None => Ok(ENCODING_OPTIONS.deserialize(&bytes[Self::header_len()..])?),
+		}
 	}
 
 	#[inline(always)]
 	/// Convert [`Self`] to bytes.
 	fn to_bytes(&self) -> Result<Vec<u8>, anyhow::Error> {
-		let mut vec = ENCODING_OPTIONS.serialize(self)?;
+		Self::__to_bytes(self)
+	}
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. Serialize any `value` the same way as [`Self::to_bytes()`].
+	fn __to_bytes<V: serde::Serialize>(value: &V) -> Result<Vec<u8>, anyhow::Error> {
+		let mut vec = ENCODING_OPTIONS.serialize(value)?;
 		header_return!(vec)
 	}
 
 	#[inline(always)]
 	/// Create [`Self`] directly from reader `R`.
+	///
+	/// `reader` is wrapped in a [`BufReader`], if it is already buffered
+	/// (or in-memory, e.g. `&[u8]`), use [`Self::raw_from_reader()`] instead.
 	fn from_reader<R>(reader: &mut R) -> Result<Self, anyhow::Error>
 		where
 			R: Read,
 	{
-		let mut bytes = [0_u8; 25];
-		let mut reader = BufReader::new(reader);
-		reader.read_exact(&mut bytes)?;
+		Self::raw_from_reader(&mut BufReader::new(reader))
+	}
+
+	#[inline(always)]
+	/// Same as [`Self::from_reader()`], but `reader` is used as-is, without buffering.
+	///
+	/// Deserialization does many small reads, so `reader` should already be buffered.
+	fn raw_from_reader<R>(reader: &mut R) -> Result<Self, anyhow::Error>
+		where
+			R: Read,
+	{
+		let mut bytes = [0_u8; 28];
+		let bytes = &mut bytes[..Self::header_len()];
+		reader.read_exact(bytes)?;
 		ensure_header!(bytes);
-		Ok(ENCODING_OPTIONS.deserialize_from(&mut reader)?)
+		match crate::decode_limit() {
+			Some(limit) => Ok(ENCODING_OPTIONS.with_limit(limit).deserialize_from(reader)?),
+			None => Ok(ENCODING_OPTIONS.deserialize_from(reader)?),
+		}
+	}
+
+	/// Same as `from_file()`, but reads through a [`BufReader`] with a capacity of `capacity` bytes.
+	///
+	/// The default is `8 KiB`, a larger buffer means fewer `read()` calls for large files.
+	fn from_file_with_capacity(capacity: usize) -> Result<Self, anyhow::Error> {
+		crate::common::traced!("from_file_with_capacity", "bincode", load, {
+			let file = common::file_read(&Self::absolute_path()?)?;
+			let mut s = Self::raw_from_reader(&mut BufReader::with_capacity(capacity, file))?;
+			s.__journal()?;
+			s.__validate()?;
+			Ok(s)
+		})
+	}
+
+	#[inline(always)]
+	/// Convert [`Self`] directly to the given [`slice`].
+	///
+	/// The amount of bytes written is returned on success.
+	///
+	/// ## Error
+	/// The slice must be at least [`Self::header_len()`] bytes in length to holder the header
+	/// bytes and must be large enough to hold the resulting serialized bytes.
+	fn to_slice(&self, slice: &mut [u8]) -> Result<usize, anyhow::Error> {
+		let len = slice.len();
+		let header = Self::header_bytes();
+		if len < header.len() {
+			bail!("input slice length less than {}: {len}", header.len());
+		}
+		slice[..header.len()].copy_from_slice(&header);
+
+		let mut writer = &mut slice[header.len()..];
+		ENCODING_OPTIONS.serialize_into(&mut writer, self)?;
+		Ok(len - writer.len())
 	}
 
 	#[inline(always)]
@@ -82,9 +141,10 @@ pub unsafe trait Bincode: serde::Serialize + serde::de::DeserializeOwned {
 		where
 			W: Write,
 	{
-		let mut writer = BufWriter::new(writer);
-		writer.write_all(&Self::full_header())?;
-		Ok(ENCODING_OPTIONS.serialize_into(&mut writer, self)?)
+		let mut writer = BufWriter::with_capacity(Self::WRITE_BUF_SIZE, writer);
+		writer.write_all(&Self::header_bytes())?;
+		ENCODING_OPTIONS.serialize_into(&mut writer, self)?;
+		Ok(writer.flush()?)
 	}
 
 	impl_header!();
@@ -16,7 +16,141 @@ use once_cell::sync::Lazy;
 //---------------------------------------------------------------------------------------------------- Bincode
 static ENCODING_OPTIONS: Lazy<Configuration> = Lazy::new(bincode2::config::standard);
 
-crate::common::impl_macro_binary!(Bincode2, "bin");
+crate::common::impl_macro_binary!(Bincode2, "bin", {
+	($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, $header:expr, $version:expr, serde) => {
+		$crate::bincode2!($data, $dir, $project_directory, $sub_directories, $file_name, $header, $version);
+		$crate::__bincode2_serde!($data);
+	};
+	($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, $header:expr, $version:expr, ext = $ext:expr, serde) => {
+		$crate::bincode2!($data, $dir, $project_directory, $sub_directories, $file_name, $header, $version, ext = $ext);
+		$crate::__bincode2_serde!($data);
+	};
+	($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, $header:expr, $version:expr, config = $config:expr $(, $serde:ident)?) => {
+		$crate::bincode2!($data, $dir, $project_directory, $sub_directories, $file_name, $header, $version, ext = "bin", config = $config $(, $serde)?);
+	};
+	($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, $header:expr, $version:expr, ext = $ext:expr, config = $config:expr, serde) => {
+		$crate::bincode2!($data, $dir, $project_directory, $sub_directories, $file_name, $header, $version, ext = $ext, config = $config);
+		$crate::__bincode2_serde!($data);
+	};
+	($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, $header:expr, $version:expr, ext = $ext:expr, config = $config:expr) => {
+		$crate::assert_str!($project_directory, $sub_directories, $file_name, $ext);
+		$crate::assert_dir!($dir);
+		$crate::assert_ext!($ext);
+
+		// SAFETY: The input to this `Bincode2` implementation was verified and sanity-checked via macro.
+		unsafe impl $crate::Bincode2 for $data {
+			const OS_DIRECTORY:       $crate::Dir  = $dir;
+			const PROJECT_DIRECTORY:  &'static str = $project_directory;
+			const SUB_DIRECTORIES:    &'static str = $sub_directories;
+			const FILE:               &'static str = $file_name;
+			const FILE_EXT:           &'static str = $ext;
+			const FILE_NAME:          &'static str = $crate::const_format!("{}.{}", $file_name, $ext);
+			const FILE_NAME_GZIP:     &'static str = $crate::const_format!("{}.{}.gz", $file_name, $ext);
+			const FILE_NAME_TMP:      &'static str = $crate::const_format!("{}.{}.tmp", $file_name, $ext);
+			const FILE_NAME_GZIP_TMP: &'static str = $crate::const_format!("{}.{}.gz.tmp", $file_name, $ext);
+			const READ_ONLY:          bool         = { #[allow(unused_imports)] use $crate::__ReadOnlyNo; $crate::__ReadOnlyProbe::<Self>::YES };
+			const WRITE_ONCE:         bool         = { #[allow(unused_imports)] use $crate::__WriteOnceNo; $crate::__WriteOnceProbe::<Self>::YES };
+			const HEADER:             [u8; 24]     = $header;
+			const VERSION:            u8           = $version;
+			const SEMVER:             Option<(u8, u8, u8)> = { #[allow(unused_imports)] use $crate::__SemverNo; $crate::__SemverProbe::<Self>::SEMVER };
+
+			#[inline(always)]
+			fn __validate(&self) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__ValidateYes,__ValidateNo};
+				(&$crate::__ValidateProbe(self)).__disk_validate()
+			}
+
+			#[inline(always)]
+			fn __journal(&mut self) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__JournalYes,__JournalNo};
+				(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal(self, Self::absolute_path)
+			}
+
+			#[inline(always)]
+			fn __journal_reset(metadata: &$crate::Metadata) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__JournalYes,__JournalNo};
+				(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal_reset(metadata, Self::absolute_path)
+			}
+
+			$crate::__bincode2_config!($config);
+		}
+	};
+});
+
+#[doc(hidden)]
+#[macro_export]
+/// Implement the `bincode` encode/decode functions of [`Bincode2`] with the configuration `$config`.
+macro_rules! __bincode2_config {
+	($config:expr) => {
+		#[doc(hidden)]
+		#[inline(always)]
+		/// Internal function. Encode with this type's configuration.
+		fn __encode_to_vec(&self) -> ::std::result::Result<::std::vec::Vec<u8>, $crate::__bincode2::error::EncodeError> {
+			$crate::__bincode2::encode_to_vec(self, $config)
+		}
+
+		#[doc(hidden)]
+		#[inline(always)]
+		/// Internal function. Encode any `value` with this type's configuration.
+		fn __encode_value_to_vec<V: $crate::__bincode2::Encode>(value: &V) -> ::std::result::Result<::std::vec::Vec<u8>, $crate::__bincode2::error::EncodeError> {
+			$crate::__bincode2::encode_to_vec(value, $config)
+		}
+
+		#[doc(hidden)]
+		#[inline(always)]
+		/// Internal function. Encode with this type's configuration.
+		fn __encode_into_slice(&self, slice: &mut [u8]) -> ::std::result::Result<usize, $crate::__bincode2::error::EncodeError> {
+			$crate::__bincode2::encode_into_slice(self, slice, $config)
+		}
+
+		#[doc(hidden)]
+		#[inline(always)]
+		/// Internal function. Encode with this type's configuration.
+		fn __encode_into_std_write<W: ::std::io::Write>(&self, writer: &mut W) -> ::std::result::Result<usize, $crate::__bincode2::error::EncodeError> {
+			$crate::__bincode2::encode_into_std_write(self, writer, $config)
+		}
+
+		#[doc(hidden)]
+		#[inline(always)]
+		/// Internal function. Decode with this type's configuration.
+		fn __decode_from_slice(bytes: &[u8]) -> ::std::result::Result<(Self, usize), $crate::__bincode2::error::DecodeError> {
+			$crate::__bincode2::decode_from_slice(bytes, $config)
+		}
+
+		#[doc(hidden)]
+		#[inline(always)]
+		/// Internal function. Decode with this type's configuration.
+		fn __decode_from_std_read<R: ::std::io::Read>(reader: &mut R) -> ::std::result::Result<Self, $crate::__bincode2::error::DecodeError> {
+			$crate::__bincode2::decode_from_std_read(reader, $config)
+		}
+	};
+}
+
+#[doc(hidden)]
+#[macro_export]
+/// Implement `bincode`'s `Encode` and `Decode` for a `serde` type, via `bincode::serde::Compat`.
+macro_rules! __bincode2_serde {
+	($data:ty) => {
+		impl $crate::__bincode2::Encode for $data {
+			#[inline]
+			fn encode<E: $crate::__bincode2::enc::Encoder>(&self, encoder: &mut E) -> ::std::result::Result<(), $crate::__bincode2::error::EncodeError> {
+				$crate::__bincode2::Encode::encode(&$crate::__bincode2::serde::Compat(self), encoder)
+			}
+		}
+
+		impl $crate::__bincode2::Decode for $data {
+			#[inline]
+			fn decode<D: $crate::__bincode2::de::Decoder>(decoder: &mut D) -> ::std::result::Result<Self, $crate::__bincode2::error::DecodeError> {
+				<$crate::__bincode2::serde::Compat<$data> as $crate::__bincode2::Decode>::decode(decoder).map(|c| c.0)
+			}
+		}
+
+		$crate::__bincode2::impl_borrow_decode!($data);
+	};
+}
 
 /// [`Bincode2`](https://docs.rs/bincode/2.0.0-rc.3) (`2.x.x-rc.x`) (binary) file format
 ///
@@ -39,40 +173,71 @@ crate::common::impl_macro_binary!(Bincode2, "bin");
 /// ```
 /// and add `#[derive(Encode, Decode)]` to your types, like you would with `serde`.
 ///
+/// ## `serde`
+/// Types that only implement `serde`'s `Serialize` and `Deserialize` can
+/// pass a trailing `serde` argument to [`crate::bincode2!`] instead:
+/// ```rust,ignore
+/// use serde::{Serialize, Deserialize};
+///
+/// disk::bincode2!(State, disk::Dir::Data, "MyProject", "", "state", HEADER, VERSION, serde);
+/// #[derive(Serialize, Deserialize)]
+/// struct State;
+/// ```
+/// This implements `Encode` and `Decode` for you, using `bincode`'s `serde` compatibility layer.
+///
+/// This is slower than deriving `Encode` and `Decode` directly and the bytes
+/// may differ, so don't switch an existing type between the two.
+///
 /// ## Encoding
 /// The encoding option used is:
 /// ```txt
 /// bincode::config::standard()
 /// ```
 ///
+/// This can be changed per type with a trailing `config = ...` argument to [`crate::bincode2!`]:
+/// ```rust,ignore
+/// use bincode::config::standard;
+///
+/// // Fixed-length, little-endian integers, decoding at most 1 MiB.
+/// disk::bincode2!(
+///     State, disk::Dir::Data, "MyProject", "", "state", HEADER, VERSION,
+///     config = standard().with_fixed_int_encoding().with_little_endian().with_limit::<1_048_576>()
+/// );
+/// ```
+/// Bytes saved with one configuration can not be read with another.
+///
 /// File extension is `.bin`.
 ///
 /// ## Safety
 /// When manually implementing, you are **promising** that the `PATH`'s manually specified are correct.
 pub unsafe trait Bincode2: bincode2::Encode + bincode2::Decode {
+	crate::__bincode2_config!(*ENCODING_OPTIONS);
+
 	#[doc(hidden)]
 	#[inline(always)]
 	/// Internal function. Most efficient `from_file()` impl.
 	fn __from_file() -> Result <Self, anyhow::Error> {
-		let path = Self::absolute_path()?;
-		let mut file = std::fs::File::open(path)?;
-		Self::from_reader(&mut file)
+		let file = common::file_read(&Self::absolute_path()?)?;
+		crate::limit::ensure_len(file.metadata()?.len())?;
+		Self::raw_from_reader(&mut BufReader::new(file))
 	}
 
 	#[doc(hidden)]
 	#[inline(always)]
 	/// Internal function. Most efficient `from_path()` impl.
 	fn __from_path(path: &std::path::Path) -> Result <Self, anyhow::Error> {
-		let mut file = std::fs::File::open(path)?;
-		Self::from_reader(&mut file)
+		let file = common::file_read(path)?;
+		crate::limit::ensure_len(file.metadata()?.len())?;
+		Self::raw_from_reader(&mut BufReader::new(file))
 	}
 
 	#[inline(always)]
 	/// Create [`Self`] from bytes.
 	fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
 		ensure_header!(bytes);
+		crate::limit::ensure_len(bytes.len() as u64)?;
 
-		match bincode2::decode_from_slice(&bytes[25..], *ENCODING_OPTIONS) {
+		match Self::__decode_from_slice(&bytes[Self::header_len()..]) {
 			Ok((s, _))  => Ok(s),
 			Err(e) => Err(e)?,
 		}
@@ -81,7 +246,18 @@ pub unsafe trait Bincode2: bincode2::Encode + bincode2::Decode {
 	#[inline(always)]
 	/// Convert [`Self`] to bytes.
 	fn to_bytes(&self) -> Result<Vec<u8>, anyhow::Error> {
-		let mut vec = match bincode2::encode_to_vec(self, *ENCODING_OPTIONS) {
+		let mut vec = match self.__encode_to_vec() {
+			Ok(v)  => v,
+			Err(e) => Err(e)?,
+		};
+
+		header_return!(vec)
+	}
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. Serialize any `value` the same way as [`Self::to_bytes()`].
+	fn __to_bytes<V: bincode2::Encode>(value: &V) -> Result<Vec<u8>, anyhow::Error> {
+		let mut vec = match Self::__encode_value_to_vec(value) {
 			Ok(v)  => v,
 			Err(e) => Err(e)?,
 		};
@@ -91,15 +267,42 @@ pub unsafe trait Bincode2: bincode2::Encode + bincode2::Decode {
 
 	#[inline(always)]
 	/// Create [`Self`] directly from reader `R`.
+	///
+	/// `reader` is wrapped in a [`BufReader`], if it is already buffered
+	/// (or in-memory, e.g. `&[u8]`), use [`Self::raw_from_reader()`] instead.
 	fn from_reader<R>(reader: &mut R) -> Result<Self, anyhow::Error>
 		where
 			R: Read,
 	{
-		let mut bytes = [0_u8; 25];
-		let mut reader = BufReader::new(reader);
-		reader.read_exact(&mut bytes)?;
+		Self::raw_from_reader(&mut BufReader::new(reader))
+	}
+
+	#[inline(always)]
+	/// Same as [`Self::from_reader()`], but `reader` is used as-is, without buffering.
+	///
+	/// Deserialization does many small reads, so `reader` should already be buffered.
+	fn raw_from_reader<R>(reader: &mut R) -> Result<Self, anyhow::Error>
+		where
+			R: Read,
+	{
+		let mut bytes = [0_u8; 28];
+		let bytes = &mut bytes[..Self::header_len()];
+		reader.read_exact(bytes)?;
 		ensure_header!(bytes);
-		Ok(bincode2::decode_from_std_read(&mut reader, *ENCODING_OPTIONS)?)
+		Ok(Self::__decode_from_std_read(&mut crate::limit::take(reader))?)
+	}
+
+	/// Same as `from_file()`, but reads through a [`BufReader`] with a capacity of `capacity` bytes.
+	///
+	/// The default is `8 KiB`, a larger buffer means fewer `read()` calls for large files.
+	fn from_file_with_capacity(capacity: usize) -> Result<Self, anyhow::Error> {
+		crate::common::traced!("from_file_with_capacity", "bincode2", load, {
+			let file = common::file_read(&Self::absolute_path()?)?;
+			let mut s = Self::raw_from_reader(&mut BufReader::with_capacity(capacity, file))?;
+			s.__journal()?;
+			s.__validate()?;
+			Ok(s)
+		})
 	}
 
 	#[inline(always)]
@@ -108,15 +311,16 @@ pub unsafe trait Bincode2: bincode2::Encode + bincode2::Decode {
 	/// The amount of bytes written is returned on success.
 	///
 	/// ## Error
-	/// The slice must be at least `25` bytes in length to holder the header
+	/// The slice must be at least [`Self::header_len()`] bytes in length to holder the header
 	/// bytes and must be large enough to hold the resulting serialized bytes.
 	fn to_slice(&self, slice: &mut [u8]) -> Result<usize, anyhow::Error> {
 		let len = slice.len();
-		if len < 25 {
-			bail!("input slice length less than 25: {len}");
+		let header = Self::header_bytes();
+		if len < header.len() {
+			bail!("input slice length less than {}: {len}", header.len());
 		}
-		slice[..25].copy_from_slice(&Self::full_header());
-		Ok(bincode2::encode_into_slice(self, &mut slice[25..], *ENCODING_OPTIONS)?)
+		slice[..header.len()].copy_from_slice(&header);
+		Ok(self.__encode_into_slice(&mut slice[header.len()..])?)
 	}
 
 	#[inline(always)]
@@ -127,13 +331,15 @@ pub unsafe trait Bincode2: bincode2::Encode + bincode2::Decode {
 		where
 			W: Write,
 	{
-		let mut writer = BufWriter::new(writer);
-		writer.write_all(&Self::full_header())?;
-		Ok(bincode2::encode_into_std_write(self, &mut writer, *ENCODING_OPTIONS)?)
+		let mut writer = BufWriter::with_capacity(Self::WRITE_BUF_SIZE, writer);
+		writer.write_all(&Self::header_bytes())?;
+		let len = self.__encode_into_std_write(&mut writer)?;
+		writer.flush()?;
+		Ok(len)
 	}
 
 	impl_header!();
-	common::impl_binary!("bincode2");
+	common::impl_binary!("bincode2", bincode2::Encode);
 }
 
 //---------------------------------------------------------------------------------------------------- TESTS
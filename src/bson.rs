@@ -2,7 +2,8 @@
 use anyhow::{anyhow,bail};
 use std::path::PathBuf;
 use crate::common;
-use std::io::BufReader;
+use std::io::{BufReader,Read,Write};
+use std::marker::PhantomData;
 //use log::{info,error,warn,trace,debug};
 //use serde::{Serialize,Deserialize};
 
@@ -22,6 +23,7 @@ pub unsafe trait Bson: serde::Serialize + serde::de::DeserializeOwned {
 	fn __from_file() -> Result<Self, anyhow::Error> {
 		let path = Self::absolute_path()?;
 		let file = std::fs::File::open(path)?;
+		crate::limit::ensure_len(file.metadata()?.len())?;
 		Ok(bson::from_reader(BufReader::new(file))?)
 	}
 
@@ -30,25 +32,149 @@ pub unsafe trait Bson: serde::Serialize + serde::de::DeserializeOwned {
 	/// Internal function. Most efficient `from_path()` impl.
 	fn __from_path(path: &std::path::Path) -> Result<Self, anyhow::Error> {
 		let file = std::fs::File::open(path)?;
+		crate::limit::ensure_len(file.metadata()?.len())?;
 		Ok(bson::from_reader(BufReader::new(file))?)
 	}
 
 	#[inline(always)]
 	/// Create [`Self`] from bytes.
 	fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+		crate::limit::ensure_len(bytes.len() as u64)?;
 		Ok(bson::from_slice(bytes)?)
 	}
 
 	#[inline(always)]
 	/// Convert [`Self`] to bytes.
 	fn to_bytes(&self) -> Result<Vec<u8>, anyhow::Error> {
-		Ok(bson::to_vec(self)?)
+		Self::__to_bytes(self)
+	}
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. Serialize any `value` the same way as [`Self::to_bytes()`].
+	fn __to_bytes<V: serde::Serialize>(value: &V) -> Result<Vec<u8>, anyhow::Error> {
+		Ok(bson::to_vec(value)?)
+	}
+
+	// Document stream.
+	/// Append [`Self`] as a single document to the end of the file.
+	///
+	/// This treats the file as a sequence of length-prefixed BSON documents
+	/// (the `mongodump` layout), read them back with [`Self::iter_documents()`].
+	///
+	/// The file is created if it does not exist, existing documents are never rewritten.
+	///
+	/// On success, this returns:
+	/// - The total file size in bytes
+	/// - The [`PathBuf`] it's located at
+	///
+	/// Calling this will automatically create the directories leading up to the file.
+	fn append_document(&self) -> Result<crate::Metadata, anyhow::Error> {
+		crate::common::assert_writable!();
+		crate::common::traced!("append_document", "bson", save, {
+			self.__validate()?;
+			let bytes = self.to_bytes()?;
+
+			// The whole document is written at once, at the end of the file.
+			let mut file = Self::open_append()?;
+			file.write_all(&bytes)?;
+
+			Ok(crate::Metadata::new(file.metadata()?.len(), Self::absolute_path()?))
+		})
+	}
+
+	/// Iterate over all documents in the file, see [`Self::append_document()`].
+	///
+	/// Each document is read and deserialized lazily,
+	/// after an error is returned, the iterator ends.
+	///
+	/// ```rust,ignore
+	/// for record in Record::iter_documents()? {
+	///     println!("{:?}", record?);
+	/// }
+	/// ```
+	fn iter_documents() -> Result<BsonDocuments<Self>, anyhow::Error> {
+		let file = common::file_read(&Self::absolute_path()?)?;
+		Ok(BsonDocuments {
+			reader: BufReader::new(file),
+			done: false,
+			_marker: PhantomData,
+		})
 	}
 
 	// Common data/functions.
 	common::impl_binary!("bson");
 }
 
+//---------------------------------------------------------------------------------------------------- BsonDocuments
+/// An iterator over the documents of a [`Bson`] file.
+///
+/// This is returned by [`Bson::iter_documents()`].
+pub struct BsonDocuments<T> {
+	reader: BufReader<std::fs::File>,
+	done: bool,
+	_marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Bson> BsonDocuments<T> {
+	// Read the next document, `None` on a clean EOF.
+	fn read_document(&mut self) -> Result<Option<T>, anyhow::Error> {
+		// Length prefix, this includes itself.
+		let mut prefix = [0_u8; 4];
+		let mut read = 0;
+		while read < 4 {
+			match self.reader.read(&mut prefix[read..]) {
+				Ok(0) if read == 0 => return Ok(None),
+				Ok(0) => bail!("truncated document length, {read}/4 bytes"),
+				Ok(n) => read += n,
+				Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+				Err(e) => return Err(e.into()),
+			}
+		}
+
+		let len = i32::from_le_bytes(prefix);
+		if len < 5 {
+			bail!("invalid document length: {len}");
+		}
+		crate::limit::ensure_len(len as u64)?;
+
+		let mut bytes = vec![0_u8; len as usize];
+		bytes[..4].copy_from_slice(&prefix);
+		self.reader.read_exact(&mut bytes[4..]).map_err(|e| anyhow!("truncated document: {e}"))?;
+
+		Ok(Some(T::from_bytes(&bytes)?))
+	}
+}
+
+impl<T: Bson> Iterator for BsonDocuments<T> {
+	type Item = Result<T, anyhow::Error>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.done {
+			return None;
+		}
+
+		match self.read_document() {
+			Ok(Some(t)) => Some(Ok(t)),
+			Ok(None) => {
+				self.done = true;
+				None
+			},
+			Err(e) => {
+				self.done = true;
+				Some(Err(e))
+			},
+		}
+	}
+}
+
+impl<T> std::fmt::Debug for BsonDocuments<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("BsonDocuments")
+			.field("done", &self.done)
+			.finish_non_exhaustive()
+	}
+}
+
 //---------------------------------------------------------------------------------------------------- TESTS
 //#[cfg(test)]
 //mod tests {
@@ -0,0 +1,74 @@
+//---------------------------------------------------------------------------------------------------- Use
+use std::io::Read;
+
+//---------------------------------------------------------------------------------------------------- Chunks
+/// An iterator over the bytes of a file, in chunks.
+///
+/// This is returned by `file_chunks()` and `file_chunks_gzip()`.
+///
+/// Each item is a [`Vec`] of `chunk_size` length,
+/// except for the last chunk, which may be shorter.
+///
+/// After an error is returned, the iterator ends.
+///
+/// ```rust,ignore
+/// for chunk in State::file_chunks(64 * 1024)? {
+///     hasher.update(&chunk?);
+/// }
+/// ```
+pub struct Chunks {
+	reader: Box<dyn Read + Send>,
+	chunk_size: usize,
+	done: bool,
+}
+
+impl Chunks {
+	/// Create a new [`Chunks`].
+	pub(crate) fn new(reader: Box<dyn Read + Send>, chunk_size: usize) -> Self {
+		Self { reader, chunk_size, done: false }
+	}
+
+	/// Returns the chunk size.
+	pub const fn chunk_size(&self) -> usize {
+		self.chunk_size
+	}
+}
+
+impl Iterator for Chunks {
+	type Item = Result<Vec<u8>, anyhow::Error>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.done {
+			return None;
+		}
+
+		let mut buf = Vec::with_capacity(self.chunk_size);
+
+		match (&mut self.reader).take(self.chunk_size as u64).read_to_end(&mut buf) {
+			Ok(0) => {
+				self.done = true;
+				None
+			},
+			Ok(n) => {
+				// A short read means EOF.
+				if n < self.chunk_size {
+					self.done = true;
+				}
+				Some(Ok(buf))
+			},
+			Err(e) => {
+				self.done = true;
+				Some(Err(e.into()))
+			},
+		}
+	}
+}
+
+impl std::fmt::Debug for Chunks {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Chunks")
+			.field("chunk_size", &self.chunk_size)
+			.field("done", &self.done)
+			.finish_non_exhaustive()
+	}
+}
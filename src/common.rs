@@ -3,8 +3,15 @@ use anyhow::{anyhow,bail,Error};
 use directories::ProjectDirs;
 
 use std::path::{Path,PathBuf};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use once_cell::sync::Lazy;
 use crate::Dir;
 
+//---------------------------------------------------------------------------------------------------- Constants.
+// The first 2 bytes of every `gzip` file.
+pub(crate) const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
 //---------------------------------------------------------------------------------------------------- Common Functions.
 #[inline(always)]
 // Create the `ProjectDirs` struct from a project name.
@@ -15,19 +22,157 @@ pub(crate) fn base(project_name: &str) -> Result<ProjectDirs, Error> {
 	}
 }
 
+// Runtime overrides of `PROJECT_DIRECTORY`, keyed by the compile-time value.
+static PROJECT_NAMES: Lazy<RwLock<HashMap<String, String>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+// Override the project directory `default` with `name`.
+pub(crate) fn set_project_name(default: &str, name: &str) -> Result<(), Error> {
+	if let Err(e) = crate::path::validate_project_directory(name) {
+		bail!(e);
+	}
+	PROJECT_NAMES.write().unwrap_or_else(|e| e.into_inner()).insert(default.into(), name.into());
+	Ok(())
+}
+
+// Remove the override of the project directory `default`, if any.
+pub(crate) fn reset_project_name(default: &str) {
+	PROJECT_NAMES.write().unwrap_or_else(|e| e.into_inner()).remove(default);
+}
+
+// The project directory name in use for `default`, after overrides.
+pub(crate) fn project_name(default: &str) -> String {
+	match PROJECT_NAMES.read().unwrap_or_else(|e| e.into_inner()).get(default) {
+		Some(name) => name.clone(),
+		None       => default.into(),
+	}
+}
+
 // Get the absolute OS + Project PATH.
 pub(crate) fn get_projectdir(dir: &Dir, project_name: &str) -> Result<PathBuf, Error> {
+	let mut path = get_projectdir_unversioned(dir, project_name)?;
+	crate::version_dir::push_version_dir(&mut path);
+	Ok(path)
+}
+
+// The system-wide (read-only) project directories for `dir`, most important first.
+//
+// | Platform | `Config`, `Preference`                 | `Data`, `DataLocal`                    |
+// |----------|----------------------------------------|----------------------------------------|
+// | Linux    | `$XDG_CONFIG_DIRS` or `/etc/xdg`       | `$XDG_DATA_DIRS` or `/usr/local/share:/usr/share`
+// | macOS    | `/Library/Preferences` for `Preference`, else `/Library/Application Support`
+// | Windows  | `{FOLDERID_ProgramData}`\_project_\config | `{FOLDERID_ProgramData}`\_project_\data
+//
+// `Project`, `Cache`, `Runtime` and `Path` have none.
+pub(crate) fn get_system_projectdirs(dir: &Dir, project_name: &str) -> Result<Vec<PathBuf>, Error> {
+	if let Dir::Path(_) = dir {
+		return Ok(vec![]);
+	}
+
+	// Only the project part, e.g. `\Foo Corp\Bar App` on Windows would replace the root with `join()`.
+	let project: PathBuf = base(&self::project_name(project_name))?
+		.project_path()
+		.components()
+		.filter(|c| matches!(c, std::path::Component::Normal(_)))
+		.collect();
+
+	#[cfg(all(unix, not(target_os = "macos")))]
+	let roots = {
+		// <https://specifications.freedesktop.org/basedir-spec/latest>
+		let (var, default) = match dir {
+			Dir::Config | Dir::Preference => ("XDG_CONFIG_DIRS", "/etc/xdg"),
+			Dir::Data | Dir::DataLocal    => ("XDG_DATA_DIRS", "/usr/local/share/:/usr/share/"),
+			Dir::Project | Dir::Cache | Dir::Runtime | Dir::Path(_) => return Ok(vec![]),
+		};
+		let dirs = std::env::var_os(var).filter(|v| !v.is_empty()).unwrap_or_else(|| default.into());
+		// Relative PATHs are invalid and must be ignored.
+		std::env::split_paths(&dirs).filter(|p| p.is_absolute()).collect::<Vec<PathBuf>>()
+	};
+
+	#[cfg(target_os = "macos")]
+	let roots = match dir {
+		Dir::Preference => vec![PathBuf::from("/Library/Preferences")],
+		Dir::Config | Dir::Data | Dir::DataLocal => vec![PathBuf::from("/Library/Application Support")],
+		Dir::Project | Dir::Cache | Dir::Runtime | Dir::Path(_) => return Ok(vec![]),
+	};
+
+	#[cfg(target_os = "windows")]
+	{
+		let leaf = match dir {
+			Dir::Config | Dir::Preference => "config",
+			Dir::Data | Dir::DataLocal    => "data",
+			Dir::Project | Dir::Cache | Dir::Runtime | Dir::Path(_) => return Ok(vec![]),
+		};
+		return Ok(std::env::var_os("ProgramData")
+			.map(|p| vec![PathBuf::from(p).join(&project).join(leaf)])
+			.unwrap_or_default());
+	}
+
+	#[cfg(not(target_os = "windows"))]
+	Ok(roots.into_iter().map(|root| root.join(&project)).collect())
+}
+
+// Make sure `path` (a `get_projectdir()` PATH) is the project directory of `expected`.
+//
+// This is the whole PATH `get_projectdir()` would return for `expected`
+// (including the current profile and version directory), without any project name override.
+pub(crate) fn confirm_projectdir(dir: &Dir, path: &Path, expected: &str) -> Result<(), Error> {
+	if let Err(e) = crate::path::validate_project_directory(expected) {
+		bail!(e);
+	}
+
+	let mut project = projectdir(dir, expected)?;
+	crate::version_dir::push_version_dir(&mut project);
+
+	if path != project {
+		bail!("disk: refusing to remove {path:?}, it is not the project directory of '{expected}' ({project:?})");
+	}
+
+	Ok(())
+}
+
+// Same as `get_projectdir()`, without the version directory.
+pub(crate) fn get_projectdir_unversioned(dir: &Dir, project_name: &str) -> Result<PathBuf, Error> {
+	projectdir(dir, &self::project_name(project_name))
+}
+
+// Same as `get_projectdir_unversioned()`, without project name overrides.
+fn projectdir(dir: &Dir, project_name: &str) -> Result<PathBuf, Error> {
+	// A fixed base doesn't need (or have) user directories.
+	if let Dir::Path(base) = dir {
+		let mut path = PathBuf::from(base);
+		path.push(project_name);
+		crate::profile::push_profile(&mut path);
+		return Ok(path);
+	}
+
 	let project_dir = base(project_name)?;
 
 	use Dir::*;
-	Ok(match &dir {
+	let mut path = match &dir {
 		Project    => project_dir.project_path(),
 		Cache      => project_dir.cache_dir(),
 		Config     => project_dir.config_dir(),
 		Data       => project_dir.data_dir(),
 		DataLocal  => project_dir.data_local_dir(),
 		Preference => project_dir.preference_dir(),
-	}.to_path_buf())
+		Runtime    => project_dir.runtime_dir().unwrap_or(project_dir.cache_dir()),
+		Path(_)    => unreachable!(),
+	}.to_path_buf();
+
+	crate::profile::push_profile(&mut path);
+
+	Ok(path)
+}
+
+// Same as `get_projectdir()`, without the version directory or profile.
+//
+// This is the directory containing all of the project's versions and profiles.
+pub(crate) fn get_projectdir_root(dir: &Dir, project_name: &str) -> Result<PathBuf, Error> {
+	let mut path = get_projectdir_unversioned(dir, project_name)?;
+	if crate::profile::profile().is_some() {
+		path.pop();
+	}
+	Ok(path)
 }
 
 #[inline(always)]
@@ -40,6 +185,145 @@ pub(crate) fn convert_error<T, E: std::fmt::Display + std::fmt::Debug + Send + S
 	}
 }
 
+#[cfg(any(feature = "toml", feature = "json", feature = "yaml", feature = "ron"))]
+// Deserialize, erroring if any fields were unknown (ignored).
+pub(crate) fn deserialize_strict<'de, T, D>(deserializer: D) -> Result<T, Error>
+where
+	T: serde::Deserialize<'de>,
+	D: serde::Deserializer<'de>,
+	D::Error: Send + Sync + 'static,
+{
+	let mut unknown = Vec::new();
+	let t = convert_error(serde_ignored::deserialize(deserializer, |path| unknown.push(path.to_string())))?;
+
+	if !unknown.is_empty() {
+		bail!("unknown fields: {}", unknown.join(", "));
+	}
+
+	Ok(t)
+}
+
+#[cfg(feature = "toml")]
+// Insert any struct fields in `default` missing from `table` (recursively).
+//
+// Only the fields of `shape` are merged, the entries of maps are left alone.
+//
+// Returns true if any keys were inserted.
+pub(crate) fn merge_toml(table: &mut dyn toml_edit::TableLike, default: &dyn toml_edit::TableLike, shape: &crate::shape::Shape) -> bool {
+	let mut merged = false;
+
+	for (key, d) in default.iter() {
+		let Some(shape) = shape.field(key) else { continue };
+		match table.get_mut(key) {
+			None => {
+				table.insert(key, d.clone());
+				merged = true;
+			},
+			Some(t) => {
+				if let (Some(t), Some(d)) = (t.as_table_like_mut(), d.as_table_like()) {
+					merged |= merge_toml(t, d, shape);
+				}
+			},
+		}
+	}
+
+	merged
+}
+
+#[cfg(feature = "json")]
+// Insert any struct fields in `default` missing from `value` (recursively).
+//
+// Only the fields of `shape` are merged, the entries of maps are left alone.
+//
+// Returns true if any keys were inserted.
+pub(crate) fn merge_json(value: &mut serde_json::Value, default: serde_json::Value, shape: &crate::shape::Shape) -> bool {
+	use serde_json::Value::Object;
+	let (Object(map), Object(default)) = (value, default) else { return false };
+
+	let mut merged = false;
+
+	for (key, d) in default {
+		let Some(shape) = shape.field(&key) else { continue };
+		match map.get_mut(&key) {
+			None => {
+				map.insert(key, d);
+				merged = true;
+			},
+			Some(v) => merged |= merge_json(v, d, shape),
+		}
+	}
+
+	merged
+}
+
+#[cfg(feature = "yaml")]
+// Insert any struct fields in `default` missing from `value` (recursively).
+//
+// Only the fields of `shape` are merged, the entries of maps are left alone.
+//
+// Returns true if any keys were inserted.
+pub(crate) fn merge_yaml(value: &mut serde_yaml::Value, default: serde_yaml::Value, shape: &crate::shape::Shape) -> bool {
+	use serde_yaml::Value::Mapping;
+	let (Mapping(map), Mapping(default)) = (value, default) else { return false };
+
+	let mut merged = false;
+
+	for (key, d) in default {
+		let Some(shape) = key.as_str().and_then(|k| shape.field(k)) else { continue };
+		match map.get_mut(&key) {
+			None => {
+				map.insert(key, d);
+				merged = true;
+			},
+			Some(v) => merged |= merge_yaml(v, d, shape),
+		}
+	}
+
+	merged
+}
+
+#[cfg(any(feature = "toml", feature = "json", feature = "yaml"))]
+// Strip a UTF-8 byte order mark, e.g. from Notepad.
+//
+// UTF-16 text (starting with a byte order mark, e.g. from PowerShell)
+// is transcoded to UTF-8 with the `encoding` feature, else it errors.
+pub(crate) fn decode_text(bytes: &[u8]) -> Result<std::borrow::Cow<'_, [u8]>, Error> {
+	use std::borrow::Cow;
+
+	if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+		return Ok(Cow::Borrowed(rest));
+	}
+
+	let from_bytes: fn([u8; 2]) -> u16 = match bytes {
+		[0xFF, 0xFE, ..] => u16::from_le_bytes,
+		[0xFE, 0xFF, ..] => u16::from_be_bytes,
+		_ => return Ok(Cow::Borrowed(bytes)),
+	};
+
+	#[cfg(not(feature = "encoding"))]
+	{
+		let _ = from_bytes;
+		bail!("UTF-16 encoded text is not supported without the `encoding` feature");
+	}
+
+	#[cfg(feature = "encoding")]
+	{
+		let chunks = bytes[2..].chunks_exact(2);
+		if !chunks.remainder().is_empty() {
+			bail!("UTF-16 encoded text has an odd amount of bytes");
+		}
+		let units: Vec<u16> = chunks.map(|c| from_bytes([c[0], c[1]])).collect();
+		Ok(Cow::Owned(String::from_utf16(&units)?.into_bytes()))
+	}
+}
+
+#[cfg(any(feature = "toml", feature = "json", feature = "yaml"))]
+#[inline(always)]
+// Strip a UTF-8 byte order mark.
+pub(crate) fn strip_bom(string: &str) -> &str {
+	string.strip_prefix('\u{feff}').unwrap_or(string)
+}
+
 #[inline(always)]
 // Assert PATH is safe (absolute).
 pub(crate) fn assert_safe_path(path: &Path) -> Result<(), Error> {
@@ -66,20 +350,86 @@ where
 	Ok(buf)
 }
 
+#[inline(always)]
+// Returns true if the bytes start with the `gzip` magic bytes.
+pub(crate) fn is_gzip(bytes: &[u8]) -> bool {
+	bytes.starts_with(&GZIP_MAGIC)
+}
+
 #[inline(always)]
 pub(crate) fn compress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+	compress_with(bytes, flate2::Compression::fast())
+}
+
+pub(crate) fn compress_with(bytes: &[u8], level: flate2::Compression) -> Result<Vec<u8>, Error> {
 	use std::io::prelude::*;
-	use flate2::Compression;
 	use flate2::write::GzEncoder;
 
 	// Compress bytes and write.
-	let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+	let mut encoder = GzEncoder::new(Vec::new(), level);
 	encoder.write_all(bytes)?;
 	let buf = encoder.finish()?;
 
 	Ok(buf)
 }
 
+#[cfg(feature = "base64")]
+// URL-safe `base64` without padding, decoding accepts padding anyway (some tools add it).
+pub(crate) const BASE64: base64::engine::GeneralPurpose = base64::engine::GeneralPurpose::new(
+	&base64::alphabet::URL_SAFE,
+	base64::engine::GeneralPurposeConfig::new()
+		.with_encode_padding(false)
+		.with_decode_padding_mode(base64::engine::DecodePaddingMode::Indifferent),
+);
+
+#[inline]
+// The CRC-32 checksum of `bytes` (same as `gzip`).
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+	let mut crc = flate2::Crc::new();
+	crc.update(bytes);
+	crc.sum()
+}
+
+#[inline(always)]
+// Seconds since the UNIX epoch, returns 0 on error.
+pub(crate) fn unix_timestamp() -> u64 {
+	match std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+		Ok(d)  => d.as_secs(),
+		Err(_) => 0,
+	}
+}
+
+// Rename `path` to the first free quarantine PATH at `timestamp`,
+// `path.corrupt-TIMESTAMP`, then `path.corrupt-TIMESTAMP-1`, `-2`, etc.
+//
+// `rename()` overwrites existing files, so the quarantine PATH is first
+// reserved with `create_new()`, a second quarantine in the same second
+// (from any thread or process) never replaces the first one's file.
+pub(crate) fn quarantine(path: &Path, timestamp: u64) -> Result<PathBuf, Error> {
+	let mut n = 0;
+	loop {
+		let mut corrupt = path.as_os_str().to_os_string();
+		match n {
+			0 => corrupt.push(format!(".corrupt-{timestamp}")),
+			n => corrupt.push(format!(".corrupt-{timestamp}-{n}")),
+		}
+		let corrupt = PathBuf::from(corrupt);
+
+		match std::fs::OpenOptions::new().write(true).create_new(true).open(&corrupt) {
+			Ok(_) => (),
+			Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => { n += 1; continue },
+			Err(e) => return Err(e.into()),
+		}
+
+		// Replace the (empty) reserved file.
+		if let Err(e) = std::fs::rename(path, &corrupt) {
+			std::fs::remove_file(&corrupt)?;
+			return Err(e.into());
+		}
+		return Ok(corrupt);
+	}
+}
+
 #[inline(always)]
 // Returns 0 on error.
 pub(crate) fn filesize(path: &Path) -> u64 {
@@ -89,43 +439,303 @@ pub(crate) fn filesize(path: &Path) -> u64 {
 	}
 }
 
-// Create a `File` -> `BufReader`.
+// Reserve `len` bytes of disk space for an empty `file` before writing.
+//
+// This lets the filesystem allocate contiguously and surfaces
+// `ENOSPC` before anything is written, instead of mid-write.
+//
+// On Linux this is `fallocate()` (without changing the file size),
+// filesystems that don't support it are silently skipped.
+// Elsewhere this is `set_len()`, which the write then fills exactly.
+pub(crate) fn preallocate(file: &std::fs::File, len: u64) -> Result<(), Error> {
+	if len == 0 {
+		return Ok(());
+	}
+
+	#[cfg(target_os = "linux")]
+	{
+		use std::os::unix::io::AsRawFd;
+		let len = libc::off_t::try_from(len)?;
+
+		// SAFETY: `fd` is a valid open file descriptor for the duration of the call.
+		if unsafe { libc::fallocate(file.as_raw_fd(), libc::FALLOC_FL_KEEP_SIZE, 0, len) } != 0 {
+			let e = std::io::Error::last_os_error();
+			if e.raw_os_error() == Some(libc::ENOSPC) {
+				return Err(e.into());
+			}
+		}
+	}
+
+	#[cfg(not(target_os = "linux"))]
+	file.set_len(len)?;
+
+	Ok(())
+}
+
+// The amount of bytes allocated on disk for a file, only known on Unix.
+pub(crate) fn allocated_size(metadata: &std::fs::Metadata) -> Option<u64> {
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::MetadataExt;
+		Some(metadata.blocks() * 512)
+	}
+
+	#[cfg(not(unix))]
+	{
+		let _ = metadata;
+		None
+	}
+}
+
+// Write `bytes` to the empty `file`, seeking over blocks of zeros instead of writing them.
+//
+// On filesystems that support sparse files, the skipped blocks are never allocated.
+#[cfg(any(feature = "bincode", feature = "bson", feature = "postcard", feature = "messagepack", feature = "bincode2", feature = "pickle"))]
+pub(crate) fn write_sparse(file: &mut std::fs::File, bytes: &[u8]) -> Result<(), Error> {
+	use std::io::{Seek,SeekFrom,Write};
+
+	// The usual filesystem block size.
+	const BLOCK: usize = 4096;
+
+	for chunk in bytes.chunks(BLOCK) {
+		if chunk.iter().all(|b| *b == 0) {
+			file.seek(SeekFrom::Current(chunk.len() as i64))?;
+		} else {
+			file.write_all(chunk)?;
+		}
+	}
+
+	// Trailing zeros were only seeked over.
+	file.set_len(bytes.len() as u64)?;
+	Ok(())
+}
+
+// Open a file read-only, this never creates it.
+//
+// A missing file gets the PATH attached as context, the
+// `io::Error` is kept as the source so `downcast_ref()` still works.
+pub(crate) fn file_read(path: &Path) -> Result<std::fs::File, Error> {
+	match std::fs::File::open(path) {
+		Ok(file) => Ok(file),
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+			Err(Error::new(e).context(format!("{:?} does not exist", path)))
+		},
+		Err(e) => Err(e.into()),
+	}
+}
+
+// Recursively collect all files in `dir`, symlinks are not followed.
+pub(crate) fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+	for entry in std::fs::read_dir(dir)? {
+		let entry = entry?;
+		let file_type = entry.file_type()?;
+		if file_type.is_dir() {
+			walk(&entry.path(), files)?;
+		} else if file_type.is_file() {
+			files.push(entry.path());
+		}
+	}
+	Ok(())
+}
+
+// Create a read-only `File` -> `BufReader`.
 macro_rules! file_bufr {
 	() => {
-		std::io::BufReader::new(
-			std::fs::OpenOptions::new()
-			.read(true)
-			.open(Self::absolute_path()?)?
-		)
+		std::io::BufReader::new(crate::common::file_read(&Self::absolute_path()?)?)
 	}
 }
 pub(crate) use file_bufr;
 
-// Create a `File` -> `BufReader` for gzip.
+// Create a read-only `File` -> `BufReader` for gzip.
 macro_rules! file_bufr_gzip {
 	() => {
-		std::io::BufReader::new(
-			std::fs::OpenOptions::new()
-			.read(true)
-			.open(Self::absolute_path_gzip()?)?
-		)
+		std::io::BufReader::new(crate::common::file_read(&Self::absolute_path_gzip()?)?)
 	}
 }
+pub(crate) use file_bufr_gzip;
+
+// The default `BufWriter` capacity, see `WRITE_BUF_SIZE` in the traits.
+pub(crate) const WRITE_BUF_SIZE: usize = 64 * 1024;
 
 // Create a `File` -> `BufWriter` from a `Path`.
+//
+// With a `$len`, the disk space is reserved before writing, see `preallocate()`.
+//
+// With a `$cap`, the `BufWriter` has that capacity, else `WRITE_BUF_SIZE`.
 macro_rules! file_bufw {
 	($path:expr) => {
-		std::io::BufWriter::new(
-			std::fs::OpenOptions::new()
+		crate::common::file_bufw!($path, 0)
+	};
+	($path:expr, $len:expr) => {
+		crate::common::file_bufw!($path, $len, crate::common::WRITE_BUF_SIZE)
+	};
+	($path:expr, $len:expr, $cap:expr) => {{
+		let file = std::fs::OpenOptions::new()
 			.write(true)
 			.create(true)
 			.truncate(true)
-			.open(&$path)?
-		)
-	}
+			.open(&$path)?;
+		crate::common::preallocate(&file, $len as u64)?;
+		std::io::BufWriter::with_capacity($cap, file)
+	}};
 }
 pub(crate) use file_bufw;
 
+// Fail to compile if `Self` implements `ReadOnly`.
+//
+// This is only evaluated when the calling function is actually used.
+macro_rules! assert_writable {
+	() => {
+		const { ::std::assert!(!Self::READ_ONLY, "disk: this type implements `disk::ReadOnly`, it can not be written or removed") }
+	};
+}
+pub(crate) use assert_writable;
+
+// Error if `Self` implements `WriteOnce` and `$path` already exists.
+macro_rules! ensure_write_once {
+	($path:expr) => {
+		if Self::WRITE_ONCE && $path.try_exists()? {
+			anyhow::bail!("disk: {:?} already exists and is write-once", $path);
+		}
+	};
+}
+pub(crate) use ensure_write_once;
+
+// Error if saving `$len` bytes to `$path` would exceed the project's quota, see `crate::set_quota()`.
+macro_rules! ensure_quota {
+	($path:expr, $len:expr) => {
+		crate::quota::ensure(Self::PROJECT_DIRECTORY, Self::project_dir_path, &$path, $len as u64)?;
+	};
+}
+pub(crate) use ensure_quota;
+
+// Error if saving `$len` bytes to `$path` would leave less than `crate::min_free_space()`.
+macro_rules! ensure_space {
+	($path:expr, $len:expr) => {
+		crate::space::ensure(&$path, $len as u64)?;
+	};
+}
+pub(crate) use ensure_space;
+
+// Run an I/O operation, wrapped in a `tracing` span if the feature is enabled.
+//
+// The span contains the operation, format, file name, type and duration.
+// `save` and `remove` operations (those returning `Metadata`) also record the PATH and bytes.
+// Errors are emitted as `warn!()` events, success as `debug!()`.
+//
+// The `DiskObserver` (if any) is notified afterwards,
+// successful saves and removes also call the project's sync hook (if any).
+macro_rules! traced {
+	($op:literal, $format:literal, save, $body:block) => {
+		crate::common::traced!(@metadata on_save, $op, $format, $body)
+	};
+	($op:literal, $format:literal, remove, $body:block) => {
+		crate::common::traced!(@metadata on_remove, $op, $format, $body)
+	};
+	($op:literal, $format:literal, load, $body:block) => {{
+		let (__result, __outermost) = crate::common::traced!(@inner $op, $format, $body);
+		if let (Ok((_, elapsed)), true) = (&__result, __outermost) {
+			crate::observer::notify(|o| if let Ok(path) = Self::absolute_path() { o.on_load(&path, *elapsed) });
+		}
+		__result.map(|(r, _)| r)
+	}};
+	($op:literal, $format:literal, $body:block) => {
+		crate::common::traced!(@inner $op, $format, $body).0.map(|(r, _)| r)
+	};
+	// Returns the result and duration, and if this was the outermost
+	// traced operation, only errors are sent to the observer.
+	(@inner $op:literal, $format:literal, $body:block) => {{
+		#[cfg(feature = "tracing")]
+		let __span = crate::common::trace_span!($op, $format);
+		let __now   = std::time::Instant::now();
+		let __scope = crate::observer::Scope::enter();
+
+		#[allow(clippy::redundant_closure_call)]
+		let __result: Result<_, anyhow::Error> = (|| $body)();
+		let __elapsed   = __now.elapsed();
+		let __outermost = __scope.exit();
+
+		#[cfg(feature = "tracing")]
+		{
+			__span.record("duration_us", __elapsed.as_micros() as u64);
+			crate::common::trace_result(&__result);
+		}
+
+		if let (Err(e), true) = (&__result, __outermost) {
+			crate::observer::notify(|o| o.on_error($op, e));
+		}
+
+		(__result.map(|r| (r, __elapsed)), __outermost)
+	}};
+	(@metadata $callback:ident, $op:literal, $format:literal, $body:block) => {{
+		#[cfg(feature = "tracing")]
+		let __span = crate::common::trace_span!($op, $format);
+		let __now   = std::time::Instant::now();
+		let __scope = crate::observer::Scope::enter();
+
+		// A saved or removed file makes its journal stale.
+		#[allow(clippy::redundant_closure_call)]
+		let __result: Result<crate::Metadata, anyhow::Error> = (|| $body)()
+			.and_then(|m| { Self::__journal_reset(&m)?; Ok(m) });
+		let __elapsed   = __now.elapsed();
+		let __outermost = __scope.exit();
+
+		#[cfg(feature = "tracing")]
+		{
+			__span.record("duration_us", __elapsed.as_micros() as u64);
+			if let Ok(m) = &__result {
+				m.trace_record(&__span);
+			}
+			crate::common::trace_result(&__result);
+		}
+
+		if __outermost {
+			crate::observer::notify(|o| match &__result {
+				Ok(m)  => o.$callback(m, __elapsed),
+				Err(e) => o.on_error($op, e),
+			});
+
+			if let Ok(m) = &__result {
+				crate::observer::notify_sync(Self::PROJECT_DIRECTORY, m);
+			}
+		}
+
+		__result.map(|m| {
+			let io_time = __elapsed.saturating_sub(m.serialize_time());
+			m.with_io_time(io_time)
+		})
+	}};
+}
+pub(crate) use traced;
+
+#[cfg(feature = "tracing")]
+// Enter the span used by `traced!()`.
+macro_rules! trace_span {
+	($op:literal, $format:literal) => {
+		tracing::debug_span!(
+			"disk",
+			op          = $op,
+			format      = $format,
+			file        = Self::FILE_NAME,
+			r#type      = std::any::type_name::<Self>(),
+			path        = tracing::field::Empty,
+			bytes       = tracing::field::Empty,
+			duration_us = tracing::field::Empty,
+		).entered()
+	};
+}
+#[cfg(feature = "tracing")]
+pub(crate) use trace_span;
+
+#[cfg(feature = "tracing")]
+// Emit the result event of a `traced!()` operation.
+pub(crate) fn trace_result<T>(result: &Result<T, Error>) {
+	match result {
+		Ok(_)  => tracing::debug!("ok"),
+		Err(e) => tracing::warn!(error = %e, "failed"),
+	}
+}
+
 #[inline(always)]
 // Read a PATH as bytes.
 pub(crate) fn path_to_bytes(path: &std::path::Path) -> Result<Vec<u8>, anyhow::Error> {
@@ -188,21 +798,45 @@ macro_rules! impl_file_bytes {
 		///
 		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
 		fn file_bytes_memmap(start: usize, end: usize) -> Result<Vec<u8>, anyhow::Error> {
+			Ok(unsafe { Self::file_range_memmap(start, end)? }.to_vec())
+		}
+
+		#[cfg(target_pointer_width = $bit)]
+		/// Memory map only the `start..end` range of bytes of the associated file of [`Self`].
+		///
+		/// Unlike [`Self::file_bytes_memmap`], the bytes are not copied,
+		/// the returned [`Mmap`](crate::Mmap) derefs to exactly `&file[start..end]`.
+		///
+		/// ## Errors
+		/// If `start` is greater than `end` or `end` is past the end of the file, this returns error.
+		///
+		/// ## Safety
+		/// You _must_ understand all the invariants that `memmap` comes with.
+		///
+		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
+		unsafe fn file_range_memmap(start: usize, end: usize) -> Result<crate::Mmap, anyhow::Error> {
 			if start > end {
 				bail!("file_bytes(): start > end");
 			}
 
-			let file = std::fs::File::open(Self::absolute_path()?)?;
-			let mmap = unsafe { memmap2::MmapOptions::new().map(&file)? };
-			#[cfg(unix)]
-			mmap.advise(memmap2::Advice::Sequential);
-			let len = mmap.len();
+			let file = common::file_read(&Self::absolute_path()?)?;
+			let len  = file.metadata()?.len();
 
-			if mmap.len() < end {
+			// Mapping past the end of the file is not an error, but touching it is `SIGBUS`.
+			if len < end as u64 {
 				bail!("file_bytes(): file length ({len}) less than end ({end})");
 			}
 
-			Ok(mmap[start..end].to_vec())
+			let mmap = unsafe {
+				memmap2::MmapOptions::new()
+					.offset(start as u64)
+					.len(end - start)
+					.map(&file)?
+			};
+			#[cfg(unix)]
+			mmap.advise(memmap2::Advice::Sequential)?;
+
+			Ok(mmap)
 		}
 	}
 }
@@ -241,7 +875,7 @@ macro_rules! impl_io {
 		/// ```
 		fn read_to_bytes_gzip() -> Result<Vec<u8>, anyhow::Error> {
 			// Decode compressed file bytes.
-			let buf = common::decompress(crate::common::file_bufr!())?;
+			let buf = common::decompress(crate::common::file_bufr_gzip!())?;
 
 			Ok(buf)
 		}
@@ -259,130 +893,521 @@ macro_rules! impl_io {
 			}
 		}
 
+		#[inline]
+		/// Same as [`Self::exists_bool()`] but checks if the `gzip` file exists.
+		fn exists_bool_gzip() -> Result<bool, anyhow::Error> {
+			Ok(Self::absolute_path_gzip()?.try_exists()?)
+		}
+
+		#[inline]
+		/// Same as [`Self::stat()`] but for the `gzip` file.
+		fn stat_gzip() -> Result<crate::Metadata, anyhow::Error> {
+			let path = Self::absolute_path_gzip()?;
+			let size = std::fs::metadata(&path)?.len();
+			Ok(crate::Metadata::new(size, path))
+		}
+
+		#[inline]
+		/// Same as [`Self::last_modified()`] but for the `gzip` file.
+		fn last_modified_gzip() -> Result<std::time::SystemTime, anyhow::Error> {
+			Ok(std::fs::metadata(Self::absolute_path_gzip()?)?.modified()?)
+		}
+
+		#[inline]
+		/// Same as [`Self::modified_since()`] but for the `gzip` file.
+		fn modified_since_gzip(time: std::time::SystemTime) -> Result<bool, anyhow::Error> {
+			Ok(Self::last_modified_gzip()? > time)
+		}
+
 		#[inline(always)]
 		/// Read the file as bytes and deserialize into [`Self`].
 		///
 		/// Internally, this functions calls the most optimal function for the format.
 		fn from_file() -> Result<Self, anyhow::Error> {
-			Self::__from_file()
+			crate::common::traced!("from_file", $file_ext, load, {
+				let mut s = Self::__from_file()?;
+				s.__journal()?;
+				s.__validate()?;
+				Ok(s)
+			})
 		}
 
 		#[inline(always)]
 		/// Read the file as bytes, decompress with `gzip` and deserialize into [`Self`].
 		fn from_file_gzip() -> Result<Self, anyhow::Error> {
-			Self::from_bytes(&Self::read_to_bytes_gzip()?)
+			crate::common::traced!("from_file_gzip", $file_ext, load, {
+				let s = Self::from_bytes(&Self::read_to_bytes_gzip()?)?;
+				s.__validate()?;
+				Ok(s)
+			})
 		}
 
-		#[inline(always)]
-		/// Same as [`Self::from_file`] but with [`memmap2`](https://docs.rs/memmap2).
+		#[cfg(feature = "base64")]
+		/// Serialize [`Self`] into a `gzip` compressed, `base64` encoded [`String`].
 		///
-		/// ## Safety
-		/// You _must_ understand all the invariants that `memmap` comes with.
-		///
-		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
-		unsafe fn from_file_memmap() -> Result<Self, anyhow::Error> {
-			let file = std::fs::File::open(Self::absolute_path()?)?;
-			let mmap = unsafe { memmap2::Mmap::map(&file)? };
-			#[cfg(unix)]
-			mmap.advise(memmap2::Advice::Sequential);
-			Self::from_bytes(&*mmap)
-		}
-
-		#[inline(always)]
-		/// Same as [`Self::from_file_gzip`] but with [`memmap2`](https://docs.rs/memmap2).
+		/// These are the same bytes [`Self::save_gzip()`] would write, but as a text-safe
+		/// string that can be pasted into bug reports, URLs, the clipboard, etc.
 		///
-		/// ## Safety
-		/// You _must_ understand all the invariants that `memmap` comes with.
+		/// The URL-safe `base64` alphabet is used, without padding.
 		///
-		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
-		unsafe fn from_file_gzip_memmap() -> Result<Self, anyhow::Error> {
-			let file = std::fs::File::open(Self::absolute_path_gzip()?)?;
-			let mmap = unsafe { memmap2::Mmap::map(&file)? };
-			#[cfg(unix)]
-			mmap.advise(memmap2::Advice::Sequential);
-			Self::from_bytes(&common::decompress(&*mmap)?)
+		/// Use [`Self::from_string_gzip_base64()`] to parse it back.
+		fn to_string_gzip_base64(&self) -> Result<String, anyhow::Error> {
+			use base64::Engine;
+			self.__validate()?;
+			Ok(crate::common::BASE64.encode(common::compress(&self.to_bytes()?)?))
 		}
 
-		#[inline(always)]
-		/// Reads _an arbitrary_ PATH, and attempts to deserialize into [`Self`].
+		#[cfg(feature = "base64")]
+		/// Parse a [`String`] created with [`Self::to_string_gzip_base64()`].
 		///
-		/// Internally, this functions calls the most optimal function for the format.
-		fn from_path<P: std::convert::AsRef<std::path::Path>>(path: P) -> Result<Self, anyhow::Error> {
-			Self::__from_path(path.as_ref())
+		/// Surrounding whitespace (e.g. a trailing newline from the clipboard) and padding are ignored.
+		fn from_string_gzip_base64(string: &str) -> Result<Self, anyhow::Error> {
+			use base64::Engine;
+			let bytes = crate::common::BASE64.decode(string.trim())?;
+			let s = Self::from_bytes(&common::decompress(bytes.as_slice())?)?;
+			s.__validate()?;
+			Ok(s)
 		}
 
-		#[inline(always)]
-		/// Same as [`Self::from_path`] but with [`memmap2`](https://docs.rs/memmap2).
+		/// Read the file as bytes, detect `gzip` compression and deserialize into [`Self`].
 		///
-		/// ## Safety
-		/// You _must_ understand all the invariants that `memmap` comes with.
+		/// If the associated file exists, it is read and decompressed
+		/// only if it starts with the `gzip` magic bytes (`0x1f 0x8b`).
 		///
-		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
-		unsafe fn from_path_memmap<P: std::convert::AsRef<std::path::Path>>(path: P) -> Result<Self, anyhow::Error> {
-			let file = std::fs::File::open(path.as_ref())?;
-			let mmap = unsafe { memmap2::Mmap::map(&file)? };
-			#[cfg(unix)]
-			mmap.advise(memmap2::Advice::Sequential);
-			Self::from_bytes(&*mmap)
+		/// If it does not exist, the `.gz` variant is read instead, for example:
+		/// ```text,ignore
+		/// config.json    // Checked first
+		/// config.json.gz // Checked if the above does not exist
+		/// ```
+		/// This is useful when the file may have been saved with either [`Self::save()`] or [`Self::save_gzip()`].
+		fn from_file_auto() -> Result<Self, anyhow::Error> {
+			crate::common::traced!("from_file_auto", $file_ext, load, {
+				let path = match Self::absolute_path()? {
+					p if p.exists() => p,
+					_ => {
+						#[cfg(feature = "tracing")]
+						tracing::debug!("{} does not exist, falling back to gzip", Self::FILE_NAME);
+						Self::absolute_path_gzip()?
+					},
+				};
+
+				let s = Self::__from_bytes_auto(&common::path_to_bytes(&path)?)?;
+				s.__validate()?;
+				Ok(s)
+			})
 		}
 
-		/// Try saving as a file.
+		/// Attempt to read [`Self`] from the associated file, falling back to other files on failure.
 		///
-		/// This will return the amount of `bytes` saved and the [`PathBuf`] on success.
+		/// If a file is missing or fails to deserialize, the next one is tried, in this order:
 		///
- 		/// Calling this will automatically create the directories leading up to the file.
-		fn save(&self) -> Result<crate::Metadata, anyhow::Error> {
-			use std::io::Write;
-			let bytes = self.to_writeable_fmt()?;
+		/// | Order | File                                              | [`crate::Source`] |
+		/// |-------|---------------------------------------------------|-------------------|
+		/// | 1     | `state.toml`                                      | [`crate::Source::File`]
+		/// | 2     | `state.toml.tmp`                                  | [`crate::Source::Tmp`]
+		/// | 3     | `state.toml.gz`                                   | [`crate::Source::Gzip`]
+		/// | 4     | `state-2024-06-01T12-00-00.toml`, newest first    | [`crate::Source::History`]
+		/// | 5     | `state.toml.bak`                                  | [`crate::Source::Backup`]
+		///
+		/// The historical versions are the ones [`Self::snapshot()`] and
+		/// [`Self::save_rotated()`] create, see [`Self::list_history()`].
+		///
+		/// `gzip` compression is detected for each file, see [`Self::from_file_auto()`].
+		///
+		/// On success, this returns [`Self`] and the [`crate::Source`] it was read from.
+		///
+		/// ## Errors
+		/// If all files are missing or fail, an error containing each failure is returned.
+		fn from_file_resilient() -> Result<(Self, crate::Source), anyhow::Error> {
+			crate::common::traced!("from_file_resilient", $file_ext, load, {
+				let base = Self::base_path()?;
+
+				let mut sources = vec![
+					(crate::Source::File, base.join(Self::FILE_NAME)),
+					(crate::Source::Tmp,  base.join(Self::FILE_NAME_TMP)),
+					(crate::Source::Gzip, base.join(Self::FILE_NAME_GZIP)),
+				];
+				// Historical versions, newest first.
+				for (time, path, _) in crate::history::history(&base, Self::FILE, Self::FILE_EXT)?.into_iter().rev() {
+					sources.push((crate::Source::History(time), path));
+				}
+				sources.push((crate::Source::Backup, base.join(format!("{}.bak", Self::FILE_NAME))));
+
+				let mut errors = String::new();
+				for (source, path) in sources {
+					if !path.exists() {
+						#[cfg(feature = "tracing")]
+						tracing::debug!("{source:?}: {path:?} does not exist, falling back");
+						errors += &format!("\n{source:?}: {path:?} does not exist");
+						continue;
+					}
 
-			// Create PATH.
-			let mut path = Self::base_path()?;
-			std::fs::create_dir_all(&path)?;
-			path.push(Self::FILE_NAME);
+					let result = common::path_to_bytes(&path)
+						.and_then(|b| Self::__from_bytes_auto(&b))
+						.and_then(|s| s.__validate().map(|_| s));
+
+					match result {
+						Ok(s)  => return Ok((s, source)),
+						Err(e) => {
+							#[cfg(feature = "tracing")]
+							tracing::warn!("{source:?}: {path:?}: {e}, falling back");
+							errors += &format!("\n{source:?}: {path:?}: {e}");
+						},
+					}
+				}
 
-			// Write.
-			crate::common::file_bufw!(&path).write_all(&bytes)?;
-			Ok(crate::Metadata::new(bytes.len() as u64, path))
+				bail!("all sources failed to load:{errors}")
+			})
 		}
 
-
-		/// Same as [`Self::save`] but with [`memmap2`](https://docs.rs/memmap2).
+		/// Read the file and deserialize into [`Self`], quarantining the file if deserialization fails.
 		///
-		/// ## Safety
+		/// If the file exists but fails to deserialize, it is renamed to:
+		/// `file_name` + `extension` + `.corrupt-` + `UNIX timestamp`, for example:
+		/// ```text,ignore
+		/// config.toml                    // <- Real file
+		/// config.toml.corrupt-1700000000 // <- Quarantined version
+		/// ```
+		/// This preserves the corrupt file instead of it being overwritten on the next save.
+		///
+		/// Existing quarantined files are never overwritten, files quarantined
+		/// in the same second get a `-1`, `-2`, etc suffix after the timestamp.
+		///
+		/// If the file deserializes but fails [`crate::Validate`], it is _not_ quarantined.
+		///
+		/// ## Errors
+		/// If the file was quarantined, the returned error is a [`crate::Quarantined`], which
+		/// contains the new PATH and original error and can be retrieved with [`anyhow::Error::downcast`].
+		///
+		/// Any other error (the file not existing, I/O errors) is returned as-is and nothing is renamed.
+		fn from_file_or_quarantine() -> Result<Self, anyhow::Error> {
+			crate::common::assert_writable!();
+			crate::common::traced!("from_file_or_quarantine", $file_ext, load, {
+				let path  = Self::absolute_path()?;
+				let bytes = common::path_to_bytes(&path)?;
+
+				match Self::from_bytes(&bytes) {
+					Ok(s)  => { s.__validate()?; Ok(s) },
+					Err(e) => {
+						let corrupt = common::quarantine(&path, common::unix_timestamp())?;
+
+						#[cfg(feature = "tracing")]
+						tracing::warn!("{path:?} failed to deserialize: {e}, quarantined to {corrupt:?}");
+						let metadata = crate::Metadata::new(bytes.len() as u64, corrupt);
+						Err(crate::Quarantined::new(metadata, e).into())
+					},
+				}
+			})
+		}
+
+		/// Same as [`Self::from_file_or_quarantine()`] but returns [`Default::default()`] if the file was quarantined.
+		///
+		/// Any other error (the file not existing, I/O errors) is still returned.
+		fn from_file_or_quarantine_default() -> Result<Self, anyhow::Error>
+		where
+			Self: Default,
+		{
+			crate::common::assert_writable!();
+			match Self::from_file_or_quarantine() {
+				Ok(s)  => Ok(s),
+				Err(e) if e.is::<crate::Quarantined>() => Ok(Self::default()),
+				Err(e) => Err(e),
+			}
+		}
+
+		/// Read the file and deserialize into [`Self`], or deserialize the embedded bytes if the file does not exist.
+		///
+		/// `embedded` should be the file's bytes compiled into your binary, for example:
+		/// ```rust,ignore
+		/// // A default config, with comments, shipped with the binary.
+		/// const DEFAULT: &[u8] = include_bytes!("../assets/config.toml");
+		///
+		/// let config = Config::from_file_or_embedded(DEFAULT)?;
+		/// ```
+		/// Nothing is written to disk, see [`Self::from_file_or_embedded_write()`] for that.
+		///
+		/// Any error other than the file not existing is returned as-is.
+		fn from_file_or_embedded(embedded: &'static [u8]) -> Result<Self, anyhow::Error> {
+			crate::common::traced!("from_file_or_embedded", $file_ext, load, {
+				if Self::absolute_path()?.exists() {
+					return Self::from_file();
+				}
+
+				#[cfg(feature = "tracing")]
+				tracing::debug!("file does not exist, falling back to embedded bytes");
+				let s = Self::from_bytes(embedded)?;
+				s.__validate()?;
+				Ok(s)
+			})
+		}
+
+		/// Same as [`Self::from_file_or_embedded()`], but if the file does not
+		/// exist, the `embedded` bytes are also written to disk as-is.
+		///
+		/// This means any formatting and comments in `embedded` are kept.
+		///
+		/// The bytes are only written if they successfully deserialize.
+		///
+		/// Calling this will automatically create the directories leading up to the file.
+		fn from_file_or_embedded_write(embedded: &'static [u8]) -> Result<Self, anyhow::Error> {
+			crate::common::assert_writable!();
+			crate::common::traced!("from_file_or_embedded_write", $file_ext, load, {
+				if Self::absolute_path()?.exists() {
+					return Self::from_file();
+				}
+
+				#[cfg(feature = "tracing")]
+				tracing::debug!("file does not exist, falling back to embedded bytes");
+				let s = Self::from_bytes(embedded)?;
+				s.__validate()?;
+
+				Self::save_bytes_atomic(embedded)?;
+				Ok(s)
+			})
+		}
+
+		#[doc(hidden)]
+		#[inline(always)]
+		/// Internal function. Decompresses `gzip` if detected, then calls `from_bytes()`.
+		fn __from_bytes_auto(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+			if common::is_gzip(bytes) {
+				Self::from_bytes(&common::decompress(bytes)?)
+			} else {
+				Self::from_bytes(bytes)
+			}
+		}
+
+		#[inline(always)]
+		/// Same as [`Self::from_file`] but with [`memmap2`](https://docs.rs/memmap2).
+		///
+		/// ## Safety
 		/// You _must_ understand all the invariants that `memmap` comes with.
 		///
 		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
-		unsafe fn save_memmap(&self) -> Result<crate::Metadata, anyhow::Error> {
-			// Create bytes.
-			let bytes = self.to_bytes()?;
-			let len = bytes.len();
-
-			// Create PATH.
-			let mut path = Self::base_path()?;
-			std::fs::create_dir_all(&path)?;
-			path.push(Self::FILE_NAME);
+		unsafe fn from_file_memmap() -> Result<Self, anyhow::Error> {
+			crate::common::traced!("from_file_memmap", $file_ext, load, {
+				let file = std::fs::File::open(Self::absolute_path()?)?;
+				let mmap = unsafe { memmap2::Mmap::map(&file)? };
+				#[cfg(unix)]
+				mmap.advise(memmap2::Advice::Sequential);
+				let mut s = Self::from_bytes(&*mmap)?;
+				s.__journal()?;
+				s.__validate()?;
+				Ok(s)
+			})
+		}
 
-			// Open file.
-			let file = std::fs::OpenOptions::new()
-				.read(true)
-				.write(true)
-				.create(true)
-				.open(&path)?;
+		#[inline(always)]
+		/// Same as [`Self::from_file_gzip`] but with [`memmap2`](https://docs.rs/memmap2).
+		///
+		/// ## Safety
+		/// You _must_ understand all the invariants that `memmap` comes with.
+		///
+		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
+		unsafe fn from_file_gzip_memmap() -> Result<Self, anyhow::Error> {
+			crate::common::traced!("from_file_gzip_memmap", $file_ext, load, {
+				let file = std::fs::File::open(Self::absolute_path_gzip()?)?;
+				let mmap = unsafe { memmap2::Mmap::map(&file)? };
+				#[cfg(unix)]
+				mmap.advise(memmap2::Advice::Sequential);
+				let s = Self::from_bytes(&common::decompress(&*mmap)?)?;
+				s.__validate()?;
+				Ok(s)
+			})
+		}
 
-			// Resize file length.
-			#[cfg(target_pointer_width = "64")]
-			file.set_len(len as u64)?;
-			#[cfg(not(target_pointer_width = "64"))]
-			file.set_len(len.try_into()?)?;
+		#[inline]
+		/// Memory map the file read-only and return the mapping.
+		///
+		/// Unlike [`Self::from_file_memmap`], nothing is deserialized or copied,
+		/// the [`Mmap`](crate::Mmap) derefs to `&[u8]` and can be handed to zero-copy parsers directly.
+		///
+		/// ## Safety
+		/// You _must_ understand all the invariants that `memmap` comes with.
+		///
+		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
+		unsafe fn mmap() -> Result<crate::Mmap, anyhow::Error> {
+			let file = common::file_read(&Self::absolute_path()?)?;
+			Ok(unsafe { memmap2::Mmap::map(&file)? })
+		}
 
-			// Write and flush.
-			let mut mmap = unsafe { memmap2::MmapMut::map_mut(&file)? };
+		/// Decompress the `gzip` file into an anonymous read-only memory map.
+		///
+		/// The mapping is not backed by the file, it contains the decompressed bytes.
+		///
+		/// ## Safety
+		/// You _must_ understand all the invariants that `memmap` comes with.
+		///
+		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
+		unsafe fn mmap_gzip() -> Result<crate::Mmap, anyhow::Error> {
+			let file = common::file_read(&Self::absolute_path_gzip()?)?;
+			let mmap = unsafe { memmap2::Mmap::map(&file)? };
 			#[cfg(unix)]
-			mmap.advise(memmap2::Advice::Sequential);
-			mmap.copy_from_slice(&bytes);
-			mmap.flush_async()?;
+			mmap.advise(memmap2::Advice::Sequential)?;
+			let bytes = common::decompress(&*mmap)?;
+
+			let mut anon = memmap2::MmapMut::map_anon(bytes.len())?;
+			anon.copy_from_slice(&bytes);
+			Ok(anon.make_read_only()?)
+		}
+
+		#[inline(always)]
+		/// Reads _an arbitrary_ PATH, and attempts to deserialize into [`Self`].
+		///
+		/// Internally, this functions calls the most optimal function for the format.
+		fn from_path<P: std::convert::AsRef<std::path::Path>>(path: P) -> Result<Self, anyhow::Error> {
+			crate::common::traced!("from_path", $file_ext, load, {
+				let s = Self::__from_path(path.as_ref())?;
+				s.__validate()?;
+				Ok(s)
+			})
+		}
+
+		#[inline(always)]
+		/// Same as [`Self::from_path`] but with [`memmap2`](https://docs.rs/memmap2).
+		///
+		/// ## Safety
+		/// You _must_ understand all the invariants that `memmap` comes with.
+		///
+		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
+		unsafe fn from_path_memmap<P: std::convert::AsRef<std::path::Path>>(path: P) -> Result<Self, anyhow::Error> {
+			crate::common::traced!("from_path_memmap", $file_ext, load, {
+				let file = std::fs::File::open(path.as_ref())?;
+				let mmap = unsafe { memmap2::Mmap::map(&file)? };
+				#[cfg(unix)]
+				mmap.advise(memmap2::Advice::Sequential);
+				let s = Self::from_bytes(&*mmap)?;
+				s.__validate()?;
+				Ok(s)
+			})
+		}
+
+		/// Try saving as a file.
+		///
+		/// This will return the amount of `bytes` saved and the [`PathBuf`] on success.
+		///
+ 		/// Calling this will automatically create the directories leading up to the file.
+		fn save(&self) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			self.__validate()?;
+			let now   = std::time::Instant::now();
+			let bytes = self.to_writeable_fmt()?;
+			let serialize_time = now.elapsed();
+			Ok(Self::save_bytes(&bytes)?.with_serialize_time(serialize_time))
+		}
+
+		/// Try saving raw bytes as the associated file.
+		///
+		/// This is the same as [`Self::save`], but `bytes` are written as-is,
+		/// without serialization (or validation), for example, if the
+		/// serialized bytes were already received over the network.
+		///
+		/// This will return the amount of `bytes` saved and the [`PathBuf`] on success.
+		///
+		/// The disk space for `bytes` is reserved before writing (`fallocate()` on Linux),
+		/// so running out of space errors before anything is written. This applies
+		/// to all non-`memmap` saves.
+		///
+		/// Calling this will automatically create the directories leading up to the file.
+		fn save_bytes(bytes: &[u8]) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			crate::common::traced!("save_bytes", $file_ext, save, {
+				use std::io::Write;
+
+				// Create PATH.
+				let mut path = Self::base_path()?;
+				std::fs::create_dir_all(&path)?;
+				path.push(Self::FILE_NAME);
+				let path = crate::symlink::save_path(path)?;
+				crate::common::ensure_write_once!(path);
+				crate::common::ensure_quota!(path, bytes.len());
+				crate::common::ensure_space!(path, bytes.len());
+
+				// Write.
+				crate::common::file_bufw!(&path, bytes.len(), Self::WRITE_BUF_SIZE).write_all(bytes)?;
+				Ok(crate::Metadata::new(bytes.len() as u64, path))
+			})
+		}
 
-			Ok(crate::Metadata::new(len as u64, path))
+		/// Same as [`Self::save`], but with a [`std::io::BufWriter`] capacity of `capacity` bytes
+		/// instead of [`Self::WRITE_BUF_SIZE`].
+		///
+		/// This will return the amount of `bytes` saved and the [`PathBuf`] on success.
+		///
+		/// Calling this will automatically create the directories leading up to the file.
+		fn save_with_buffer(&self, capacity: usize) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			crate::common::traced!("save_with_buffer", $file_ext, save, {
+				use std::io::Write;
+				self.__validate()?;
+
+				let now   = std::time::Instant::now();
+				let bytes = self.to_writeable_fmt()?;
+				let serialize_time = now.elapsed();
+
+				// Create PATH.
+				let mut path = Self::base_path()?;
+				std::fs::create_dir_all(&path)?;
+				path.push(Self::FILE_NAME);
+				let path = crate::symlink::save_path(path)?;
+				crate::common::ensure_write_once!(path);
+				crate::common::ensure_quota!(path, bytes.len());
+				crate::common::ensure_space!(path, bytes.len());
+
+				// Write.
+				crate::common::file_bufw!(&path, bytes.len(), capacity).write_all(&bytes)?;
+				Ok(crate::Metadata::new(bytes.len() as u64, path).with_serialize_time(serialize_time))
+			})
+		}
+
+		/// Same as [`Self::save`] but with [`memmap2`](https://docs.rs/memmap2).
+		///
+		/// ## Safety
+		/// You _must_ understand all the invariants that `memmap` comes with.
+		///
+		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
+		unsafe fn save_memmap(&self) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			crate::common::traced!("save_memmap", $file_ext, save, {
+				self.__validate()?;
+
+				// Create bytes.
+				let now = std::time::Instant::now();
+				let bytes = self.to_bytes()?;
+				let serialize_time = now.elapsed();
+				let len = bytes.len();
+
+				// Create PATH.
+				let mut path = Self::base_path()?;
+				std::fs::create_dir_all(&path)?;
+				path.push(Self::FILE_NAME);
+				let path = crate::symlink::save_path(path)?;
+				crate::common::ensure_write_once!(path);
+				crate::common::ensure_quota!(path, len);
+				crate::common::ensure_space!(path, len);
+
+				// Open file.
+				let file = std::fs::OpenOptions::new()
+					.read(true)
+					.write(true)
+					.create(true)
+					.open(&path)?;
+
+				// Resize file length.
+				#[cfg(target_pointer_width = "64")]
+				file.set_len(len as u64)?;
+				#[cfg(not(target_pointer_width = "64"))]
+				file.set_len(len.try_into()?)?;
+
+				// Write and flush.
+				let mut mmap = unsafe { memmap2::MmapMut::map_mut(&file)? };
+				#[cfg(unix)]
+				mmap.advise(memmap2::Advice::Sequential);
+				mmap.copy_from_slice(&bytes);
+				mmap.flush_async()?;
+
+				Ok(crate::Metadata::new(len as u64, path).with_serialize_time(serialize_time))
+			})
 		}
 
 		/// Try saving as a compressed file using `gzip`.
@@ -391,6 +1416,9 @@ macro_rules! impl_io {
 		/// - The amount of compressed `bytes` saved to disk
 		/// - The [`PathBuf`] of the file
 		///
+		/// The size before compression is also recorded, see
+		/// [`crate::Metadata::uncompressed_size`] and [`crate::Metadata::compression_ratio`].
+		///
 		/// This will suffix the file with `.gz`, for example:
 		/// ```text,ignore
 		/// config.json    // Normal file name with `.save()`
@@ -399,20 +1427,39 @@ macro_rules! impl_io {
 		///
 		/// Calling this will automatically create the directories leading up to the file.
 		fn save_gzip(&self) -> Result<crate::Metadata, anyhow::Error> {
-			// Compress bytes and write.
-			let c = common::compress(&self.to_bytes()?)?;
-			let c_len = c.len();
-
-			// Create PATH.
-			let mut path = Self::base_path()?;
-			std::fs::create_dir_all(&path)?;
-			path.push(Self::FILE_NAME_GZIP);
-
-			// Write.
-			use std::io::Write;
-			crate::common::file_bufw!(&path).write_all(&c)?;
+			crate::common::assert_writable!();
+			self.__validate()?;
+			let now   = std::time::Instant::now();
+			let bytes = self.to_bytes()?;
+			let serialize_time = now.elapsed();
+			Ok(Self::save_bytes_gzip(&bytes)?.with_serialize_time(serialize_time))
+		}
 
-			Ok(crate::Metadata::new(c_len as u64, path))
+		/// Same as [`Self::save_gzip`] but with raw bytes, see [`Self::save_bytes`].
+		///
+		/// `bytes` are compressed before writing.
+		fn save_bytes_gzip(bytes: &[u8]) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			crate::common::traced!("save_bytes_gzip", $file_ext, save, {
+				// Compress bytes and write.
+				let c = common::compress(bytes)?;
+				let c_len = c.len();
+
+				// Create PATH.
+				let mut path = Self::base_path()?;
+				std::fs::create_dir_all(&path)?;
+				path.push(Self::FILE_NAME_GZIP);
+				let path = crate::symlink::save_path(path)?;
+				crate::common::ensure_write_once!(path);
+				crate::common::ensure_quota!(path, c_len);
+				crate::common::ensure_space!(path, c_len);
+
+				// Write.
+				use std::io::Write;
+				crate::common::file_bufw!(&path, c_len, Self::WRITE_BUF_SIZE).write_all(&c)?;
+
+				Ok(crate::Metadata::new(c_len as u64, path).with_uncompressed_size(bytes.len() as u64))
+			})
 		}
 
 		/// Same as [`Self::save_gzip`] but with [`memmap2`](https://docs.rs/memmap2).
@@ -422,34 +1469,49 @@ macro_rules! impl_io {
 		///
 		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
 		unsafe fn save_gzip_memmap(&self) -> Result<crate::Metadata, anyhow::Error> {
-			// Compress bytes and write.
-			let c = common::compress(&self.to_bytes()?)?;
-			let c_len = c.len();
-
-			// Create PATH.
-			let mut path = Self::base_path()?;
-			std::fs::create_dir_all(&path)?;
-			path.push(Self::FILE_NAME_GZIP);
-
-			// Open file.
-			let file = std::fs::OpenOptions::new()
-				.read(true)
-				.write(true)
-				.create(true)
-				.open(&path)?;
-
-			// Resize file length.
-			#[cfg(target_pointer_width = "64")]
-			file.set_len(c_len as u64)?;
-			#[cfg(not(target_pointer_width = "64"))]
-			file.set_len(c_len.try_into()?)?;
-
-			// Write and flush.
-			let mut mmap = unsafe { memmap2::MmapMut::map_mut(&file)? };
-			mmap.copy_from_slice(&c);
-			mmap.flush_async()?;
-
-			Ok(crate::Metadata::new(c_len as u64, path))
+			crate::common::assert_writable!();
+			crate::common::traced!("save_gzip_memmap", $file_ext, save, {
+				self.__validate()?;
+
+				// Compress bytes and write.
+				let now = std::time::Instant::now();
+				let bytes = self.to_bytes()?;
+				let len = bytes.len();
+				let c = common::compress(&bytes)?;
+				let serialize_time = now.elapsed();
+				let c_len = c.len();
+
+				// Create PATH.
+				let mut path = Self::base_path()?;
+				std::fs::create_dir_all(&path)?;
+				path.push(Self::FILE_NAME_GZIP);
+				let path = crate::symlink::save_path(path)?;
+				crate::common::ensure_write_once!(path);
+				crate::common::ensure_quota!(path, c_len);
+				crate::common::ensure_space!(path, c_len);
+
+				// Open file.
+				let file = std::fs::OpenOptions::new()
+					.read(true)
+					.write(true)
+					.create(true)
+					.open(&path)?;
+
+				// Resize file length.
+				#[cfg(target_pointer_width = "64")]
+				file.set_len(c_len as u64)?;
+				#[cfg(not(target_pointer_width = "64"))]
+				file.set_len(c_len.try_into()?)?;
+
+				// Write and flush.
+				let mut mmap = unsafe { memmap2::MmapMut::map_mut(&file)? };
+				mmap.copy_from_slice(&c);
+				mmap.flush_async()?;
+
+				Ok(crate::Metadata::new(c_len as u64, path)
+					.with_serialize_time(serialize_time)
+					.with_uncompressed_size(len as u64))
+			})
 		}
 
 		/// Try saving to a TEMPORARY file first, then renaming it to the associated file.
@@ -465,170 +1527,704 @@ macro_rules! impl_io {
 		/// ```
 		/// Already existing `.tmp` files will be overwritten.
 		///
+		/// On Linux, if the filesystem supports it, an unnamed `O_TMPFILE` is written instead of the `.tmp` file,
+		/// it is only given a name once fully written, so a partial `.tmp` file is never visible or left behind.
+		/// If the file already exists, the `O_TMPFILE` is named `file_name` + `extension` + `.PID-NANOS.tmp`
+		/// and then renamed over it, an interrupt in between leaves that file behind, [`Self::rm_tmp()`] removes it.
+		///
 		/// This will return the amount of `bytes` saved and the [`PathBuf`] on success.
 		///
 		/// Calling this will automatically create the directories leading up to the file.
 		fn save_atomic(&self) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			self.__validate()?;
+			let now   = std::time::Instant::now();
 			let bytes = self.to_writeable_fmt()?;
+			let serialize_time = now.elapsed();
+			Ok(Self::save_bytes_atomic(&bytes)?.with_serialize_time(serialize_time))
+		}
 
-			// Create PATH.
-			let mut path = Self::base_path()?;
-			std::fs::create_dir_all(&path)?;
+		/// Same as [`Self::save_atomic`] but with raw bytes, see [`Self::save_bytes`].
+		fn save_bytes_atomic(bytes: &[u8]) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			crate::common::traced!("save_bytes_atomic", $file_ext, save, {
+				// Create PATH.
+				let mut path = Self::base_path()?;
+				std::fs::create_dir_all(&path)?;
+
+				// TMP and normal PATH.
+				let mut tmp = path.clone();
+				tmp.push(Self::FILE_NAME_TMP);
+				path.push(Self::FILE_NAME);
+				let (path, tmp) = crate::symlink::save_path_atomic(path, tmp)?;
+				crate::common::ensure_write_once!(path);
+				crate::common::ensure_quota!(path, bytes.len());
+				crate::common::ensure_space!(path, bytes.len());
+
+				// Write to an unnamed file if supported.
+				#[cfg(target_os = "linux")]
+				if crate::tmp::write_unnamed(&path, bytes)? {
+					return Ok(crate::Metadata::new(bytes.len() as u64, path));
+				}
 
-			// TMP and normal PATH.
-			let mut tmp = path.clone();
-			tmp.push(Self::FILE_NAME_TMP);
-			path.push(Self::FILE_NAME);
+				// Write to TMP.
+				use std::io::Write;
+				if let Err(e) = crate::common::file_bufw!(&tmp, bytes.len(), Self::WRITE_BUF_SIZE).write_all(bytes) {
+					std::fs::remove_file(&tmp)?;
+					bail!(e);
+				}
 
-			// Write to TMP.
-			use std::io::Write;
-			if let Err(e) = crate::common::file_bufw!(&tmp).write_all(&bytes) {
-				std::fs::remove_file(&tmp)?;
-				bail!(e);
-			}
+				// Rename TMP to normal.
+				if let Err(e) = std::fs::rename(&tmp, &path) {
+					std::fs::remove_file(&tmp)?;
+					bail!(e);
+				}
 
-			// Rename TMP to normal.
-			if let Err(e) = std::fs::rename(&tmp, &path) {
-				std::fs::remove_file(&tmp)?;
-				bail!(e);
-			}
+				Ok(crate::Metadata::new(bytes.len() as u64, path))
+			})
+		}
 
-			Ok(crate::Metadata::new(bytes.len() as u64, path))
+		/// Same as [`Self::save_atomic()`], but safe for network filesystems (NFS, SMB), see [`crate::is_network_fs()`].
+		///
+		/// On local filesystems, this is [`Self::save_atomic()`].
+		///
+		/// On network filesystems, renames can fail spuriously (e.g. while another client has the file open)
+		/// or report success before the server has the data, so instead:
+		/// 1. The data is written to the `.tmp` file and `fsync()`'ed
+		/// 2. The file is overwritten in place and `fsync()`'ed
+		/// 3. The `.tmp` file is removed
+		///
+		/// This is not atomic, but if interrupted during `2.`, the `.tmp` file is a complete copy of the new data.
+		///
+		/// This is meant for files that may be on a network share, e.g. roaming `AppData` on Windows ([`crate::Dir::Config`]).
+		///
+		/// This will return the amount of `bytes` saved and the [`PathBuf`] on success.
+		///
+		/// Calling this will automatically create the directories leading up to the file.
+		fn save_atomic_networksafe(&self) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			self.__validate()?;
+			let now   = std::time::Instant::now();
+			let bytes = self.to_writeable_fmt()?;
+			let serialize_time = now.elapsed();
+			Ok(Self::save_bytes_atomic_networksafe(&bytes)?.with_serialize_time(serialize_time))
 		}
 
-		/// Combines [`Self::save_gzip()`] and [`Self::save_atomic()`].
-		fn save_atomic_gzip(&self) -> Result<crate::Metadata, anyhow::Error> {
-			// Compress bytes.
-			let c = common::compress(&self.to_bytes()?)?;
-			let c_len = c.len();
+		/// Same as [`Self::save_atomic_networksafe`] but with raw bytes, see [`Self::save_bytes`].
+		fn save_bytes_atomic_networksafe(bytes: &[u8]) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			if !crate::is_network_fs(&Self::base_path()?)? {
+				return Self::save_bytes_atomic(bytes);
+			}
 
-			// Create PATH.
-			let mut path = Self::base_path()?;
-			std::fs::create_dir_all(&path)?;
+			crate::common::traced!("save_bytes_atomic_networksafe", $file_ext, save, {
+				// Create PATH.
+				let mut path = Self::base_path()?;
+				std::fs::create_dir_all(&path)?;
 
-			// Create TMP and normal.
-			let mut tmp = path.clone();
-			tmp.push(Self::FILE_NAME_GZIP_TMP);
-			path.push(Self::FILE_NAME_GZIP);
+				// TMP and normal PATH.
+				let mut tmp = path.clone();
+				tmp.push(Self::FILE_NAME_TMP);
+				path.push(Self::FILE_NAME);
+				let (path, tmp) = crate::symlink::save_path_atomic(path, tmp)?;
+				crate::common::ensure_write_once!(path);
+				crate::common::ensure_quota!(path, bytes.len());
+				crate::common::ensure_space!(path, bytes.len());
 
-			// Write to TMP.
-			use std::io::Write;
-			if let Err(e) = crate::common::file_bufw!(&tmp).write_all(&c) {
-				std::fs::remove_file(&tmp)?;
-				bail!(e);
-			}
+				crate::network::write_copy(&path, &tmp, bytes)?;
 
-			// Rename TMP to normal.
-			if let Err(e) = std::fs::rename(&tmp, &path) {
-				std::fs::remove_file(&tmp)?;
-				bail!(e);
+				Ok(crate::Metadata::new(bytes.len() as u64, path))
+			})
+		}
+
+		/// Save the file atomically, keeping the previous version as timestamped history.
+		///
+		/// The previous file (if any) is copied next to it, named with its modification time
+		/// in UTC, the same way as [`Self::snapshot()`], for example:
+		/// ```text,ignore
+		/// config.toml                     // <- Real file, the new data
+		/// config-2024-06-01T12-00-00.toml // <- The previous version
+		/// config-2024-05-28T09-30-00.toml // <- The version before that
+		/// ```
+		/// Then, the newest `keep` historical versions are kept and older ones are removed,
+		/// see [`Self::prune_history()`]. `keep` being `0` keeps no history.
+		///
+		/// The real file is saved with [`Self::save_atomic()`], so it is never left missing.
+		///
+		/// This will return the amount of `bytes` saved and the [`PathBuf`] on success.
+		///
+		/// Calling this will automatically create the directories leading up to the file.
+		fn save_rotated(&self, keep: usize) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			self.__validate()?;
+			let now   = std::time::Instant::now();
+			let bytes = self.to_writeable_fmt()?;
+			let serialize_time = now.elapsed();
+
+			// Copy the previous version into history.
+			let path = Self::absolute_path()?;
+			crate::common::ensure_write_once!(path);
+			if keep != 0 && path.try_exists()? {
+				let modified = std::fs::metadata(&path)?
+					.modified()
+					.unwrap_or_else(|_| std::time::SystemTime::now());
+				let history = crate::history::free_history_path(&Self::base_path()?, Self::FILE, Self::FILE_EXT, modified);
+				crate::history::reflink_or_copy(&path, &history)?;
 			}
 
-			Ok(crate::Metadata::new(c_len as u64, path))
+			let metadata = Self::save_bytes_atomic(&bytes)?.with_serialize_time(serialize_time);
+			Self::prune_history(keep)?;
+			Ok(metadata)
 		}
 
-		/// Same as [`Self::save_atomic()`] but with [`memmap2`](https://docs.rs/memmap2).
+		/// Save the file atomically, cloning an identical backup instead of writing a copy.
 		///
-		/// ## Safety
-		/// You _must_ understand all the invariants that `memmap` comes with.
+		/// If the serialized bytes are exactly the same as a historical version
+		/// (see [`Self::list_history()`]) or the `.bak` file, the file is replaced with
+		/// a copy-on-write clone of it (`FICLONE` on Linux, `clonefile()` on macOS),
+		/// so the data is only on disk once on filesystems that support it, else a normal copy.
+		/// Otherwise this is the same as [`Self::save_atomic()`].
 		///
-		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
-		unsafe fn save_atomic_memmap(&self) -> Result<crate::Metadata, anyhow::Error> {
-			// Create bytes
-			let bytes = self.to_bytes()?;
-			let len = bytes.len();
+		/// The file and the backup stay independent, later saves never modify the backup.
+		///
+		/// This is meant for frequently snapshotting mostly-unchanged data, e.g. with [`Self::save_rotated()`].
+		///
+		/// This will return the amount of `bytes` saved and the [`PathBuf`] on success.
+		fn save_dedup(&self) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			self.__validate()?;
+			let now   = std::time::Instant::now();
+			let bytes = self.to_writeable_fmt()?;
+			let serialize_time = now.elapsed();
+
+			let base = Self::base_path()?;
+			let mut candidates: Vec<PathBuf> = Self::list_history()?
+				.into_iter()
+				.filter(|(_, _, size)| *size == bytes.len() as u64)
+				.map(|(_, path, _)| path)
+				.collect();
+			candidates.push(base.join(format!("{}.bak", Self::FILE_NAME)));
+
+			let mut duplicate = None;
+			for path in candidates {
+				if common::filesize(&path) == bytes.len() as u64 && std::fs::read(&path).is_ok_and(|b| b == bytes) {
+					duplicate = Some(path);
+					break;
+				}
+			}
 
-			// Create PATH.
-			let mut path = Self::base_path()?;
-			std::fs::create_dir_all(&path)?;
+			let Some(from) = duplicate else {
+				return Ok(Self::save_bytes_atomic(&bytes)?.with_serialize_time(serialize_time));
+			};
 
-			// TMP and normal PATH.
-			let mut tmp = path.clone();
-			tmp.push(Self::FILE_NAME_TMP);
-			path.push(Self::FILE_NAME);
+			crate::common::traced!("save_dedup", $file_ext, save, {
+				let (path, tmp) = crate::symlink::save_path_atomic(base.join(Self::FILE_NAME), base.join(Self::FILE_NAME_TMP))?;
+				crate::common::ensure_write_once!(path);
 
-			// Open file.
-			let file = std::fs::OpenOptions::new()
-				.read(true)
-				.write(true)
-				.create(true)
-				.open(&tmp)?;
+				// Clone to TMP.
+				if tmp.try_exists()? {
+					std::fs::remove_file(&tmp)?;
+				}
+				if let Err(e) = crate::history::reflink_or_copy(&from, &tmp) {
+					if tmp.try_exists()? {
+						std::fs::remove_file(&tmp)?;
+					}
+					bail!(e);
+				}
 
-			// Resize file length.
-			#[cfg(target_pointer_width = "64")]
-			file.set_len(len as u64)?;
-			#[cfg(not(target_pointer_width = "64"))]
-			file.set_len(len.try_into()?)?;
+				// Rename TMP to normal.
+				if let Err(e) = std::fs::rename(&tmp, &path) {
+					std::fs::remove_file(&tmp)?;
+					bail!(e);
+				}
 
-			// Write to TMP.
-			let mut mmap = unsafe { memmap2::MmapMut::map_mut(&file)? };
-			mmap.copy_from_slice(&bytes);
+				Ok(crate::Metadata::new(bytes.len() as u64, path).with_serialize_time(serialize_time))
+			})
+		}
 
-			// Hang on flush.
-			if let Err(e) = mmap.flush() {
-				std::fs::remove_file(&tmp)?;
-				bail!(e);
-			}
+		/// Register [`Self`] for [`crate::verify_project()`].
+		///
+		/// Once registered, [`crate::verify_project()`] checks this type's file
+		/// (and its `gzip` variant) by loading it with [`Self::from_file()`],
+		/// instead of only parsing it (or skipping it, for binary formats).
+		///
+		/// Registering the same type more than once does nothing.
+		fn register_verify() {
+			crate::verify::register(crate::verify::Registered {
+				type_name: std::any::type_name::<Self>(),
+				project: Self::PROJECT_DIRECTORY,
+				path: Self::absolute_path,
+				path_gzip: Self::absolute_path_gzip,
+				load: || Self::from_file().map(drop),
+				load_gzip: || Self::from_file_gzip().map(drop),
+			});
+		}
 
-			// Rename TMP to normal.
-			if let Err(e) = std::fs::rename(&tmp, &path) {
-				std::fs::remove_file(&tmp)?;
-				bail!(e);
+		#[doc(hidden)]
+		/// Internal constant. Re-save the file for [`crate::Project::resave_all()`], [`None`] if read-only.
+		const __RESAVE: Option<fn() -> Result<crate::Metadata, anyhow::Error>> = match Self::READ_ONLY {
+			true  => None,
+			false => Some(|| Self::from_file()?.save_atomic()),
+		};
+
+		#[doc(hidden)]
+		/// Internal function. The entry of [`Self`] in a [`crate::Project`], see [`crate::project!`].
+		fn __project_entry() -> crate::__ProjectEntry {
+			crate::__ProjectEntry {
+				dir: Self::OS_DIRECTORY,
+				path: Self::absolute_path,
+				path_gzip: Self::absolute_path_gzip,
+				load: || Self::from_file().map(drop),
+				resave: Self::__RESAVE,
 			}
+		}
 
-			Ok(crate::Metadata::new(len as u64, path))
+		/// Returns the absolute PATH of the journal file, see [`crate::Journal`].
+		///
+		/// This is the file name + `.wal`, e.g. `config.toml.wal`.
+		fn absolute_path_journal() -> Result<PathBuf, anyhow::Error> {
+			Ok(crate::journal::journal_path(&Self::absolute_path()?))
 		}
 
-		/// Same as [`Self::save_atomic_gzip()`] but with [`memmap2`](https://docs.rs/memmap2).
+		/// Append a `record` to the journal instead of saving the whole file, see [`crate::Journal`].
 		///
-		/// ## Safety
-		/// You _must_ understand all the invariants that `memmap` comes with.
+		/// The file must already exist, e.g. with [`Self::save()`].
 		///
-		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
-		unsafe fn save_atomic_gzip_memmap(&self) -> Result<crate::Metadata, anyhow::Error> {
-			// Compress bytes.
-			let c = common::compress(&self.to_bytes()?)?;
-			let c_len = c.len();
+		/// The record is `fsync()`'ed before returning.
+		///
+		/// On success, this returns:
+		/// - The total size of the journal in bytes
+		/// - The [`PathBuf`] of the journal
+		fn save_journal(record: &<Self as crate::Journal>::Record) -> Result<crate::Metadata, anyhow::Error>
+		where
+			Self: crate::Journal,
+		{
+			crate::common::assert_writable!();
+			crate::common::traced!("save_journal", $file_ext, save, {
+				let path = Self::absolute_path()?;
+				crate::common::ensure_write_once!(path);
+				let size = crate::journal::append::<Self>(&path, record)?;
+				Ok(crate::Metadata::new(size, crate::journal::journal_path(&path)))
+			})
+		}
 
-			// Create PATH.
-			let mut path = Self::base_path()?;
-			std::fs::create_dir_all(&path)?;
+		/// Fold the pending journal into the file, see [`crate::Journal`].
+		///
+		/// This loads the file with [`Self::from_file()`] (replaying the journal),
+		/// saves it with [`Self::save_atomic()`], then removes the journal.
+		///
+		/// This will return the amount of `bytes` saved and the [`PathBuf`] on success.
+		fn checkpoint() -> Result<crate::Metadata, anyhow::Error>
+		where
+			Self: crate::Journal,
+		{
+			crate::common::assert_writable!();
+			let metadata = Self::from_file()?.save_atomic()?;
+			crate::journal::remove(&Self::absolute_path()?)?;
+			Ok(metadata)
+		}
 
-			// TMP and normal PATH.
-			let mut tmp = path.clone();
-			tmp.push(Self::FILE_NAME_GZIP_TMP);
-			path.push(Self::FILE_NAME_GZIP);
+		/// Returns the absolute PATH of the audit log, see [`crate::AuditLog`].
+		///
+		/// This is the file name + `.audit`, e.g. `config.toml.audit`.
+		fn absolute_path_audit() -> Result<PathBuf, anyhow::Error> {
+			Ok(crate::audit::audit_path(&Self::absolute_path()?))
+		}
 
-			// Open file.
-			let file = std::fs::OpenOptions::new()
-				.read(true)
-				.write(true)
-				.create(true)
-				.open(&tmp)?;
+		/// Append a `record` to the audit log, see [`crate::AuditLog`].
+		///
+		/// The file itself does not need to exist.
+		///
+		/// The record is `fsync()`'ed before returning.
+		///
+		/// On success, this returns:
+		/// - The total size of the audit log in bytes
+		/// - The [`PathBuf`] of the audit log
+		///
+		/// Calling this will automatically create the directories leading up to the audit log.
+		fn append_audit(record: &<Self as crate::AuditLog>::Record) -> Result<crate::Metadata, anyhow::Error>
+		where
+			Self: crate::AuditLog,
+		{
+			crate::common::assert_writable!();
+			crate::common::traced!("append_audit", $file_ext, save, {
+				std::fs::create_dir_all(Self::base_path()?)?;
+				let path = Self::absolute_path_audit()?;
+				let size = crate::audit::append::<Self>(&path, record)?;
+				Ok(crate::Metadata::new(size, path))
+			})
+		}
 
-			// Resize file length.
-			#[cfg(target_pointer_width = "64")]
-			file.set_len(c_len as u64)?;
-			#[cfg(not(target_pointer_width = "64"))]
-			file.set_len(c_len.try_into()?)?;
+		/// Rotate the audit log if it is `max_size` bytes or larger, see [`crate::AuditLog`].
+		///
+		/// The audit log is renamed to a file with the current time in UTC
+		/// between the file name and `.audit`, the next record starts a new log, e.g:
+		/// ```text,ignore
+		/// config.toml.audit                     // <- Current log, new records
+		/// config.toml-2024-06-01T12-00-00.audit // <- Rotated log
+		/// config.toml-2024-05-28T09-30-00.audit // <- Older rotated log
+		/// ```
+		///
+		/// An empty or missing audit log is never rotated.
+		///
+		/// On success, this returns the size and [`PathBuf`] of the rotated log,
+		/// or `None` if it was not rotated.
+		fn rotate_audit(max_size: u64) -> Result<Option<crate::Metadata>, anyhow::Error>
+		where
+			Self: crate::AuditLog,
+		{
+			crate::common::assert_writable!();
+			let rotated = crate::audit::rotate(&Self::base_path()?, Self::FILE_NAME, &Self::absolute_path_audit()?, max_size)?;
+			Ok(rotated.map(|(size, path)| crate::Metadata::new(size, path)))
+		}
 
-			// Write to TMP.
-			let mut mmap = unsafe { memmap2::MmapMut::map_mut(&file)? };
-			mmap.copy_from_slice(&c);
+		/// Iterate over all audit records, oldest first, see [`crate::AuditLog`].
+		///
+		/// This reads the rotated logs (oldest first), then the current one.
+		///
+		/// Logs are read lazily, one record at a time.
+		fn iter_audit() -> Result<crate::AuditRecords<Self>, anyhow::Error>
+		where
+			Self: crate::AuditLog,
+		{
+			let files = crate::audit::files(&Self::base_path()?, Self::FILE_NAME, &Self::absolute_path_audit()?)?;
+			Ok(crate::AuditRecords::new(files))
+		}
 
-			// Hang on flush.
-			if let Err(e) = mmap.flush() {
-				std::fs::remove_file(&tmp)?;
-				bail!(e);
-			}
+		/// Save a copy of [`Self`] with its sensitive fields replaced, see [`crate::Redact`].
+		///
+		/// `self` is not modified, the copy is what gets validated and saved with [`Self::save()`].
+		///
+		/// This will return the amount of `bytes` saved and the [`PathBuf`] on success.
+		fn save_redacted(&self) -> Result<crate::Metadata, anyhow::Error>
+		where
+			Self: crate::Redact,
+		{
+			crate::common::assert_writable!();
+			let mut redacted = self.clone();
+			crate::Redact::redact(&mut redacted);
+			redacted.save()
+		}
 
-			// Rename TMP to normal.
-			if let Err(e) = std::fs::rename(&tmp, &path) {
-				std::fs::remove_file(&tmp)?;
-				bail!(e);
-			}
+		/// Load the file with [`Self::from_file()`], then fill in the redacted fields, see [`crate::Redact`].
+		fn from_file_redacted() -> Result<Self, anyhow::Error>
+		where
+			Self: crate::Redact,
+		{
+			let mut s = Self::from_file()?;
+			crate::Redact::fill(&mut s)?;
+			Ok(s)
+		}
 
-			Ok(crate::Metadata::new(c_len as u64, path))
+		#[cfg(feature = "signing")]
+		#[inline(always)]
+		/// The absolute PATH of the signature file of [`Self::save_signed()`], e.g. `preset.toml.sig`.
+		fn absolute_path_signature() -> Result<PathBuf, anyhow::Error> {
+			Ok(crate::signing::signature_path(&Self::absolute_path()?))
+		}
+
+		#[cfg(feature = "signing")]
+		/// Save the file atomically, then sign it with `key`.
+		///
+		/// The `ed25519` signature of the file's exact bytes is saved (also atomically)
+		/// next to the file, see [`Self::absolute_path_signature()`]:
+		/// ```text,ignore
+		/// preset.toml     // <- Real file, unchanged, still readable with `from_file()`
+		/// preset.toml.sig // <- Detached signature
+		/// ```
+		/// Load it with [`Self::from_file_verified()`].
+		///
+		/// If interrupted between saving the file and the signature, the signature no longer matches,
+		/// so the file is refused by [`Self::from_file_verified()`] rather than trusted.
+		///
+		/// This will return the amount of `bytes` saved and the [`PathBuf`] of the file on success.
+		fn save_signed(&self, key: &crate::SigningKey) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			self.__validate()?;
+			let now   = std::time::Instant::now();
+			let bytes = self.to_writeable_fmt()?;
+			let serialize_time = now.elapsed();
+
+			let metadata = Self::save_bytes_atomic(&bytes)?;
+			crate::signing::save_signature(&metadata.to_parts().1, &bytes, key)?;
+			Ok(metadata.with_serialize_time(serialize_time))
+		}
+
+		#[cfg(feature = "signing")]
+		/// Read the file, verify its signature with `key`, then deserialize it.
+		///
+		/// This errors without deserializing if the signature file is missing
+		/// or if the file was modified after [`Self::save_signed()`].
+		///
+		/// Pending [`crate::Journal`] records are _not_ replayed, since they are not signed.
+		fn from_file_verified(key: &crate::VerifyingKey) -> Result<Self, anyhow::Error> {
+			Self::from_path_verified(Self::absolute_path()?, key)
+		}
+
+		#[cfg(feature = "signing")]
+		/// Same as [`Self::from_file_verified()`] but from any `path`, e.g. a preset shipped with your program.
+		///
+		/// The signature is read from `path` + `.sig`.
+		fn from_path_verified<P: std::convert::AsRef<std::path::Path>>(path: P, key: &crate::VerifyingKey) -> Result<Self, anyhow::Error> {
+			crate::common::traced!("from_path_verified", $file_ext, load, {
+				let path  = path.as_ref();
+				let bytes = crate::common::path_to_bytes(path)?;
+				crate::signing::verify(path, &bytes, key)?;
+				let s = Self::from_bytes(&bytes)?;
+				s.__validate()?;
+				Ok(s)
+			})
+		}
+
+		/// Combines [`Self::save_gzip()`] and [`Self::save_atomic()`].
+		fn save_atomic_gzip(&self) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			self.__validate()?;
+			let now   = std::time::Instant::now();
+			let bytes = self.to_bytes()?;
+			let serialize_time = now.elapsed();
+			Ok(Self::save_bytes_atomic_gzip(&bytes)?.with_serialize_time(serialize_time))
+		}
+
+		/// Same as [`Self::save_atomic_gzip`] but with raw bytes, see [`Self::save_bytes`].
+		///
+		/// `bytes` are compressed before writing.
+		fn save_bytes_atomic_gzip(bytes: &[u8]) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			crate::common::traced!("save_bytes_atomic_gzip", $file_ext, save, {
+				// Compress bytes.
+				let c = common::compress(bytes)?;
+				let c_len = c.len();
+
+				// Create PATH.
+				let mut path = Self::base_path()?;
+				std::fs::create_dir_all(&path)?;
+
+				// Create TMP and normal.
+				let mut tmp = path.clone();
+				tmp.push(Self::FILE_NAME_GZIP_TMP);
+				path.push(Self::FILE_NAME_GZIP);
+				let (path, tmp) = crate::symlink::save_path_atomic(path, tmp)?;
+				crate::common::ensure_write_once!(path);
+				crate::common::ensure_quota!(path, c_len);
+				crate::common::ensure_space!(path, c_len);
+
+				// Write to an unnamed file if supported.
+				#[cfg(target_os = "linux")]
+				if crate::tmp::write_unnamed(&path, &c)? {
+					return Ok(crate::Metadata::new(c_len as u64, path).with_uncompressed_size(bytes.len() as u64));
+				}
+
+				// Write to TMP.
+				use std::io::Write;
+				if let Err(e) = crate::common::file_bufw!(&tmp, c_len, Self::WRITE_BUF_SIZE).write_all(&c) {
+					std::fs::remove_file(&tmp)?;
+					bail!(e);
+				}
+
+				// Rename TMP to normal.
+				if let Err(e) = std::fs::rename(&tmp, &path) {
+					std::fs::remove_file(&tmp)?;
+					bail!(e);
+				}
+
+				Ok(crate::Metadata::new(c_len as u64, path).with_uncompressed_size(bytes.len() as u64))
+			})
+		}
+
+		/// Save [`Self`] using as little disk space as possible, for when the disk is (nearly) full.
+		///
+		/// This is [`Self::save_atomic_gzip()`] with the best (slowest) compression, except:
+		/// - [`crate::set_min_free_space()`] is ignored, only the compressed bytes must fit
+		/// - No sidecar files are written (history, backups, schemas, signatures)
+		/// - The uncompressed file is removed afterwards, freeing its space
+		///
+		/// The file is saved as the `gzip` variant, read it back with
+		/// [`Self::from_file_auto()`] or [`Self::from_file_gzip()`], e.g:
+		/// ```rust,ignore
+		/// if let Err(e) = state.save() {
+		///     if e.downcast_ref::<disk::InsufficientSpace>().is_some() {
+		///         state.save_minimal()?;
+		///     }
+		/// }
+		/// ```
+		///
+		/// This will return the amount of `bytes` saved and the [`PathBuf`] on success.
+		fn save_minimal(&self) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			self.__validate()?;
+			crate::common::traced!("save_minimal", $file_ext, save, {
+				let now   = std::time::Instant::now();
+				let bytes = self.to_bytes()?;
+				let c     = common::compress_with(&bytes, flate2::Compression::best())?;
+				let serialize_time = now.elapsed();
+				let c_len = c.len();
+
+				// Create PATH.
+				let mut path = Self::base_path()?;
+				std::fs::create_dir_all(&path)?;
+
+				// Create TMP and normal.
+				let mut tmp = path.clone();
+				tmp.push(Self::FILE_NAME_GZIP_TMP);
+				path.push(Self::FILE_NAME_GZIP);
+				let (path, tmp) = crate::symlink::save_path_atomic(path, tmp)?;
+				crate::common::ensure_write_once!(path);
+				crate::common::ensure_quota!(path, c_len);
+
+				// Write to TMP.
+				use std::io::Write;
+				if let Err(e) = crate::common::file_bufw!(&tmp, c_len, Self::WRITE_BUF_SIZE).write_all(&c) {
+					std::fs::remove_file(&tmp)?;
+					bail!(e);
+				}
+
+				// Rename TMP to normal.
+				if let Err(e) = std::fs::rename(&tmp, &path) {
+					std::fs::remove_file(&tmp)?;
+					bail!(e);
+				}
+
+				// Remove the (now older) uncompressed file.
+				match std::fs::remove_file(Self::absolute_path()?) {
+					Err(e) if e.kind() != std::io::ErrorKind::NotFound => bail!(e),
+					_ => (),
+				}
+
+				Ok(crate::Metadata::new(c_len as u64, path)
+					.with_uncompressed_size(bytes.len() as u64)
+					.with_serialize_time(serialize_time))
+			})
+		}
+
+		/// Same as [`Self::save_atomic()`] but with [`memmap2`](https://docs.rs/memmap2).
+		///
+		/// ## Safety
+		/// You _must_ understand all the invariants that `memmap` comes with.
+		///
+		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
+		unsafe fn save_atomic_memmap(&self) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			crate::common::traced!("save_atomic_memmap", $file_ext, save, {
+				self.__validate()?;
+
+				// Create bytes
+				let now = std::time::Instant::now();
+				let bytes = self.to_bytes()?;
+				let serialize_time = now.elapsed();
+				let len = bytes.len();
+
+				// Create PATH.
+				let mut path = Self::base_path()?;
+				std::fs::create_dir_all(&path)?;
+
+				// TMP and normal PATH.
+				let mut tmp = path.clone();
+				tmp.push(Self::FILE_NAME_TMP);
+				path.push(Self::FILE_NAME);
+				let (path, tmp) = crate::symlink::save_path_atomic(path, tmp)?;
+				crate::common::ensure_write_once!(path);
+				crate::common::ensure_quota!(path, len);
+				crate::common::ensure_space!(path, len);
+
+				// Open file.
+				let file = std::fs::OpenOptions::new()
+					.read(true)
+					.write(true)
+					.create(true)
+					.open(&tmp)?;
+
+				// Resize file length.
+				#[cfg(target_pointer_width = "64")]
+				file.set_len(len as u64)?;
+				#[cfg(not(target_pointer_width = "64"))]
+				file.set_len(len.try_into()?)?;
+
+				// Write to TMP.
+				let mut mmap = unsafe { memmap2::MmapMut::map_mut(&file)? };
+				mmap.copy_from_slice(&bytes);
+
+				// Hang on flush.
+				if let Err(e) = mmap.flush() {
+					std::fs::remove_file(&tmp)?;
+					bail!(e);
+				}
+
+				// Rename TMP to normal.
+				if let Err(e) = std::fs::rename(&tmp, &path) {
+					std::fs::remove_file(&tmp)?;
+					bail!(e);
+				}
+
+				Ok(crate::Metadata::new(len as u64, path).with_serialize_time(serialize_time))
+			})
+		}
+
+		/// Same as [`Self::save_atomic_gzip()`] but with [`memmap2`](https://docs.rs/memmap2).
+		///
+		/// ## Safety
+		/// You _must_ understand all the invariants that `memmap` comes with.
+		///
+		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
+		unsafe fn save_atomic_gzip_memmap(&self) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			crate::common::traced!("save_atomic_gzip_memmap", $file_ext, save, {
+				self.__validate()?;
+
+				// Compress bytes.
+				let now = std::time::Instant::now();
+				let bytes = self.to_bytes()?;
+				let len = bytes.len();
+				let c = common::compress(&bytes)?;
+				let serialize_time = now.elapsed();
+				let c_len = c.len();
+
+				// Create PATH.
+				let mut path = Self::base_path()?;
+				std::fs::create_dir_all(&path)?;
+
+				// TMP and normal PATH.
+				let mut tmp = path.clone();
+				tmp.push(Self::FILE_NAME_GZIP_TMP);
+				path.push(Self::FILE_NAME_GZIP);
+				let (path, tmp) = crate::symlink::save_path_atomic(path, tmp)?;
+				crate::common::ensure_write_once!(path);
+				crate::common::ensure_quota!(path, c_len);
+				crate::common::ensure_space!(path, c_len);
+
+				// Open file.
+				let file = std::fs::OpenOptions::new()
+					.read(true)
+					.write(true)
+					.create(true)
+					.open(&tmp)?;
+
+				// Resize file length.
+				#[cfg(target_pointer_width = "64")]
+				file.set_len(c_len as u64)?;
+				#[cfg(not(target_pointer_width = "64"))]
+				file.set_len(c_len.try_into()?)?;
+
+				// Write to TMP.
+				let mut mmap = unsafe { memmap2::MmapMut::map_mut(&file)? };
+				mmap.copy_from_slice(&c);
+
+				// Hang on flush.
+				if let Err(e) = mmap.flush() {
+					std::fs::remove_file(&tmp)?;
+					bail!(e);
+				}
+
+				// Rename TMP to normal.
+				if let Err(e) = std::fs::rename(&tmp, &path) {
+					std::fs::remove_file(&tmp)?;
+					bail!(e);
+				}
+
+				Ok(crate::Metadata::new(c_len as u64, path)
+					.with_serialize_time(serialize_time)
+					.with_uncompressed_size(len as u64))
+			})
 		}
 
 		/// Rename the associated file before attempting to delete it.
@@ -646,36 +2242,48 @@ macro_rules! impl_io {
 		/// ```
 		/// Already existing `.tmp` files will be overwritten.
 		fn rm_atomic() -> Result<crate::Metadata, anyhow::Error> {
-			let mut path = Self::base_path()?;
+			crate::common::assert_writable!();
+			crate::common::traced!("rm_atomic", $file_ext, remove, {
+				let mut path = Self::base_path()?;
 
-			let mut tmp = path.clone();
-			tmp.push(Self::FILE_NAME_TMP);
-			path.push(Self::FILE_NAME);
+				let mut tmp = path.clone();
+				tmp.push(Self::FILE_NAME_TMP);
+				path.push(Self::FILE_NAME);
 
-			if !path.exists() { return Ok(crate::Metadata::zero(path)) }
+				if !path.exists() { return Ok(crate::Metadata::zero(path)) }
 
-			let size = crate::common::filesize(&path);
-			std::fs::rename(&path, &tmp)?;
-			std::fs::remove_file(&tmp)?;
+				// A followed symlink is kept, only its target is removed.
+				let (path, tmp) = crate::symlink::save_path_atomic(path, tmp)?;
 
-			Ok(crate::Metadata::new(size, path))
+				let size = crate::common::filesize(&path);
+				std::fs::rename(&path, &tmp)?;
+				std::fs::remove_file(&tmp)?;
+
+				Ok(crate::Metadata::new(size, path))
+			})
 		}
 
 		/// Same as [`Self::rm_atomic()`] but looks for the `.gz` extension.
 		fn rm_atomic_gzip() -> Result<crate::Metadata, anyhow::Error> {
-			let mut path = Self::base_path()?;
+			crate::common::assert_writable!();
+			crate::common::traced!("rm_atomic_gzip", $file_ext, remove, {
+				let mut path = Self::base_path()?;
 
-			let mut tmp = path.clone();
-			tmp.push(Self::FILE_NAME_GZIP_TMP);
-			path.push(Self::FILE_NAME_GZIP);
+				let mut tmp = path.clone();
+				tmp.push(Self::FILE_NAME_GZIP_TMP);
+				path.push(Self::FILE_NAME_GZIP);
 
-			if !path.exists() { return Ok(crate::Metadata::zero(path)) }
+				if !path.exists() { return Ok(crate::Metadata::zero(path)) }
 
-			let size = crate::common::filesize(&path);
-			std::fs::rename(&path, &tmp)?;
-			std::fs::remove_file(&tmp)?;
+				// A followed symlink is kept, only its target is removed.
+				let (path, tmp) = crate::symlink::save_path_atomic(path, tmp)?;
 
-			Ok(crate::Metadata::new(size, path))
+				let size = crate::common::filesize(&path);
+				std::fs::rename(&path, &tmp)?;
+				std::fs::remove_file(&tmp)?;
+
+				Ok(crate::Metadata::new(size, path))
+			})
 		}
 
 		/// Try deleting any leftover `.tmp` files from [`Self::save_atomic()`] or [`Self::save_atomic_gzip()`]
@@ -683,18 +2291,34 @@ macro_rules! impl_io {
 		/// This will return success if the files don't exist or if deleted.
 		///
 		/// It will return failure if files existed but could not be deleted or if any other error occurs.
+		///
+		/// Each file is handled independently, see [`Self::rm_tmp_report()`] for what was removed.
 		fn rm_tmp() -> Result<(), anyhow::Error> {
-			let mut tmp = Self::base_path()?;
-			let mut gzip = tmp.clone();
+			crate::common::assert_writable!();
+			crate::common::traced!("rm_tmp", $file_ext, {
+				let report = Self::rm_tmp_report()?;
 
-			tmp.push(Self::FILE_NAME_TMP);
-			gzip.push(Self::FILE_NAME_GZIP_TMP);
+				for file in [&report.tmp, &report.tmp_gzip].into_iter().chain(&report.unnamed) {
+					if let Some(e) = &file.error {
+						bail!("failed to remove {:?}: {e}", file.path);
+					}
+				}
 
-			if !tmp.exists() && !gzip.exists() { return Ok(()) }
+				Ok(())
+			})
+		}
 
-			std::fs::remove_file(tmp)?;
-			std::fs::remove_file(gzip)?;
-			Ok(())
+		/// Same as [`Self::rm_tmp()`] but returns which `.tmp` files existed, their sizes and what was removed.
+		///
+		/// The `.tmp`, `.gz.tmp` and `O_TMPFILE` (`.PID-NANOS.tmp`, see [`Self::save_atomic()`]) files
+		/// are handled independently, an error removing one is recorded in the [`crate::RmTmpReport`] instead of returned.
+		///
+		/// This only errors if the PATH could not be determined or its directory could not be read.
+		fn rm_tmp_report() -> Result<crate::RmTmpReport, anyhow::Error> {
+			crate::common::assert_writable!();
+			let base    = Self::base_path()?;
+			let unnamed = crate::tmp::unnamed_tmp(&base, &[Self::FILE_NAME, Self::FILE_NAME_GZIP])?;
+			Ok(crate::RmTmpReport::remove(base.join(Self::FILE_NAME_TMP), base.join(Self::FILE_NAME_GZIP_TMP), unnamed))
 		}
 
 		#[inline(always)]
@@ -718,12 +2342,317 @@ macro_rules! impl_io {
 			Ok(crate::Metadata::new(size, path))
 		}
 
+		/// Reads a range of bytes of the associated file of [`Self`].
+		///
+		/// Unlike [`Self::file_bytes`], this takes any range, for example:
+		/// ```rust,ignore
+		/// State::file_bytes_range(..);      // The whole file
+		/// State::file_bytes_range(100..);   // From byte 100 to the end
+		/// State::file_bytes_range(..25);    // The first 25 bytes
+		/// State::file_bytes_range(10..=20); // Bytes 10 to 20 (inclusive)
+		/// ```
+		///
+		/// ## Errors
+		/// If the range's start is greater than its end, if
+		/// the end is greater than the file length, or if
+		/// an inclusive end (or exclusive start) of `usize::MAX` overflows, this returns error.
+		fn file_bytes_range<R: std::ops::RangeBounds<usize>>(range: R) -> Result<Vec<u8>, anyhow::Error> {
+			use std::io::{Read,Seek,SeekFrom};
+			use std::ops::Bound;
+
+			let mut file = std::fs::File::open(Self::absolute_path()?)?;
+			let len = file.metadata()?.len();
+
+			let start = match range.start_bound() {
+				Bound::Included(s) => *s as u64,
+				Bound::Excluded(s) => (*s as u64).checked_add(1).ok_or_else(|| anyhow!("file_bytes_range(): start ({s}) + 1 overflows"))?,
+				Bound::Unbounded   => 0,
+			};
+			let end = match range.end_bound() {
+				Bound::Included(e) => (*e as u64).checked_add(1).ok_or_else(|| anyhow!("file_bytes_range(): end ({e}) + 1 overflows"))?,
+				Bound::Excluded(e) => *e as u64,
+				Bound::Unbounded   => len,
+			};
+
+			if start > end {
+				bail!("file_bytes_range(): start ({start}) > end ({end})");
+			}
+			if end > len {
+				bail!("file_bytes_range(): file length ({len}) less than end ({end})");
+			}
+
+			let mut buf = vec![0; usize::try_from(end - start)?];
+			file.seek(SeekFrom::Start(start))?;
+			file.read_exact(&mut buf)?;
+
+			Ok(buf)
+		}
+
+		/// Reads the last `len` bytes of the associated file of [`Self`].
+		///
+		/// This is useful for reading a footer without reading the whole file.
+		///
+		/// ## Errors
+		/// If `len` is greater than the file length, this returns error.
+		fn file_bytes_tail(len: usize) -> Result<Vec<u8>, anyhow::Error> {
+			use std::io::{Read,Seek,SeekFrom};
+
+			let mut file = std::fs::File::open(Self::absolute_path()?)?;
+			let file_len = file.metadata()?.len();
+
+			if len as u64 > file_len {
+				bail!("file_bytes_tail(): file length ({file_len}) less than len ({len})");
+			}
+
+			let mut buf = vec![0; len];
+			file.seek(SeekFrom::End(-(len as i64)))?;
+			file.read_exact(&mut buf)?;
+
+			Ok(buf)
+		}
+
+		/// Returns an iterator over the bytes of the associated file of [`Self`], in `chunk_size` chunks.
+		///
+		/// The file is read lazily, so large files can be
+		/// processed (hashed, uploaded) without reading it all into memory.
+		///
+		/// ## Errors
+		/// If `chunk_size` is `0` or the file could not be opened, this returns error.
+		fn file_chunks(chunk_size: usize) -> Result<crate::Chunks, anyhow::Error> {
+			if chunk_size == 0 {
+				bail!("file_chunks(): chunk_size is 0");
+			}
+
+			let file = std::fs::File::open(Self::absolute_path()?)?;
+			Ok(crate::Chunks::new(Box::new(file), chunk_size))
+		}
+
+		/// Same as [`Self::file_chunks`], but for the _decompressed_ bytes of the `gzip` file.
+		fn file_chunks_gzip(chunk_size: usize) -> Result<crate::Chunks, anyhow::Error> {
+			if chunk_size == 0 {
+				bail!("file_chunks_gzip(): chunk_size is 0");
+			}
+
+			let decoder = flate2::bufread::GzDecoder::new(crate::common::file_bufr_gzip!());
+			Ok(crate::Chunks::new(Box::new(decoder), chunk_size))
+		}
+
+		/// Reads a range of bytes (`start..end`) of the _decompressed_ `gzip` file of [`Self`].
+		///
+		/// Decompression is streamed and stops once `end` is reached,
+		/// so reading a header does not decompress the whole file.
+		///
+		/// ## Errors
+		/// If `start` is greater than `end`, or if the decompressed
+		/// data is shorter than `end`, this returns error.
+		fn file_bytes_gzip(start: usize, end: usize) -> Result<Vec<u8>, anyhow::Error> {
+			use std::io::Read;
+
+			if start > end {
+				bail!("file_bytes_gzip(): start > end");
+			}
+
+			let mut decoder = flate2::bufread::GzDecoder::new(crate::common::file_bufr_gzip!());
+
+			// Skip the bytes before `start`.
+			let skipped = std::io::copy(&mut (&mut decoder).take(start as u64), &mut std::io::sink())?;
+			if skipped != start as u64 {
+				bail!("file_bytes_gzip(): decompressed length ({skipped}) less than start ({start})");
+			}
+
+			let mut buf = vec![0; end - start];
+			decoder.read_exact(&mut buf)?;
+
+			Ok(buf)
+		}
+
 		$crate::common::impl_file_bytes!("64", u64);
 		$crate::common::impl_file_bytes!("32", u32);
 	}
 }
 pub(crate) use impl_io;
 
+//---------------------------------------------------------------------------------------------------- impl_strict
+// Implements strict (deny unknown fields) functions for text formats.
+//
+// The trait must implement `from_bytes_strict()`.
+macro_rules! impl_strict {
+	() => {
+		/// Same as [`Self::from_file`] but errors on unknown fields.
+		///
+		/// Normally, fields in the file that do not exist in [`Self`] are ignored.
+		///
+		/// This is useful for catching typos in hand-edited files, for example:
+		/// ```text,ignore
+		/// volumee = 50 # <- typo, `volume` was intended
+		/// ```
+		/// The returned error will contain the PATH of all unknown fields, e.g:
+		/// ```text,ignore
+		/// unknown fields: volumee, some.nested.field
+		/// ```
+		fn from_file_strict() -> Result<Self, anyhow::Error> {
+			let s = Self::from_bytes_strict(&Self::read_to_bytes()?)?;
+			s.__validate()?;
+			Ok(s)
+		}
+
+		/// Same as [`Self::from_path`] but errors on unknown fields.
+		///
+		/// See [`Self::from_file_strict`] for more info.
+		fn from_path_strict<P: std::convert::AsRef<std::path::Path>>(path: P) -> Result<Self, anyhow::Error> {
+			let s = Self::from_bytes_strict(&crate::common::path_to_bytes(path.as_ref())?)?;
+			s.__validate()?;
+			Ok(s)
+		}
+	}
+}
+pub(crate) use impl_strict;
+
+//---------------------------------------------------------------------------------------------------- impl_upgrade
+// Implements missing field repair for text formats.
+//
+// The trait must implement `__from_bytes_upgraded()`.
+macro_rules! impl_upgrade {
+	() => {
+		/// Read the file and deserialize into [`Self`], filling in missing fields from [`Default`].
+		///
+		/// Any fields missing in the file are taken from [`Self::default()`], for example:
+		/// ```text,ignore
+		/// # `state.toml` on disk, created by an older version.
+		/// volume = 50
+		///
+		/// # `Self::default()`, a newer version with a new field.
+		/// volume = 100
+		/// theme  = "dark"
+		///
+		/// # The returned `Self` and the upgraded `state.toml`.
+		/// volume = 50
+		/// theme  = "dark"
+		/// ```
+		/// If any fields were missing, the file is re-written with [`Self::save_atomic()`].
+		///
+		/// On success, this returns [`Self`] and the [`crate::Metadata`] of the re-written file, if it was re-written.
+		fn from_file_upgraded() -> Result<(Self, Option<crate::Metadata>), anyhow::Error>
+		where
+			Self: Default,
+		{
+			crate::common::assert_writable!();
+			let (s, upgraded) = Self::__from_bytes_upgraded(&Self::read_to_bytes()?)?;
+			s.__validate()?;
+
+			match upgraded {
+				true  => { let metadata = s.save_atomic()?; Ok((s, Some(metadata))) },
+				false => Ok((s, None)),
+			}
+		}
+	}
+}
+pub(crate) use impl_upgrade;
+
+//---------------------------------------------------------------------------------------------------- impl_merge
+// Implements `save_merged()` for text formats.
+macro_rules! impl_merge {
+	() => {
+		/// Save [`Self`] atomically, merging in changes made to the file by someone else.
+		///
+		/// `base` must be the value as it was loaded (e.g. a clone right after [`Self::from_file()`]).
+		///
+		/// [`Self`] (ours), the file on disk (theirs) and `base` are merged key by key:
+		/// ```text,ignore
+		/// # base          # ours (Self)    # theirs (disk)    # saved
+		/// volume = 50     volume = 75      volume = 50        volume = 75
+		/// theme  = "dark" theme  = "dark"  theme  = "light"   theme  = "light"
+		/// ```
+		/// - Keys only changed on one side take that side's value
+		/// - Keys changed the same way on both sides are kept
+		/// - Nested tables/maps are merged recursively
+		/// - Arrays and other values are compared as a whole
+		///
+		/// If the same key was changed differently on both sides, this errors with
+		/// the conflicting keys (e.g. `audio.volume`) and nothing is saved.
+		///
+		/// If the file does not exist, [`Self`] is saved as-is.
+		///
+		/// On success, this returns the merged [`Self`] and the [`crate::Metadata`] of the saved file.
+		///
+		/// ## Concurrency
+		/// The file is not locked, a write by someone else between reading and
+		/// saving the file (a very small window) is overwritten.
+		fn save_merged(&self, base: &Self) -> Result<(Self, crate::Metadata), anyhow::Error> {
+			crate::common::assert_writable!();
+			self.__validate()?;
+
+			let theirs = match Self::read_to_bytes() {
+				Ok(bytes) => Self::from_bytes(&bytes)?,
+				Err(e) if e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound) => {
+					let merged: Self = serde_json::from_value(serde_json::to_value(self)?)?;
+					let metadata = merged.save_atomic()?;
+					return Ok((merged, metadata));
+				},
+				Err(e) => return Err(e),
+			};
+
+			let merged = crate::merge::merge3(
+				serde_json::to_value(base)?,
+				serde_json::to_value(self)?,
+				serde_json::to_value(&theirs)?,
+			)?;
+
+			let merged: Self = serde_json::from_value(merged)?;
+			merged.__validate()?;
+			let metadata = merged.save_atomic()?;
+			Ok((merged, metadata))
+		}
+	}
+}
+pub(crate) use impl_merge;
+
+//---------------------------------------------------------------------------------------------------- impl_schema
+// Implements JSON Schema export for text formats.
+#[cfg(feature = "schemars")]
+macro_rules! impl_schema {
+	() => {
+		#[inline(always)]
+		/// The absolute PATH of the JSON Schema file associated with this struct.
+		///
+		/// This is [`Self::FILE`] + `.schema.json`, located next to the associated file, e.g:
+		/// ```text,ignore
+		/// config.toml        // <- Real file
+		/// config.schema.json // <- JSON Schema
+		/// ```
+		fn absolute_path_schema() -> Result<PathBuf, anyhow::Error> {
+			let mut base = Self::base_path()?;
+			base.push(format!("{}.schema.json", Self::FILE));
+
+			common::assert_safe_path(&base)?;
+
+			Ok(base)
+		}
+
+		/// Generate a [JSON Schema](https://json-schema.org) of [`Self`] with [`schemars`](https://docs.rs/schemars)
+		/// and save it next to the associated file, see [`Self::absolute_path_schema()`].
+		///
+		/// Editors can use this for autocompletion/validation when users edit the file by hand.
+		///
+		/// Calling this will automatically create the directories leading up to the file.
+		fn write_schema() -> Result<crate::Metadata, anyhow::Error>
+		where
+			Self: schemars::JsonSchema,
+		{
+			use std::io::Write;
+			let bytes = serde_json::to_vec_pretty(&schemars::schema_for!(Self))?;
+
+			std::fs::create_dir_all(Self::base_path()?)?;
+			let path = Self::absolute_path_schema()?;
+
+			crate::common::file_bufw!(&path, bytes.len(), Self::WRITE_BUF_SIZE).write_all(&bytes)?;
+			Ok(crate::Metadata::new(bytes.len() as u64, path))
+		}
+	}
+}
+#[cfg(feature = "schemars")]
+pub(crate) use impl_schema;
+
 //---------------------------------------------------------------------------------------------------- impl_common
 // Implements the CONSTANTS and common PATH methods for all traits.
 macro_rules! impl_common {
@@ -746,6 +2675,47 @@ macro_rules! impl_common {
 		const FILE_NAME_TMP: &'static str;
 		/// What the `gzip` + `tmp` variant of the filename will be.
 		const FILE_NAME_GZIP_TMP: &'static str;
+		/// The [`std::io::BufWriter`] capacity used when saving, `64 KiB` by default.
+		///
+		/// Writes larger than this skip the buffer entirely, see [`Self::save_with_buffer()`] to tune a single save.
+		const WRITE_BUF_SIZE: usize = crate::common::WRITE_BUF_SIZE;
+		/// If [`Self`] is read-only, see [`crate::ReadOnly`].
+		///
+		/// The macros set this if [`crate::ReadOnly`] is implemented.
+		const READ_ONLY: bool = false;
+		/// If [`Self`] is write-once, see [`crate::WriteOnce`].
+		///
+		/// The macros set this if [`crate::WriteOnce`] is implemented.
+		const WRITE_ONCE: bool = false;
+
+		#[doc(hidden)]
+		#[inline(always)]
+		/// Internal function. Calls [`crate::Validate::validate()`] if implemented.
+		///
+		/// This is overridden by the macros, manual implementations do nothing.
+		fn __validate(&self) -> Result<(), anyhow::Error> {
+			Ok(())
+		}
+
+		#[doc(hidden)]
+		#[inline(always)]
+		/// Internal function. Replays the pending journal if [`crate::Journal`] is implemented.
+		///
+		/// This is overridden by the macros, manual implementations do nothing.
+		fn __journal(&mut self) -> Result<(), anyhow::Error> {
+			Ok(())
+		}
+
+		#[doc(hidden)]
+		#[inline(always)]
+		/// Internal function. Removes the journal if [`crate::Journal`] is implemented
+		/// and `metadata` is of the file itself, which was just saved or removed.
+		///
+		/// This is overridden by the macros, manual implementations do nothing.
+		#[allow(unused_variables)]
+		fn __journal_reset(metadata: &crate::Metadata) -> Result<(), anyhow::Error> {
+			Ok(())
+		}
 
 		#[inline]
 		/// Create the directories leading up-to the file.
@@ -775,68 +2745,472 @@ macro_rules! impl_common {
 			}
 		}
 
+		/// Create a timestamped copy of the file next to it.
+		///
+		/// The copy is named `file_name` + `-` + `UTC timestamp` + `extension`, for example:
+		/// ```text,ignore
+		/// config.toml                     // <- Real file
+		/// config-2024-06-01T12-00-00.toml // <- Snapshot
+		/// ```
+		/// Multiple snapshots within the same second are suffixed with `-1`, `-2`, etc.
+		///
+		/// If the filesystem supports it (e.g. `btrfs`, `XFS`, `APFS`), the copy is a
+		/// copy-on-write clone (`FICLONE` on Linux, `clonefile()` on macOS), which is
+		/// instant and takes no extra space until either file is modified.
+		/// Otherwise, this falls back to a normal copy.
+		///
+		/// This is useful before risky operations, e.g. [`Self::migrate_from_latest_version()`].
+		///
+		/// On success, this returns:
+		/// - The amount of bytes copied
+		/// - The [`PathBuf`] of the snapshot
+		fn snapshot() -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			let path     = Self::absolute_path()?;
+			let snapshot = crate::history::free_history_path(
+				&Self::base_path()?,
+				Self::FILE,
+				Self::FILE_EXT,
+				std::time::SystemTime::now(),
+			);
+
+			let size = crate::history::reflink_or_copy(&path, &snapshot)?;
+			Ok(crate::Metadata::new(size, snapshot))
+		}
+
+		/// List the historical versions of the file, oldest first.
+		///
+		/// Historical versions are the timestamped copies created by
+		/// [`Self::snapshot()`] and [`Self::save_rotated()`].
+		///
+		/// Each entry is:
+		/// - The [`SystemTime`](std::time::SystemTime) in the file name (second precision)
+		/// - The [`PathBuf`] of the version
+		/// - The file size in bytes
+		///
+		/// This returns an empty [`Vec`] if there is no history.
+		fn list_history() -> Result<Vec<(std::time::SystemTime, PathBuf, u64)>, anyhow::Error> {
+			crate::history::history(&Self::base_path()?, Self::FILE, Self::FILE_EXT)
+		}
+
+		/// Atomically replace the file with a historical version.
+		///
+		/// The newest version at or before `timestamp` is restored, so both an exact
+		/// time from [`Self::list_history()`] and an arbitrary point in time work, e.g:
+		/// ```rust,ignore
+		/// // Restore the file as it was a week ago.
+		/// let week = std::time::Duration::from_secs(60 * 60 * 24 * 7);
+		/// State::restore_from(std::time::SystemTime::now() - week)?;
+		/// ```
+		/// The version is copied to a `.tmp` file first, then renamed over the file, see [`Self::save_atomic()`].
+		/// The historical version itself is left as-is, and the current file is _not_ kept,
+		/// use [`Self::snapshot()`] first to keep it.
+		///
+		/// On success, this returns:
+		/// - The amount of bytes restored
+		/// - The [`PathBuf`] of the file
+		///
+		/// ## Errors
+		/// This errors if there is no historical version at or before `timestamp`.
+		fn restore_from(timestamp: std::time::SystemTime) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			let Some((_, from, _)) = Self::list_history()?
+				.into_iter()
+				.rev()
+				.find(|(time, _, _)| *time <= timestamp)
+			else {
+				bail!("no historical version of {} at or before {}", Self::FILE_NAME, crate::history::timestamp(timestamp));
+			};
+
+			let mut path = Self::base_path()?;
+			let mut tmp  = path.clone();
+			tmp.push(Self::FILE_NAME_TMP);
+			path.push(Self::FILE_NAME);
+			crate::common::ensure_write_once!(path);
+
+			// Copy to TMP.
+			if tmp.try_exists()? {
+				std::fs::remove_file(&tmp)?;
+			}
+			let size = crate::history::reflink_or_copy(&from, &tmp)?;
+
+			// Rename TMP to normal.
+			if let Err(e) = std::fs::rename(&tmp, &path) {
+				std::fs::remove_file(&tmp)?;
+				bail!(e);
+			}
+
+			Ok(crate::Metadata::new(size, path))
+		}
+
+		/// Remove all but the newest `keep` historical versions of the file.
+		///
+		/// Historical versions are the timestamped copies created by
+		/// [`Self::snapshot()`] and [`Self::save_rotated()`].
+		///
+		/// On success, this returns:
+		/// - The amount of bytes removed
+		/// - The base [`PathBuf`] the versions were removed from
+		fn prune_history(keep: usize) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			let base    = Self::base_path()?;
+			let history = crate::history::history(&base, Self::FILE, Self::FILE_EXT)?;
+
+			let mut size = 0;
+			for (_, path, len) in history.iter().rev().skip(keep) {
+				std::fs::remove_file(path)?;
+				size += len;
+			}
+
+			Ok(crate::Metadata::new(size, base))
+		}
+
+		/// Create an empty file if it does not exist.
+		///
+		/// An existing file is left untouched.
+		///
+		/// No read method creates the file as a side effect,
+		/// this can be used to explicitly opt into that behavior.
+		///
+		/// On success, this returns:
+		/// - The file size in bytes (`0` if it was created)
+		/// - The [`PathBuf`] it's located at
+		///
+		/// Calling this will automatically create the directories leading up to the file.
+		fn create_if_missing() -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			let mut path = Self::base_path()?;
+			std::fs::create_dir_all(&path)?;
+			path.push(Self::FILE_NAME);
+
+			let file = std::fs::OpenOptions::new()
+				.append(true)
+				.create(true)
+				.open(&path)?;
+
+			Ok(crate::Metadata::new(file.metadata()?.len(), path))
+		}
+
+		#[inline]
+		/// Check if the file exists.
+		///
+		/// Unlike [`Self::exists()`], this returns `Ok(false)` if the file does not exist
+		/// and only returns an error if existence could not be determined (e.g. permission denied).
+		fn exists_bool() -> Result<bool, anyhow::Error> {
+			Ok(Self::absolute_path()?.try_exists()?)
+		}
+
+		#[inline]
+		/// Returns the file size in bytes and it's [`PathBuf`].
+		///
+		/// Unlike [`Self::exists()`], the underlying I/O error is returned as-is,
+		/// so a missing file can be told apart from other failures with
+		/// [`anyhow::Error::downcast_ref`] to [`std::io::Error`].
+		fn stat() -> Result<crate::Metadata, anyhow::Error> {
+			let path = Self::absolute_path()?;
+			let size = std::fs::metadata(&path)?.len();
+			Ok(crate::Metadata::new(size, path))
+		}
+
+		#[inline]
+		/// Returns the last modification time of the file.
+		///
+		/// This errors if the file does not exist, or if the platform does not support modification times.
+		fn last_modified() -> Result<std::time::SystemTime, anyhow::Error> {
+			Ok(std::fs::metadata(Self::absolute_path()?)?.modified()?)
+		}
+
+		#[inline]
+		/// Returns `true` if the file was modified after `time`.
+		///
+		/// For example, to check if the file changed since it was loaded:
+		/// ```rust,ignore
+		/// let loaded_at = std::time::SystemTime::now();
+		/// let state = State::from_file()?;
+		///
+		/// /* ... */
+		///
+		/// if State::modified_since(loaded_at)? {
+		///     // Someone else changed the file, reload.
+		/// }
+		/// ```
+		///
+		/// This errors if the file does not exist.
+		fn modified_since(time: std::time::SystemTime) -> Result<bool, anyhow::Error> {
+			Ok(Self::last_modified()? > time)
+		}
+
 		#[inline(always)]
 		/// Returns the file size in bytes and it's [`PathBuf`].
+		///
+		/// On Unix, this also returns the size allocated on disk, see [`crate::Metadata::allocated_size()`].
 		fn file_size() -> Result<crate::Metadata, anyhow::Error> {
 			let path = Self::absolute_path()?;
 			let file = std::fs::File::open(&path)?;
-			let size = file.metadata()?.len();
+			let metadata = file.metadata()?;
 
-			Ok(crate::Metadata::new(size, path))
+			let m = crate::Metadata::new(metadata.len(), path);
+			Ok(match common::allocated_size(&metadata) {
+				Some(a) => m.with_allocated_size(a),
+				None => m,
+			})
+		}
+
+		#[inline]
+		/// Open the file read-only and return the handle.
+		///
+		/// The file is not created if it does not exist.
+		///
+		/// This is for custom I/O (seeking, partial reads, etc), the
+		/// PATH is the same as [`Self::absolute_path()`] with the same safety checks.
+		fn open_read() -> Result<std::fs::File, anyhow::Error> {
+			common::file_read(&Self::absolute_path()?)
+		}
+
+		/// Open the file for writing and return the handle.
+		///
+		/// The file is created if it does not exist, but it is _not_ truncated,
+		/// the handle starts at the beginning of any existing data.
+		///
+		/// Calling this will automatically create the directories leading up to the file.
+		fn open_write() -> Result<std::fs::File, anyhow::Error> {
+			crate::common::assert_writable!();
+			let path = Self::absolute_path()?;
+			crate::common::ensure_write_once!(path);
+			std::fs::create_dir_all(Self::base_path()?)?;
+
+			Ok(std::fs::OpenOptions::new()
+				.write(true)
+				.create(true)
+				.truncate(false)
+				.open(path)?)
+		}
+
+		/// Open the file for appending and return the handle.
+		///
+		/// The file is created if it does not exist, all writes go to the end of the file.
+		///
+		/// Calling this will automatically create the directories leading up to the file.
+		fn open_append() -> Result<std::fs::File, anyhow::Error> {
+			crate::common::assert_writable!();
+			let path = Self::absolute_path()?;
+			crate::common::ensure_write_once!(path);
+			std::fs::create_dir_all(Self::base_path()?)?;
+
+			Ok(std::fs::OpenOptions::new()
+				.append(true)
+				.create(true)
+				.open(path)?)
+		}
+
+		/// Returns the full base path associated with this struct (PATH leading up to the file).
+		///
+		/// In contrast to [`Self::sub_dir_parent_path`], this returns all sub-directories,
+		/// e.g: `my/sub/dirs` would return `/.../my/sub/dirs`
+		///
+		/// This includes [`Self::PROJECT_DIRECTORY`], [`Self::SUB_DIRECTORIES`] and excludes [`Self::FILE_NAME`].
+		///
+		/// On Windows, PATHs long enough to hit the `260` character `MAX_PATH` limit
+		/// are returned as extended-length PATHs, e.g. `\\?\C:\Users\...`.
+		fn base_path() -> Result<PathBuf, anyhow::Error> {
+			// Get a `ProjectDir` from our project name.
+			let mut base = Self::project_dir_path()?;
+
+			// Append sub directories (if any).
+			if Self::SUB_DIRECTORIES.len() != 0 {
+				#[cfg(target_os = "windows")]
+				Self::SUB_DIRECTORIES.split_terminator(&['/', '\\'][..]).for_each(|dir| base.push(dir));
+				#[cfg(target_family = "unix")]
+				Self::SUB_DIRECTORIES.split_terminator('/').for_each(|dir| base.push(dir));
+			}
+
+			Ok(crate::path::long_path(base))
+		}
+
+		#[inline(always)]
+		/// Returns the absolute PATH of the file associated with this struct.
+		///
+		/// This includes [`Self::PROJECT_DIRECTORY`], [`Self::SUB_DIRECTORIES`] and [`Self::FILE_NAME`].
+		///
+		/// On Windows, this is an extended-length PATH if needed, see [`Self::base_path()`].
+		fn absolute_path() -> Result<PathBuf, anyhow::Error> {
+			let mut base = Self::base_path()?;
+			base.push(Self::FILE_NAME);
+
+			common::assert_safe_path(&base)?;
+
+			Ok(crate::path::long_path(base))
+		}
+
+		/// Same as [`Self::absolute_path()`], but as a [`String`] with the platform's separators.
+		///
+		/// Non-UTF-8 PATHs are converted lossily, this is meant for printing.
+		fn absolute_path_string() -> Result<String, anyhow::Error> {
+			Ok(Self::absolute_path()?.to_string_lossy().into_owned())
+		}
+
+		/// Open the directory containing the file in the platform's file manager.
+		///
+		/// This is Finder on macOS and Explorer on Windows (both with the file selected, if it exists),
+		/// and `xdg-open` elsewhere, for "Show config folder" style buttons.
+		///
+		/// The directory is created if it does not exist.
+		///
+		/// This returns once the file manager is started, it is not waited on.
+		fn reveal() -> Result<(), anyhow::Error> {
+			let base = Self::base_path()?;
+			std::fs::create_dir_all(&base)?;
+			crate::editor::reveal(&base, &base.join(Self::FILE_NAME))
+		}
+
+		/// Returns all PATHs associated with this file as strings, see [`crate::FilePaths`].
+		///
+		/// This is meant for printing, e.g. for a `myapp config path` command:
+		/// ```rust,ignore
+		/// println!("{}", Config::print_paths()?);
+		/// ```
+		fn print_paths() -> Result<crate::FilePaths, anyhow::Error> {
+			let string = |path: &std::path::Path| path.to_string_lossy().into_owned();
+			let base = Self::base_path()?;
+			let file = Self::absolute_path()?;
+
+			let history = match Self::list_history() {
+				Ok(h)  => h.iter().map(|(_, path, _)| string(path)).collect(),
+				Err(_) => vec![],
+			};
+
+			Ok(crate::FilePaths {
+				project_directory: string(&Self::project_dir_path()?),
+				directory: string(&base),
+				file: string(&file),
+				gzip: string(&base.join(Self::FILE_NAME_GZIP)),
+				tmp: string(&base.join(Self::FILE_NAME_TMP)),
+				gzip_tmp: string(&base.join(Self::FILE_NAME_GZIP_TMP)),
+				backup: string(&base.join(format!("{}.bak", Self::FILE_NAME))),
+				journal: string(&crate::journal::journal_path(&file)),
+				audit: string(&crate::audit::audit_path(&file)),
+				history,
+			})
+		}
+
+		/// Set the extended attribute `key` of the file to `value`, e.g. to tag it with the app version that wrote it.
+		///
+		/// This does not touch the file's contents, and errors if it does not exist.
+		///
+		/// The attribute is stored:
+		/// - As `user.disk.$KEY` on Linux and `disk.$KEY` on macOS (extended attributes)
+		/// - As the alternate data stream `$FILE:disk.$KEY` on Windows (NTFS)
+		/// - In a sidecar file, e.g. `config.toml.xattr`, if the filesystem supports neither
+		///
+		/// `key` cannot be empty, longer than `200` bytes, or contain control characters, `:`, `/`, or `\`.
+		///
+		/// ## Saving
+		/// Attributes belong to the file itself, so saves that replace it,
+		/// e.g. [`Self::save_atomic()`], drop them (except the sidecar).
+		///
+		/// Set attributes after saving:
+		/// ```rust,ignore
+		/// Config::default().save_atomic()?;
+		/// Config::set_xattr("app_version", b"1.4.2")?;
+		///
+		/// assert_eq!(Config::get_xattr("app_version")?.unwrap(), b"1.4.2");
+		/// ```
+		fn set_xattr(key: &str, value: &[u8]) -> Result<(), anyhow::Error> {
+			crate::common::assert_writable!();
+			crate::xattr::set(&Self::absolute_path()?, key, value)
+		}
+
+		/// Get the extended attribute `key` of the file, see [`Self::set_xattr()`].
+		///
+		/// This returns `None` if the attribute is not set, and errors if the file does not exist.
+		fn get_xattr(key: &str) -> Result<Option<Vec<u8>>, anyhow::Error> {
+			crate::xattr::get(&Self::absolute_path()?, key)
 		}
 
-		/// Returns the full base path associated with this struct (PATH leading up to the file).
+		/// Find the file in the user directory, or in the system-wide directories.
 		///
-		/// In contrast to [`Self::sub_dir_parent_path`], this returns all sub-directories,
-		/// e.g: `my/sub/dirs` would return `/.../my/sub/dirs`
+		/// This first checks [`Self::absolute_path()`], then the same file within the
+		/// system directories (in order), returning the first PATH that exists:
 		///
-		/// This includes [`Self::PROJECT_DIRECTORY`], [`Self::SUB_DIRECTORIES`] and excludes [`Self::FILE_NAME`].
-		fn base_path() -> Result<PathBuf, anyhow::Error> {
-			// Get a `ProjectDir` from our project name.
-			let mut base = Self::project_dir_path()?;
-
-			// Append sub directories (if any).
-			if Self::SUB_DIRECTORIES.len() != 0 {
-				#[cfg(target_os = "windows")]
-				Self::SUB_DIRECTORIES.split_terminator(&['/', '\\'][..]).for_each(|dir| base.push(dir));
-				#[cfg(target_family = "unix")]
-				Self::SUB_DIRECTORIES.split_terminator('/').for_each(|dir| base.push(dir));
+		/// | Platform | [`crate::Dir::Config`], [`crate::Dir::Preference`] | [`crate::Dir::Data`], [`crate::Dir::DataLocal`] |
+		/// |----------|-----------------------------------------------------|-------------------------------------------------|
+		/// | Linux    | `$XDG_CONFIG_DIRS` or `/etc/xdg`                    | `$XDG_DATA_DIRS` or `/usr/local/share:/usr/share`
+		/// | macOS    | `/Library/Preferences` for `Preference`, else `/Library/Application Support` | `/Library/Application Support`
+		/// | Windows  | `{FOLDERID_ProgramData}`\\`_project_path_`\\config | `{FOLDERID_ProgramData}`\\`_project_path_`\\data
+		///
+		/// [`crate::Dir::Project`] and [`crate::Dir::Cache`] only check the user directory.
+		///
+		/// For example, with `toml!(State, Dir::Data, "MyProject", "some/dirs", "state")` on Linux:
+		/// ```text,ignore
+		/// ~/.local/share/myproject/some/dirs/state.toml  // <- The user's file
+		/// /usr/local/share/myproject/some/dirs/state.toml
+		/// /usr/share/myproject/some/dirs/state.toml      // <- Read-only defaults shipped by a package
+		/// ```
+		/// The profile and version directory (if any) are only used for the user directory.
+		///
+		/// This only finds the file, read it with `from_path()`, and save with `save()` as usual (to the user directory).
+		fn find_existing() -> Option<PathBuf> {
+			let user = Self::absolute_path().ok()?;
+			if user.is_file() {
+				return Some(user);
 			}
 
-			Ok(base)
+			let relative = Self::base_path().ok()?
+				.strip_prefix(Self::project_dir_path().ok()?).ok()?
+				.join(Self::FILE_NAME);
+
+			common::get_system_projectdirs(&Self::OS_DIRECTORY, Self::PROJECT_DIRECTORY)
+				.ok()?
+				.into_iter()
+				.map(|dir| dir.join(&relative))
+				.find(|path| path.is_file())
 		}
 
-		#[inline(always)]
-		/// Returns the absolute PATH of the file associated with this struct.
+		/// Same as [`Self::absolute_path()`], but with all symlinks resolved.
 		///
-		/// This includes [`Self::PROJECT_DIRECTORY`], [`Self::SUB_DIRECTORIES`] and [`Self::FILE_NAME`].
-		fn absolute_path() -> Result<PathBuf, anyhow::Error> {
-			let mut base = Self::base_path()?;
-			base.push(Self::FILE_NAME);
-
-			common::assert_safe_path(&base)?;
+		/// This is the real location of the file, e.g. the target of a dotfile manager's symlink.
+		///
+		/// This errors if the file does not exist.
+		fn absolute_path_resolved() -> Result<PathBuf, anyhow::Error> {
+			Ok(std::fs::canonicalize(Self::absolute_path()?)?)
+		}
 
-			Ok(base)
+		/// Returns all symlinks leading up to (and including) the file, starting from [`Self::project_dir_path()`].
+		///
+		/// Atomic saves rename over the file, so if the file itself is a symlink, it is replaced
+		/// with a regular file unless a [`crate::SymlinkPolicy`] is set. If the project directory
+		/// (or a sub-directory) is a symlink, saves go to its target.
+		///
+		/// An empty [`Vec`] means there are no symlinks (or nothing exists yet).
+		fn symlinks() -> Result<Vec<PathBuf>, anyhow::Error> {
+			Ok(crate::symlink::symlinks(&Self::project_dir_path()?, &Self::absolute_path()?))
 		}
 
 		/// Try deleting the file.
 		///
 		/// This will return success if the file doesn't exist or if deleted.
 		///
+		/// If the file is a symlink, see [`crate::SymlinkPolicy`] for what is removed.
+		///
 		/// It will return failure if the file existed but could not be deleted or if any other error occurs.
 		///
 		/// On success, this returns:
 		/// - The amount of bytes removed
 		/// - The [`PathBuf`] that was removed
 		fn rm() -> Result<crate::Metadata, anyhow::Error> {
-			let mut path = Self::base_path()?;
-			path.push(Self::FILE_NAME);
-
-			if !path.exists() { return Ok(crate::Metadata::zero(path)) }
-
-			let size = crate::common::filesize(&path);
-			std::fs::remove_file(&path)?;
-			Ok(crate::Metadata::new(size, path))
+			crate::common::assert_writable!();
+			crate::common::traced!("rm", $file_ext, remove, {
+				let mut path = Self::base_path()?;
+				path.push(Self::FILE_NAME);
+
+				if !path.exists() { return Ok(crate::Metadata::zero(path)) }
+
+				// A followed symlink is kept, only its target is removed.
+				let target = crate::symlink::rm_path(&path)?;
+				let size = crate::common::filesize(&target);
+				std::fs::remove_file(&target)?;
+				Ok(crate::Metadata::new(size, target))
+			})
 		}
 
 		#[inline]
@@ -863,10 +3237,13 @@ macro_rules! impl_common {
 		/// - The amount of bytes removed
 		/// - The [`PathBuf`] that was removed
 		fn rm_base() -> Result<crate::Metadata, anyhow::Error> {
-			let path = Self::base_path()?;
-			let size = crate::common::filesize(&path);
-			std::fs::remove_dir_all(&path)?;
-			Ok(crate::Metadata::new(size, path))
+			crate::common::assert_writable!();
+			crate::common::traced!("rm_base", $file_ext, remove, {
+				let path = Self::base_path()?;
+				let size = crate::common::filesize(&path);
+				std::fs::remove_dir_all(&path)?;
+				Ok(crate::Metadata::new(size, path))
+			})
 		}
 
 		#[inline]
@@ -890,10 +3267,13 @@ macro_rules! impl_common {
 		/// - The amount of bytes removed
 		/// - The [`PathBuf`] that was removed
 		fn rm_sub() -> Result<crate::Metadata, anyhow::Error> {
-			let path = Self::sub_dir_parent_path()?;
-			let size = crate::common::filesize(&path);
-			std::fs::remove_dir_all(&path)?;
-			Ok(crate::Metadata::new(size, path))
+			crate::common::assert_writable!();
+			crate::common::traced!("rm_sub", $file_ext, remove, {
+				let path = Self::sub_dir_parent_path()?;
+				let size = crate::common::filesize(&path);
+				std::fs::remove_dir_all(&path)?;
+				Ok(crate::Metadata::new(size, path))
+			})
 		}
 
 		#[inline]
@@ -912,7 +3292,8 @@ macro_rules! impl_common {
 		/// rm -rf ~/.local/share/myproject
 		/// ```
 		/// The input to all `disk` macros are sanity checked.
-		/// The worst you can do with this function is delete your project's directory.
+		/// The worst you can do with this function is delete your project's directory,
+		/// see [`Self::rm_project_confirm()`] for a guarded version.
 		///
 		/// This function calls [`std::fs::remove_dir_all`], which does _not_ follow symlinks.
 		///
@@ -920,10 +3301,90 @@ macro_rules! impl_common {
 		/// - The amount of bytes removed
 		/// - The [`PathBuf`] that was removed
 		fn rm_project() -> Result<crate::Metadata, anyhow::Error> {
-			let path = Self::project_dir_path()?;
-			let size = crate::common::filesize(&path);
-			std::fs::remove_dir_all(&path)?;
-			Ok(crate::Metadata::new(size, path))
+			crate::common::assert_writable!();
+			crate::common::traced!("rm_project", $file_ext, remove, {
+				let path = Self::project_dir_path()?;
+				let size = crate::common::filesize(&path);
+				std::fs::remove_dir_all(&path)?;
+				Ok(crate::Metadata::new(size, path))
+			})
+		}
+
+		/// Same as [`Self::rm_project()`], but only if the project directory is `project`.
+		///
+		/// The PATH about to be removed is re-checked to be the whole project directory
+		/// `project` maps to (including any profile or version directory), if it isn't
+		/// (e.g. the project name was overridden at runtime) nothing is removed and an error is returned.
+		///
+		/// This is the recommended way to remove a project directory:
+		/// ```rust,ignore
+		/// // Errors, nothing is removed.
+		/// State::rm_project_confirm(disk::ProjectName("SomeOtherProject"))?;
+		///
+		/// // Removes the project directory.
+		/// State::rm_project_confirm(disk::ProjectName("MyProject"))?;
+		/// ```
+		///
+		/// See [`crate::ProjectName`] for a full example.
+		fn rm_project_confirm(project: crate::ProjectName<'_>) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			crate::common::traced!("rm_project_confirm", $file_ext, remove, {
+				let path = Self::project_dir_path()?;
+				crate::common::confirm_projectdir(&Self::OS_DIRECTORY, &path, project.0)?;
+				let size = crate::common::filesize(&path);
+				std::fs::remove_dir_all(&path)?;
+				Ok(crate::Metadata::new(size, path))
+			})
+		}
+
+		/// Remove leftover `.tmp` files older than `older_than` from the whole project directory.
+		///
+		/// Unlike `rm_tmp()` which only knows about this type's own `.tmp` files,
+		/// this walks every sub-directory of [`Self::project_dir_path()`], so it also catches
+		/// the `.tmp` files of other types, [`crate::Kv`] stores, etc.
+		///
+		/// `.tmp` files newer than `older_than` are left alone since they may belong to a save in progress.
+		///
+		/// A missing project directory is not an error.
+		///
+		/// On success, this returns:
+		/// - The total amount of bytes removed
+		/// - The project [`PathBuf`]
+		fn gc_tmp_project(older_than: std::time::Duration) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			crate::common::traced!("gc_tmp_project", $file_ext, remove, {
+				let path = Self::project_dir_path()?;
+				if !path.exists() { return Ok(crate::Metadata::zero(path)) }
+
+				let mut files = vec![];
+				crate::common::walk(&path, &mut files)?;
+
+				let now = std::time::SystemTime::now();
+				let mut size = 0;
+				for file in files {
+					if !file.file_name().unwrap_or_default().to_string_lossy().ends_with(".tmp") {
+						continue;
+					}
+
+					// Skip files that are too new, or already gone (a save finished).
+					let metadata = match std::fs::metadata(&file) {
+						Ok(m) => m,
+						Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+						Err(e) => return Err(e.into()),
+					};
+					if now.duration_since(metadata.modified()?).unwrap_or_default() < older_than {
+						continue;
+					}
+
+					match std::fs::remove_file(&file) {
+						Ok(()) => size += metadata.len(),
+						Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+						Err(e) => return Err(e.into()),
+					}
+				}
+
+				Ok(crate::Metadata::new(size, path))
+			})
 		}
 
 		#[inline(always)]
@@ -954,6 +3415,119 @@ macro_rules! impl_common {
 			Ok(crate::Metadata::new(size, path))
 		}
 
+		/// Check there are at least `required_bytes` free on the filesystem of this file's directory.
+		///
+		/// On success, this returns the amount of bytes available.
+		///
+		/// This errors with [`crate::InsufficientSpace`] if not,
+		/// or if the directory does not exist.
+		fn check_free_space(required_bytes: u64) -> Result<u64, anyhow::Error> {
+			crate::space::check(&Self::base_path()?, required_bytes)
+		}
+
+		/// Returns the total size in bytes of all files in the
+		/// project directory ([`Self::PROJECT_DIRECTORY`]), recursively, and it's [`PathBuf`].
+		///
+		/// Symlinks are not followed, this is the size checked against [`crate::set_quota()`].
+		///
+		/// This errors if the PATH does not exist.
+		fn project_dir_size_recursive() -> Result<crate::Metadata, anyhow::Error> {
+			let path = Self::project_dir_path()?;
+			let mut files = vec![];
+			crate::common::walk(&path, &mut files)?;
+			let size = files.iter().map(|f| crate::common::filesize(f)).sum();
+
+			Ok(crate::Metadata::new(size, path))
+		}
+
+		/// Returns all `vMAJOR.MINOR` version directories of this project, oldest first.
+		///
+		/// See [`crate::set_version_dir()`].
+		fn version_dirs() -> Result<Vec<PathBuf>, anyhow::Error> {
+			let root = common::get_projectdir_unversioned(&Self::OS_DIRECTORY, Self::PROJECT_DIRECTORY)?;
+			crate::version_dir::version_dirs(&root)
+		}
+
+		/// Copy this file from the version directory `version` (e.g. `v1.3`) into the current one.
+		///
+		/// The old file is left as-is, an existing file in the current version directory is overwritten.
+		///
+		/// See [`crate::set_version_dir()`].
+		///
+		/// ## Errors
+		/// This errors if `version` is not exactly `vMAJOR.MINOR`, like the directories [`Self::version_dirs()`] returns.
+		fn migrate_from_version(version: &str) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			crate::version_dir::validate_name(version)?;
+			let mut from = common::get_projectdir_unversioned(&Self::OS_DIRECTORY, Self::PROJECT_DIRECTORY)?;
+			from.push(version);
+			let to   = Self::absolute_path()?;
+			crate::common::ensure_write_once!(to);
+			let size = crate::version_dir::migrate(&from, &Self::__version_relative_path(), &to)?;
+			Ok(crate::Metadata::new(size, to))
+		}
+
+		/// Copy this file from the newest older version directory that contains it.
+		///
+		/// This does nothing and returns `None` if the file already exists
+		/// in the current version directory, or no older version has it.
+		///
+		/// See [`crate::set_version_dir()`].
+		fn migrate_from_latest_version() -> Result<Option<crate::Metadata>, anyhow::Error> {
+			crate::common::assert_writable!();
+			let to = Self::absolute_path()?;
+			if to.exists() {
+				return Ok(None);
+			}
+
+			let root     = common::get_projectdir_unversioned(&Self::OS_DIRECTORY, Self::PROJECT_DIRECTORY)?;
+			let relative = Self::__version_relative_path();
+			match crate::version_dir::latest_containing(&root, &relative)? {
+				Some(from) => {
+					let size = crate::version_dir::migrate(&from, &relative, &to)?;
+					Ok(Some(crate::Metadata::new(size, to)))
+				},
+				None => Ok(None),
+			}
+		}
+
+		#[doc(hidden)]
+		/// Internal function. The file's PATH relative to a version directory.
+		fn __version_relative_path() -> PathBuf {
+			let mut path = PathBuf::new();
+			if Self::SUB_DIRECTORIES.len() != 0 {
+				#[cfg(target_os = "windows")]
+				Self::SUB_DIRECTORIES.split_terminator(&['/', '\\'][..]).for_each(|dir| path.push(dir));
+				#[cfg(target_family = "unix")]
+				Self::SUB_DIRECTORIES.split_terminator('/').for_each(|dir| path.push(dir));
+			}
+			path.push(Self::FILE_NAME);
+			path
+		}
+
+		/// Override [`Self::PROJECT_DIRECTORY`] at runtime.
+		///
+		/// After this, all `PATH` functions of _every_ type with the same
+		/// [`Self::PROJECT_DIRECTORY`] will use `name` instead, e.g. for a
+		/// white-label build where the brand name is only known at startup.
+		///
+		/// `name` is checked with the same rules as the macros.
+		fn set_project_name(name: &str) -> Result<(), anyhow::Error> {
+			common::set_project_name(Self::PROJECT_DIRECTORY, name)
+		}
+
+		/// Remove the override set with [`Self::set_project_name`], if any.
+		fn reset_project_name() {
+			common::reset_project_name(Self::PROJECT_DIRECTORY)
+		}
+
+		/// Returns the project directory name in use.
+		///
+		/// This is [`Self::PROJECT_DIRECTORY`] unless it was overridden with [`Self::set_project_name`].
+		fn project_name() -> String {
+			common::project_name(Self::PROJECT_DIRECTORY)
+		}
+
 		/// Return the full parent project directory associated with this struct.
 		///
 		/// This is the `PATH` leading up to [`Self::PROJECT_DIRECTORY`].
@@ -994,17 +3568,64 @@ pub(crate) use impl_common;
 // Implements common methods on a [String] based trait.
 // This automatically implements [impl_common!()].
 macro_rules! impl_string {
-	($file_ext:literal) => {
+	($file_ext:literal $(, $comment:literal)?) => {
+		/// The line endings used when saving, [`crate::Newline::Lf`] by default, see [`crate::LineEnding`].
+		const NEWLINE: crate::Newline = crate::Newline::Lf;
+
+		/// Who is writing the file, appended as a comment when saving, [`None`] by default, see [`crate::WriteStamp`].
+		const WRITE_STAMP: Option<&'static str> = None;
+
 		#[inline(always)]
 		/// Turn [`Self`] into bytes, maintaining formatting if possible.
+		///
+		/// Line endings are [`Self::NEWLINE`], a stamp is appended if [`Self::WRITE_STAMP`] is set.
 		fn to_writeable_fmt(&self) -> Result<Vec<u8>, anyhow::Error> {
-			Ok(self.to_string()?.into_bytes())
+			Ok(Self::NEWLINE.apply(Self::__stamp(self.to_string()?)).into_bytes())
 		}
 
 		#[inline(always)]
 		/// Consume [`Self`] into bytes, maintaining formatting if possible.
+		///
+		/// Line endings are [`Self::NEWLINE`], a stamp is appended if [`Self::WRITE_STAMP`] is set.
 		fn into_writeable_fmt(self) -> Result<Vec<u8>, anyhow::Error> {
-			Ok(self.to_string()?.into_bytes())
+			Ok(Self::NEWLINE.apply(Self::__stamp(self.to_string()?)).into_bytes())
+		}
+
+		#[doc(hidden)]
+		#[inline(always)]
+		/// Internal function. Turn any `view` into bytes that can be written to disk as [`Self`].
+		fn __view_to_writeable_fmt<V: serde::Serialize>(view: &V) -> Result<Vec<u8>, anyhow::Error> {
+			Ok(Self::NEWLINE.apply(Self::__stamp(Self::__to_string(view)?)).into_bytes())
+		}
+
+		#[doc(hidden)]
+		#[inline(always)]
+		/// Internal function. Append the write stamp, if this format has comments.
+		fn __stamp(string: String) -> String {
+			match <[&str]>::first(&[$($comment)?]) {
+				Some(comment) => crate::stamp::apply(comment, Self::WRITE_STAMP, string),
+				None => string,
+			}
+		}
+
+		#[doc(hidden)]
+		#[inline(always)]
+		/// Internal function. Remove a trailing write stamp, if this format has comments.
+		fn __strip_stamp(string: &str) -> &str {
+			match <[&str]>::first(&[$($comment)?]) {
+				Some(comment) => crate::stamp::strip(comment, string),
+				None => string,
+			}
+		}
+
+		crate::common::impl_view!(serde::Serialize);
+
+		#[inline]
+		/// Same as [`Self::to_string()`], but with `newline` line endings.
+		///
+		/// Saves use [`Self::NEWLINE`], see [`crate::LineEnding`].
+		fn to_string_newline(&self, newline: crate::Newline) -> Result<String, anyhow::Error> {
+			Ok(newline.apply(self.to_string()?))
 		}
 
 		#[inline(always)]
@@ -1013,29 +3634,212 @@ macro_rules! impl_string {
 			Ok(std::fs::read_to_string(Self::absolute_path()?)?)
 		}
 
+		/// Compare [`Self`] against the associated file.
+		///
+		/// This returns a unified diff from the file on disk (old) to [`Self::to_writeable_fmt()`] (new), for example:
+		/// ```text,ignore
+		/// --- /home/alice/.local/share/myproject/state.toml
+		/// +++ /home/alice/.local/share/myproject/state.toml
+		/// @@ -1,2 +1,2 @@
+		///  string = "Hello"
+		/// -number = 123
+		/// +number = 456
+		/// ```
+		/// This is what would change if [`Self::save()`] were called.
+		///
+		/// [`None`] is returned if there are no differences.
+		///
+		/// If the file does not exist, it is treated as empty.
+		fn diff_file(&self) -> Result<Option<String>, anyhow::Error> {
+			let path = Self::absolute_path()?;
+			let new  = self.to_string_newline(Self::NEWLINE)?;
+			let old  = match std::fs::read_to_string(&path) {
+				Ok(s) => s,
+				Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+				Err(e) => return Err(e.into()),
+			};
+
+			let old = Self::__strip_stamp(&old);
+			if old == new {
+				return Ok(None);
+			}
+
+			let path = path.display().to_string();
+			Ok(Some(similar::TextDiff::from_lines(old, new.as_str()).unified_diff().header(&path, &path).to_string()))
+		}
+
+		/// Open the file in the user's editor and wait for it to exit.
+		///
+		/// The editor is `$VISUAL`, then `$EDITOR`, or the platform's default handler
+		/// (`xdg-open`, `open` on macOS, `start` on Windows) if neither is set.
+		///
+		/// If the file does not exist, [`Self::default()`] is saved first.
+		///
+		/// This returns the editor's exit status, the file is not read, see [`Self::edit()`].
+		///
+		/// `xdg-open` may return before the file is closed, depending on the handler.
+		fn open_in_editor() -> Result<std::process::ExitStatus, anyhow::Error>
+		where
+			Self: Default,
+		{
+			crate::common::assert_writable!();
+			let path = Self::absolute_path()?;
+			if !path.try_exists()? {
+				Self::default().save()?;
+			}
+			crate::editor::open(&path)
+		}
+
+		/// Same as [`Self::open_in_editor()`], then load (and validate) the edited file with [`Self::from_file()`].
+		///
+		/// This is `git config --edit` style editing, e.g:
+		/// ```rust,ignore
+		/// // `myapp config edit`
+		/// let config = match Config::edit() {
+		///     Ok(config) => config,
+		///     Err(e) => {
+		///         eprintln!("invalid config: {e}");
+		///         std::process::exit(1);
+		///     },
+		/// };
+		/// ```
+		///
+		/// This errors without reading the file if the editor exits unsuccessfully.
+		fn edit() -> Result<Self, anyhow::Error>
+		where
+			Self: Default,
+		{
+			let status = Self::open_in_editor()?;
+			if !status.success() {
+				bail!("disk: the editor exited with {status}, {} was not reloaded", Self::FILE_NAME);
+			}
+			Self::from_file()
+		}
+
 		common::impl_io!($file_ext);
 		common::impl_common!($file_ext);
 	};
 }
 pub(crate) use impl_string;
 
+//---------------------------------------------------------------------------------------------------- impl_view
+// Implements `save_view()` methods, `$view` is the trait a view must implement.
+macro_rules! impl_view {
+	($view:path) => {
+		/// Save `view` as the associated file, instead of [`Self`].
+		///
+		/// `view` is serialized exactly like [`Self`] would be (same format, style, header, etc),
+		/// this is for saving a trimmed or redacted projection of [`Self`], without
+		/// a second type implementing this trait, e.g:
+		/// ```rust,ignore
+		/// #[derive(Serialize)]
+		/// struct StateView<'a> {
+		///     // Everything except `session_token`.
+		///     user: &'a str,
+		///     volume: u8,
+		/// }
+		///
+		/// State::save_view(&StateView { user: &state.user, volume: state.volume })?;
+		/// ```
+		///
+		/// `view` is not validated (see [`crate::Validate`]), and it should
+		/// be readable as [`Self`] (e.g. with `#[serde(default)]` on the missing fields).
+		///
+		/// This will return the amount of `bytes` saved and the [`PathBuf`] on success.
+		fn save_view<V: $view>(view: &V) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			let now   = std::time::Instant::now();
+			let bytes = Self::__view_to_writeable_fmt(view)?;
+			let serialize_time = now.elapsed();
+			Ok(Self::save_bytes(&bytes)?.with_serialize_time(serialize_time))
+		}
+
+		/// Same as [`Self::save_view`], but atomically, see [`Self::save_atomic`].
+		fn save_view_atomic<V: $view>(view: &V) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			let now   = std::time::Instant::now();
+			let bytes = Self::__view_to_writeable_fmt(view)?;
+			let serialize_time = now.elapsed();
+			Ok(Self::save_bytes_atomic(&bytes)?.with_serialize_time(serialize_time))
+		}
+	};
+}
+pub(crate) use impl_view;
+
 //---------------------------------------------------------------------------------------------------- impl_binary
 // Implements common methods on a binary based trait.
 // This automatically implements `impl_common!()`.
 macro_rules! impl_binary {
 	($file_ext:literal) => {
+		crate::common::impl_binary!($file_ext, serde::Serialize);
+	};
+	($file_ext:literal, $view:path) => {
 		#[inline(always)]
 		/// Turn [`Self`] into bytes that can be written to disk.
 		fn to_writeable_fmt(&self) -> Result<Vec<u8>, anyhow::Error> {
 			self.to_bytes()
 		}
 
+		#[doc(hidden)]
+		#[inline(always)]
+		/// Internal function. Turn any `view` into bytes that can be written to disk as [`Self`].
+		fn __view_to_writeable_fmt<V: $view>(view: &V) -> Result<Vec<u8>, anyhow::Error> {
+			Self::__to_bytes(view)
+		}
+
+		crate::common::impl_view!($view);
+
 		#[inline(always)]
 		/// Consume [`Self`] into bytes that can be written to disk.
 		fn into_writeable_fmt(self) -> Result<Vec<u8>, anyhow::Error> {
 			self.to_bytes()
 		}
 
+		/// Same as [`Self::save()`], but blocks of zeros are not written, making a sparse file.
+		///
+		/// Every `4096` byte block that only contains zeros is seeked over instead of written,
+		/// on filesystems that support sparse files (most, except e.g. `FAT` and `HFS+`),
+		/// those blocks take up no space on disk, and read back as zeros.
+		///
+		/// This is for large, mostly empty data, e.g. preallocated indices.
+		///
+		/// On success, this returns the amount of `bytes` saved and the [`PathBuf`], along with the
+		/// amount of bytes actually allocated on Unix, see [`crate::Metadata::allocated_size()`].
+		///
+		/// Calling this will automatically create the directories leading up to the file.
+		fn save_sparse(&self) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			self.__validate()?;
+			crate::common::traced!("save_sparse", $file_ext, save, {
+				let now   = std::time::Instant::now();
+				let bytes = self.to_bytes()?;
+				let serialize_time = now.elapsed();
+
+				// Create PATH.
+				let mut path = Self::base_path()?;
+				std::fs::create_dir_all(&path)?;
+				path.push(Self::FILE_NAME);
+				let path = crate::symlink::save_path(path)?;
+				crate::common::ensure_write_once!(path);
+				crate::common::ensure_quota!(path, bytes.len());
+				crate::common::ensure_space!(path, bytes.len());
+
+				// Write.
+				let mut file = std::fs::OpenOptions::new()
+					.write(true)
+					.create(true)
+					.truncate(true)
+					.open(&path)?;
+				common::write_sparse(&mut file, &bytes)?;
+
+				let m = crate::Metadata::new(bytes.len() as u64, path).with_serialize_time(serialize_time);
+				Ok(match common::allocated_size(&file.metadata()?) {
+					Some(a) => m.with_allocated_size(a),
+					None => m,
+				})
+			})
+		}
+
 		crate::common::impl_io!($file_ext);
 		crate::common::impl_common!($file_ext);
 	};
@@ -1043,184 +3847,60 @@ macro_rules! impl_binary {
 pub(crate) use impl_binary;
 
 //---------------------------------------------------------------------------------------------------- Compile-time assertions, sanity checks.
-// Assert string does not contain invalid path symbol.
-#[doc(hidden)]
-#[macro_export]
-macro_rules! assert_str_invalid_symbol {
-	($symbol:literal, $project:tt, $sub:tt, $file:tt) => {
-		$crate::const_assert!(!$crate::contains!($project, $symbol), "disk: 'Project Directory' must not contain '{}'", $symbol);
-		$crate::const_assert!(!$crate::contains!($sub, $symbol), "disk: 'Sub Directories' must not contain '{}'", $symbol);
-		$crate::const_assert!(!$crate::contains!($file, $symbol), "disk: 'File Name' must not contain '{}'", $symbol);
-	}
-}
-
-// INVARIANT: Input should be UPPERCASE.
-// Assert string is not a reserved file name.
+// Assert string inputs are valid.
+//
+// Warn (as a deprecation) if the PATH is close to `MAX_PATH` on Windows.
 #[doc(hidden)]
 #[macro_export]
-macro_rules! assert_str_reserved {
-	($symbol:literal, $project:tt, $sub:tt, $file:tt) => {
-		$crate::const_assert!(!$crate::convert_case!(upper, $project), $symbol, "disk: 'Project Directory' must not be a reserved filename: '{}'", $symbol);
-		$crate::const_assert!(!$crate::convert_case!(upper, $sub),     $symbol, "disk: 'Sub Directories' must not be a reserved filename: '{}'", $symbol);
-		$crate::const_assert!(!$crate::convert_case!(upper, $file),    $symbol, "disk: 'File Name' must not be a reserved filename: '{}'", $symbol);
-		$crate::seq!(N in 0..10 {
-			const _: () = {
-				if !$crate::contains!($sub, '\\') && $sub.len() > 255 {
-					::std::panic!("disk: the single 'Sub Directory' is a reserved filename");
-				} else if N < $crate::split!($sub, '\\').len() {
-					if $crate::split!($sub, '\\')[N].len() > 255 {
-						::std::panic!("disk: one of the 'Sub Directories' is a reserved filename");
-					}
-				}
-			};
-			const _: () = {
-				if !$crate::contains!($sub, '/') && $sub.len() > 255 {
-					::std::panic!("disk: the single 'Sub Directory' is a reserved filename");
-				} else if N < $crate::split!($sub, '/').len() {
-					if $crate::split!($sub, '/')[N].len() > 255 {
-						::std::panic!("disk: one of the 'Sub Directories' is a reserved filename");
-					}
-				}
-			};
-		});
-	}
+macro_rules! assert_str {
+	($project:expr, $sub:expr, $file:expr) => {
+		$crate::assert_str!($project, $sub, $file, "");
+	};
+	($project:expr, $sub:expr, $file:expr, $ext:expr) => {
+		const _: () = if let ::std::result::Result::Err(e) = $crate::validate_path_components($project, $sub, $file) {
+			::std::panic!("{}", e);
+		};
+		const _: () = $crate::__LongPath::<{ $crate::__near_max_path($project, $sub, $file, $ext) }>::check();
+	};
 }
 
-// Assert string does not contain invalid path symbol.
+// Assert a `Dir::Path` is valid.
 #[doc(hidden)]
 #[macro_export]
-macro_rules! assert_str_invalid_symbol_start_end {
-	($symbol:literal, $project:tt, $sub:tt, $file:tt) => {
-		$crate::const_assert!(!$crate::starts_with!($project, $symbol), "disk: 'Project Directory' must not start with '{}'", $symbol);
-		$crate::const_assert!(!$crate::starts_with!($sub,     $symbol), "disk: 'Sub Directories' must not start with '{}'", $symbol);
-		$crate::const_assert!(!$crate::starts_with!($file,    $symbol), "disk: 'File Name' must not start with '{}'", $symbol);
-		$crate::const_assert!(!$crate::ends_with!($project,   $symbol), "disk: 'Project Directory' must not end with '{}'", $symbol);
-		$crate::const_assert!(!$crate::ends_with!($sub,       $symbol), "disk: 'Sub Directories' must not end with '{}'", $symbol);
-		$crate::const_assert!(!$crate::ends_with!($file,      $symbol), "disk: 'File Name' must not end with '{}'", $symbol);
-		#[cfg(target_os = "windows")]
-		$crate::seq!(N in 0..10 {
-			const _: () = {
-				if N < $crate::split!($sub, '\\').len() {
-					if $crate::starts_with!($crate::split!($sub, '\\')[N], $symbol) {
-						panic!("disk: one of the 'Sub Directories' starts with an invalid symbol");
-					}
-				}
-			};
-		});
-		$crate::seq!(N in 0..10 {
-			const _: () = {
-				if N < $crate::split!($sub, '/').len() {
-					if $crate::starts_with!($crate::split!($sub, '/')[N], $symbol) {
-						panic!("disk: one of the 'Sub Directories' starts with an invalid symbol");
-					}
-				}
-			};
-		});
-		#[cfg(target_os = "windows")]
-		$crate::seq!(N in 0..10 {
-			const _: () = {
-				if N < $crate::split!($sub, '\\').len() {
-					if $crate::ends_with!($crate::split!($sub, '\\')[N], $symbol) {
-						panic!("disk: one of the 'Sub Directories' ends with an invalid symbol");
-					}
-				}
-			};
-		});
-		$crate::seq!(N in 0..10 {
-			const _: () = {
-				if N < $crate::split!($sub, '/').len() {
-					if $crate::ends_with!($crate::split!($sub, '/')[N], $symbol) {
-						panic!("disk: one of the 'Sub Directories' ends with an invalid symbol");
-					}
-				}
-			};
-		});
+macro_rules! assert_dir {
+	($dir:expr) => {
+		const _: () = if let $crate::Dir::Path(path) = $dir {
+			if let ::std::result::Result::Err(e) = $crate::validate_base_path(path) {
+				::std::panic!("{}", e);
+			}
+		};
 	}
 }
 
-// Assert string inputs are valid.
+// Assert a user-provided file extension is valid.
 #[doc(hidden)]
 #[macro_export]
-macro_rules! assert_str {
-	($project:tt, $sub:tt, $file:tt) => {
-		// Non-Zero length check.
-		$crate::const_assert!($project.len() != 0, "disk: 'Project Directory' must not be an empty string");
-		$crate::const_assert!($file.len() != 0, "disk: 'File Name' must not be an empty string!");
-
-		// `Project` + `File` Length overflow check.
-		$crate::const_assert!($project.len() < 255, "disk: 'Project Directory' must be less than 255 bytes long");
-		$crate::const_assert!($file.len() < 255, "disk: 'File Name' must be less than 255 bytes long!");
-
-		// `Project` + `Sub` + `File` length overflow check.
-		$crate::const_assert!($project.len() + $sub.len() + $file.len() < 4000, "disk: Directories combined must be less than 4000 bytes long");
-
-		// `Sub` count overflow check.
-		$crate::const_assert!($crate::split!($sub, '/').len() < 10, "disk: 'Sub Directories' are limited to 10-depth");
-
-		// Individual `Sub` length overflow check.
-		#[cfg(target_os = "windows")]
-		$crate::seq!(N in 0..10 {
-			const _: () = {
-				if !$crate::contains!($sub, '\\') && $sub.len() > 255 {
-					::std::panic!("disk: the single 'Sub Directory' is longer than 255 bytes");
-				} else if N < $crate::split!($sub, '\\').len() {
-					if $crate::split!($sub, '\\')[N].len() > 255 {
-						::std::panic!("disk: one of the 'Sub Directories' is longer than 255 bytes");
-					}
-				}
-			};
-		});
-		$crate::seq!(N in 0..10 {
-			const _: () = {
-				if !$crate::contains!($sub, '/') && $sub.len() > 255 {
-					::std::panic!("disk: the single 'Sub Directory' is longer than 255 bytes");
-				} else if N < $crate::split!($sub, '/').len() {
-					if $crate::split!($sub, '/')[N].len() > 255 {
-						::std::panic!("disk: one of the 'Sub Directories' is longer than 255 bytes");
-					}
-				}
-			};
-		});
-
-		// Reserved file name check (windows-only).
-//		$crate::assert_str_reserved!("CON",  $project, $sub, $file);
-
-		// Weird symbol checks.
-		$crate::const_assert!(!$crate::contains!($project, "/"), "disk: 'Project Directory' must not contain '/'");
-		$crate::const_assert!(!$crate::contains!($project, "\\"), "disk: 'Project Directory' must not contain '\\'");
-		$crate::const_assert!(!$crate::contains!($file, "/"), "disk: 'File Name' must not contain '/'");
-		$crate::const_assert!(!$crate::contains!($file, "\\"), "disk: 'File Name' must not contain '\\'");
-		$crate::assert_str_invalid_symbol!("<",  $project, $sub, $file);
-		$crate::assert_str_invalid_symbol!(">",  $project, $sub, $file);
-		$crate::assert_str_invalid_symbol!(":",  $project, $sub, $file);
-		$crate::assert_str_invalid_symbol!("\"", $project, $sub, $file);
-		$crate::assert_str_invalid_symbol!("\'", $project, $sub, $file);
-		$crate::assert_str_invalid_symbol!("|",  $project, $sub, $file);
-		$crate::assert_str_invalid_symbol!("?",  $project, $sub, $file);
-		$crate::assert_str_invalid_symbol!("*",  $project, $sub, $file);
-		$crate::assert_str_invalid_symbol!("^",  $project, $sub, $file);
-		$crate::assert_str_invalid_symbol!("$",  $project, $sub, $file);
-		$crate::assert_str_invalid_symbol!("&",  $project, $sub, $file);
-		$crate::assert_str_invalid_symbol!("(",  $project, $sub, $file);
-		$crate::assert_str_invalid_symbol!(")",  $project, $sub, $file);
-
-		// Assert PATHs do not start/end with invalid symbol.
-		$crate::assert_str_invalid_symbol_start_end!(" ", $project, $sub, $file);
-		$crate::assert_str_invalid_symbol_start_end!("/", $project, $sub, $file);
-		$crate::assert_str_invalid_symbol_start_end!("\\", $project, $sub, $file);
+macro_rules! assert_ext {
+	($ext:expr) => {
+		const _: () = if let ::std::result::Result::Err(e) = $crate::validate_file_extension($ext) {
+			::std::panic!("{}", e);
+		};
 	}
 }
 
 //---------------------------------------------------------------------------------------------------- Macros for impl macro.
 // Binary files.
+//
+// Extra arms for the generated macro can be passed in a trailing `{ ... }`.
 macro_rules! impl_macro_binary {
-	($trait:ident, $file_ext:literal) => {
+	($trait:ident, $file_ext:literal $(, { $($arms:tt)* })?) => {
 		use $crate::Dir;
 		paste::item! {
 			#[doc = "
 Implement the [`" $trait "`] trait
 
 File extension is `" $file_ext "` and is automatically appended.
+It can be overridden with a trailing `ext = \"...\"` argument.
 
 ### Input
 These are the inputs you need to provide to implement [`" $trait "`].
@@ -1229,11 +3909,13 @@ These are the inputs you need to provide to implement [`" $trait "`].
 |----------------------|-----------------------------------------|-----------------------------------|--------------------|---------------|
 | `$data`              | Identifier of the data to implement for |                                   | `struct` or `enum` | `State`
 | `$dir`               | Which OS directory to use               | [`" $trait "::OS_DIRECTORY`]      | [`Dir`]            | [`Dir::Data`]
+| `path = $path`       | Fixed absolute base instead of `$dir`   | [`" $trait "::OS_DIRECTORY`]      | [`&str`]           | `\"/opt\"`
 | `$project_directory` | The name of the top project folder      | [`" $trait "::PROJECT_DIRECTORY`] | [`&str`]           | `\"MyProject\"`
 | `$sub_directories`   | (Optional) sub-directories before file  | [`" $trait "::SUB_DIRECTORIES`]   | [`&str`]           | `\"some/dirs\"`
 | `$file_name`         | The file name to use                    | [`" $trait "::FILE_NAME`]         | [`&str`]           | `\"state\"`
 | `$header`            | `24` custom byte header                 | [`" $trait "::HEADER`]            | `[u8; 24]`         | `[1_u8; 24]`
 | `$version`           | `1` byte custom version                 | [`" $trait "::VERSION`]           | `u8`               | `5_u8`
+| `ext = $ext`         | (Optional) override the file extension  | [`" $trait "::FILE_EXT`]          | [`&str`]           | `\"yml\"`
 
 ### Example
 ```rust,ignore
@@ -1255,8 +3937,20 @@ This example would be located at `~/.local/share/myproject/some/dirs/state." $fi
 "]
 			#[macro_export]
 			macro_rules! [<$trait:lower>] {
+				$($($arms)*)?
+				($data:ty, path = $path:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, $header:expr, $version:expr) => {
+					$crate::[<$trait:lower>]!($data, $crate::Dir::Path($path), $project_directory, $sub_directories, $file_name, $header, $version);
+				};
+				($data:ty, path = $path:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, $header:expr, $version:expr, ext = $ext:expr) => {
+					$crate::[<$trait:lower>]!($data, $crate::Dir::Path($path), $project_directory, $sub_directories, $file_name, $header, $version, ext = $ext);
+				};
 				($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, $header:expr, $version:expr) => {
-					$crate::assert_str!($project_directory, $sub_directories, $file_name);
+					$crate::[<$trait:lower>]!($data, $dir, $project_directory, $sub_directories, $file_name, $header, $version, ext = $file_ext);
+				};
+				($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, $header:expr, $version:expr, ext = $ext:expr) => {
+					$crate::assert_str!($project_directory, $sub_directories, $file_name, $ext);
+					$crate::assert_dir!($dir);
+					$crate::assert_ext!($ext);
 
 					// SAFETY: The input to this `" $trait "` implementation was verified and sanity-checked via macro.
 			 		unsafe impl $crate::$trait for $data {
@@ -1264,13 +3958,37 @@ This example would be located at `~/.local/share/myproject/some/dirs/state." $fi
 						const PROJECT_DIRECTORY:  &'static str = $project_directory;
 						const SUB_DIRECTORIES:    &'static str = $sub_directories;
 						const FILE:               &'static str = $file_name;
-						const FILE_EXT:           &'static str = $file_ext;
-						const FILE_NAME:          &'static str = $crate::const_format!("{}.{}", $file_name, $file_ext);
-						const FILE_NAME_GZIP:     &'static str = $crate::const_format!("{}.{}.gz", $file_name, $file_ext);
-						const FILE_NAME_TMP:      &'static str = $crate::const_format!("{}.{}.tmp", $file_name, $file_ext);
-						const FILE_NAME_GZIP_TMP: &'static str = $crate::const_format!("{}.{}.gz.tmp", $file_name, $file_ext);
+						const FILE_EXT:           &'static str = $ext;
+						const FILE_NAME:          &'static str = $crate::const_format!("{}.{}", $file_name, $ext);
+						const FILE_NAME_GZIP:     &'static str = $crate::const_format!("{}.{}.gz", $file_name, $ext);
+						const FILE_NAME_TMP:      &'static str = $crate::const_format!("{}.{}.tmp", $file_name, $ext);
+						const FILE_NAME_GZIP_TMP: &'static str = $crate::const_format!("{}.{}.gz.tmp", $file_name, $ext);
+						const READ_ONLY:          bool         = { #[allow(unused_imports)] use $crate::__ReadOnlyNo; $crate::__ReadOnlyProbe::<Self>::YES };
+						const WRITE_ONCE:         bool         = { #[allow(unused_imports)] use $crate::__WriteOnceNo; $crate::__WriteOnceProbe::<Self>::YES };
 						const HEADER:             [u8; 24]     = $header;
 						const VERSION:            u8           = $version;
+						const SEMVER:             Option<(u8, u8, u8)> = { #[allow(unused_imports)] use $crate::__SemverNo; $crate::__SemverProbe::<Self>::SEMVER };
+
+						#[inline(always)]
+						fn __validate(&self) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__ValidateYes,__ValidateNo};
+							(&$crate::__ValidateProbe(self)).__disk_validate()
+						}
+
+						#[inline(always)]
+						fn __journal(&mut self) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__JournalYes,__JournalNo};
+							(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal(self, Self::absolute_path)
+						}
+
+						#[inline(always)]
+						fn __journal_reset(metadata: &$crate::Metadata) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__JournalYes,__JournalNo};
+							(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal_reset(metadata, Self::absolute_path)
+						}
 					}
 				};
 			}
@@ -1288,7 +4006,8 @@ macro_rules! impl_macro_no_ext {
 			#[doc = "
 Implement the [`" $trait "`] trait
 
-[`" $trait "`] has no file extension.
+[`" $trait "`] has no file extension, unless one is
+provided with a trailing `ext = \"...\"` argument.
 
 ### Input
 These are the inputs you need to provide to implement [`" $trait "`].
@@ -1297,9 +4016,11 @@ These are the inputs you need to provide to implement [`" $trait "`].
 |----------------------|-----------------------------------------|-----------------------------------|--------------------|---------------|
 | `$data`              | Identifier of the data to implement for |                                   | `struct` or `enum` | `MyState`
 | `$dir`               | Which OS directory to use               | [`" $trait "::OS_DIRECTORY`]      | [`Dir`]            | [`Dir::Data`]
+| `path = $path`       | Fixed absolute base instead of `$dir`   | [`" $trait "::OS_DIRECTORY`]      | [`&str`]           | `\"/opt\"`
 | `$project_directory` | The name of the top project folder      | [`" $trait "::PROJECT_DIRECTORY`] | [`&str`]           | `\"MyProject\"`
 | `$sub_directories`   | (Optional) sub-directories before file  | [`" $trait "::SUB_DIRECTORIES`]   | [`&str`]           | `\"some/dirs\"`
 | `$file_name`         | The file name to use                    | [`" $trait "::FILE_NAME`]         | [`&str`]           | `\"state\"`
+| `ext = $ext`         | (Optional) override the file extension  | [`" $trait "::FILE_EXT`]          | [`&str`]           | `\"yml\"`
 
 ### Example
 ```rust
@@ -1318,8 +4039,15 @@ This example would be located at `~/.local/share/myproject/some/dirs/state`.
 "]
 			#[macro_export]
 			macro_rules! [<$trait:lower>] {
+				($data:ty, path = $path:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr) => {
+					$crate::[<$trait:lower>]!($data, $crate::Dir::Path($path), $project_directory, $sub_directories, $file_name);
+				};
+				($data:ty, path = $path:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, ext = $ext:expr) => {
+					$crate::[<$trait:lower>]!($data, $crate::Dir::Path($path), $project_directory, $sub_directories, $file_name, ext = $ext);
+				};
 				($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr) => {
 					$crate::assert_str!($project_directory, $sub_directories, $file_name);
+					$crate::assert_dir!($dir);
 
 					// SAFETY: The input to this `" $trait "` implementation was verified and sanity-checked via macro.
 			 		unsafe impl $crate::$trait for $data {
@@ -1332,6 +4060,72 @@ This example would be located at `~/.local/share/myproject/some/dirs/state`.
 						const FILE_NAME_GZIP:     &'static str = $crate::const_format!("{}.gz", $file_name);
 						const FILE_NAME_TMP:      &'static str = $crate::const_format!("{}.tmp", $file_name);
 						const FILE_NAME_GZIP_TMP: &'static str = $crate::const_format!("{}.gz.tmp", $file_name);
+						const READ_ONLY:          bool         = { #[allow(unused_imports)] use $crate::__ReadOnlyNo; $crate::__ReadOnlyProbe::<Self>::YES };
+						const WRITE_ONCE:         bool         = { #[allow(unused_imports)] use $crate::__WriteOnceNo; $crate::__WriteOnceProbe::<Self>::YES };
+						$crate::__text_options!($trait);
+
+						#[inline(always)]
+						fn __validate(&self) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__ValidateYes,__ValidateNo};
+							(&$crate::__ValidateProbe(self)).__disk_validate()
+						}
+
+						#[inline(always)]
+						fn __journal(&mut self) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__JournalYes,__JournalNo};
+							(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal(self, Self::absolute_path)
+						}
+
+						#[inline(always)]
+						fn __journal_reset(metadata: &$crate::Metadata) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__JournalYes,__JournalNo};
+							(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal_reset(metadata, Self::absolute_path)
+						}
+					}
+				};
+				($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, ext = $ext:expr) => {
+					$crate::assert_str!($project_directory, $sub_directories, $file_name, $ext);
+					$crate::assert_dir!($dir);
+					$crate::assert_ext!($ext);
+
+					// SAFETY: The input to this `" $trait "` implementation was verified and sanity-checked via macro.
+			 		unsafe impl $crate::$trait for $data {
+						const OS_DIRECTORY:       $crate::Dir  = $dir;
+						const PROJECT_DIRECTORY:  &'static str = $project_directory;
+						const SUB_DIRECTORIES:    &'static str = $sub_directories;
+						const FILE:               &'static str = $file_name;
+						const FILE_EXT:           &'static str = $ext;
+						const FILE_NAME:          &'static str = $crate::const_format!("{}.{}", $file_name, $ext);
+						const FILE_NAME_GZIP:     &'static str = $crate::const_format!("{}.{}.gz", $file_name, $ext);
+						const FILE_NAME_TMP:      &'static str = $crate::const_format!("{}.{}.tmp", $file_name, $ext);
+						const FILE_NAME_GZIP_TMP: &'static str = $crate::const_format!("{}.{}.gz.tmp", $file_name, $ext);
+						const READ_ONLY:          bool         = { #[allow(unused_imports)] use $crate::__ReadOnlyNo; $crate::__ReadOnlyProbe::<Self>::YES };
+						const WRITE_ONCE:         bool         = { #[allow(unused_imports)] use $crate::__WriteOnceNo; $crate::__WriteOnceProbe::<Self>::YES };
+						$crate::__text_options!($trait);
+
+						#[inline(always)]
+						fn __validate(&self) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__ValidateYes,__ValidateNo};
+							(&$crate::__ValidateProbe(self)).__disk_validate()
+						}
+
+						#[inline(always)]
+						fn __journal(&mut self) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__JournalYes,__JournalNo};
+							(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal(self, Self::absolute_path)
+						}
+
+						#[inline(always)]
+						fn __journal_reset(metadata: &$crate::Metadata) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__JournalYes,__JournalNo};
+							(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal_reset(metadata, Self::absolute_path)
+						}
 					}
 				};
 			}
@@ -1342,14 +4136,20 @@ This example would be located at `~/.local/share/myproject/some/dirs/state`.
 pub(crate) use impl_macro_no_ext;
 
 // Regular files.
+//
+// Extra arms for the generated macro can be passed in a trailing `{ ... }`.
 macro_rules! impl_macro {
-	($trait:ident, $file_ext:literal) => {
+	($trait:ident, $file_ext:literal $(, { $($arms:tt)* })?) => {
 		use $crate::Dir;
 		paste::paste! {
 			#[doc = "
 Implement the [`" $trait "`] trait
 
 File extension is `" $file_ext "` and is automatically appended.
+It can be overridden with a trailing `ext = \"...\"` argument.
+
+A literal `ext = \"\"` leaves the file without an extension, which together
+with a leading `.` in `$file_name` can be used for rc-files, e.g. `.myapprc`.
 
 ### Input
 These are the inputs you need to provide to implement [`" $trait "`].
@@ -1358,9 +4158,11 @@ These are the inputs you need to provide to implement [`" $trait "`].
 |----------------------|-----------------------------------------|-----------------------------------|--------------------|---------------|
 | `$data`              | Identifier of the data to implement for |                                   | `struct` or `enum` | `MyState`
 | `$dir`               | Which OS directory to use               | [`" $trait "::OS_DIRECTORY`]      | [`Dir`]            | [`Dir::Data`]
+| `path = $path`       | Fixed absolute base instead of `$dir`   | [`" $trait "::OS_DIRECTORY`]      | [`&str`]           | `\"/opt\"`
 | `$project_directory` | The name of the top project folder      | [`" $trait "::PROJECT_DIRECTORY`] | [`&str`]           | `\"MyProject\"`
 | `$sub_directories`   | (Optional) sub-directories before file  | [`" $trait "::SUB_DIRECTORIES`]   | [`&str`]           | `\"some/dirs\"`
 | `$file_name`         | The file name to use                    | [`" $trait "::FILE_NAME`]         | [`&str`]           | `\"state\"`
+| `ext = $ext`         | (Optional) override the file extension  | [`" $trait "::FILE_EXT`]          | [`&str`]           | `\"yml\"`
 
 ### Example
 ```rust
@@ -1379,8 +4181,61 @@ This example would be located at `~/.local/share/myproject/some/dirs/state." $fi
 "]
 			#[macro_export]
 			macro_rules! [<$trait:lower>] {
+				$($($arms)*)?
+				($data:ty, path = $path:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr) => {
+					$crate::[<$trait:lower>]!($data, $crate::Dir::Path($path), $project_directory, $sub_directories, $file_name);
+				};
+				($data:ty, path = $path:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, ext = $ext:expr) => {
+					$crate::[<$trait:lower>]!($data, $crate::Dir::Path($path), $project_directory, $sub_directories, $file_name, ext = $ext);
+				};
 				($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr) => {
+					$crate::[<$trait:lower>]!($data, $dir, $project_directory, $sub_directories, $file_name, ext = $file_ext);
+				};
+				($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, ext = "") => {
 					$crate::assert_str!($project_directory, $sub_directories, $file_name);
+					$crate::assert_dir!($dir);
+
+					// SAFETY: The input to this `" $trait "` implementation was verified and sanity-checked via macro.
+			 		unsafe impl $crate::$trait for $data {
+						const OS_DIRECTORY:       $crate::Dir  = $dir;
+						const PROJECT_DIRECTORY:  &'static str = $project_directory;
+						const SUB_DIRECTORIES:    &'static str = $sub_directories;
+						const FILE:               &'static str = $file_name;
+						const FILE_EXT:           &'static str = "";
+						const FILE_NAME:          &'static str = $file_name;
+						const FILE_NAME_GZIP:     &'static str = $crate::const_format!("{}.gz", $file_name);
+						const FILE_NAME_TMP:      &'static str = $crate::const_format!("{}.tmp", $file_name);
+						const FILE_NAME_GZIP_TMP: &'static str = $crate::const_format!("{}.gz.tmp", $file_name);
+						const READ_ONLY:          bool         = { #[allow(unused_imports)] use $crate::__ReadOnlyNo; $crate::__ReadOnlyProbe::<Self>::YES };
+						const WRITE_ONCE:         bool         = { #[allow(unused_imports)] use $crate::__WriteOnceNo; $crate::__WriteOnceProbe::<Self>::YES };
+						$crate::__text_options!($trait);
+
+						#[inline(always)]
+						fn __validate(&self) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__ValidateYes,__ValidateNo};
+							(&$crate::__ValidateProbe(self)).__disk_validate()
+						}
+
+						#[inline(always)]
+						fn __journal(&mut self) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__JournalYes,__JournalNo};
+							(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal(self, Self::absolute_path)
+						}
+
+						#[inline(always)]
+						fn __journal_reset(metadata: &$crate::Metadata) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__JournalYes,__JournalNo};
+							(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal_reset(metadata, Self::absolute_path)
+						}
+					}
+				};
+				($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, ext = $ext:expr) => {
+					$crate::assert_str!($project_directory, $sub_directories, $file_name, $ext);
+					$crate::assert_dir!($dir);
+					$crate::assert_ext!($ext);
 
 					// SAFETY: The input to this `" $trait "` implementation was verified and sanity-checked via macro.
 			 		unsafe impl $crate::$trait for $data {
@@ -1388,11 +4243,35 @@ This example would be located at `~/.local/share/myproject/some/dirs/state." $fi
 						const PROJECT_DIRECTORY:  &'static str = $project_directory;
 						const SUB_DIRECTORIES:    &'static str = $sub_directories;
 						const FILE:               &'static str = $file_name;
-						const FILE_EXT:           &'static str = $file_ext;
-						const FILE_NAME:          &'static str = $crate::const_format!("{}.{}", $file_name, $file_ext);
-						const FILE_NAME_GZIP:     &'static str = $crate::const_format!("{}.{}.gz", $file_name, $file_ext);
-						const FILE_NAME_TMP:      &'static str = $crate::const_format!("{}.{}.tmp", $file_name, $file_ext);
-						const FILE_NAME_GZIP_TMP: &'static str = $crate::const_format!("{}.{}.gz.tmp", $file_name, $file_ext);
+						const FILE_EXT:           &'static str = $ext;
+						const FILE_NAME:          &'static str = $crate::const_format!("{}.{}", $file_name, $ext);
+						const FILE_NAME_GZIP:     &'static str = $crate::const_format!("{}.{}.gz", $file_name, $ext);
+						const FILE_NAME_TMP:      &'static str = $crate::const_format!("{}.{}.tmp", $file_name, $ext);
+						const FILE_NAME_GZIP_TMP: &'static str = $crate::const_format!("{}.{}.gz.tmp", $file_name, $ext);
+						const READ_ONLY:          bool         = { #[allow(unused_imports)] use $crate::__ReadOnlyNo; $crate::__ReadOnlyProbe::<Self>::YES };
+						const WRITE_ONCE:         bool         = { #[allow(unused_imports)] use $crate::__WriteOnceNo; $crate::__WriteOnceProbe::<Self>::YES };
+						$crate::__text_options!($trait);
+
+						#[inline(always)]
+						fn __validate(&self) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__ValidateYes,__ValidateNo};
+							(&$crate::__ValidateProbe(self)).__disk_validate()
+						}
+
+						#[inline(always)]
+						fn __journal(&mut self) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__JournalYes,__JournalNo};
+							(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal(self, Self::absolute_path)
+						}
+
+						#[inline(always)]
+						fn __journal_reset(metadata: &$crate::Metadata) -> ::std::result::Result<(), $crate::Error> {
+							#[allow(unused_imports)]
+							use $crate::{__JournalYes,__JournalNo};
+							(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal_reset(metadata, Self::absolute_path)
+						}
 					}
 				};
 			}
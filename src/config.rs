@@ -0,0 +1,587 @@
+//---------------------------------------------------------------------------------------------------- Use
+use anyhow::{anyhow,bail};
+use serde::{Serialize,Deserialize,de::DeserializeOwned};
+use std::path::PathBuf;
+use crate::{common,Dir,Metadata};
+
+//---------------------------------------------------------------------------------------------------- Format
+/// The file formats usable with [`DiskConfig`].
+///
+/// Each variant is only available with its feature flag enabled.
+///
+/// ## Options
+/// [`Format::to_bytes()`] and [`Format::from_bytes()`] use each format's _default_
+/// options, the bytes are the same as a type implemented with the plain macro
+/// (e.g. `disk::json!(State, ...)`) would save:
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::json!(State, Dir::Data, "disk_test", "format", "state");
+/// disk::toml!(Config, Dir::Data, "disk_test", "format", "config");
+/// #[derive(Serialize,Deserialize,PartialEq,Debug)]
+/// struct State { a: u8, b: String }
+/// #[derive(Serialize,Deserialize,PartialEq,Debug)]
+/// struct Config { a: u8, b: String }
+///
+/// let state  = State { a: 1, b: "b".into() };
+/// let config = Config { a: 1, b: "b".into() };
+///
+/// assert_eq!(Format::Json.to_bytes(&state).unwrap(), state.to_bytes().unwrap());
+/// assert_eq!(Format::Toml.to_bytes(&config).unwrap(), config.to_bytes().unwrap());
+/// assert_eq!(Format::Json.from_bytes::<State>(&state.to_bytes().unwrap()).unwrap(), state);
+/// ```
+///
+/// Per-type options are **not** applied: a [`TomlStyle`](crate::TomlStyle),
+/// [`MessagePack::NAMED`](crate::MessagePack::NAMED), custom `ron`/`pickle` options
+/// or a [`LineEnding`](crate::LineEnding) only affect that type's own trait functions.
+///
+/// [`Bincode`](crate::Bincode) and [`Bincode2`](crate::Bincode2) have no variant,
+/// their files start with the type's own `HEADER` and `VERSION`.
+#[derive(Copy,Clone,Debug,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
+pub enum Format {
+	#[cfg(feature = "toml")]
+	/// [`Toml`](crate::Toml), `.toml`
+	Toml,
+	#[cfg(feature = "json")]
+	/// [`Json`](crate::Json), `.json`
+	Json,
+	#[cfg(feature = "yaml")]
+	/// [`Yaml`](crate::Yaml), `.yml`
+	Yaml,
+	#[cfg(feature = "ron")]
+	/// [`Ron`](crate::Ron), `.ron`
+	Ron,
+	#[cfg(feature = "plain")]
+	/// [`Plain`](crate::Plain), no extension
+	Plain,
+	#[cfg(feature = "pickle")]
+	/// [`Pickle`](crate::Pickle), `.pickle`
+	Pickle,
+	#[cfg(feature = "messagepack")]
+	/// [`MessagePack`](crate::MessagePack), `.messagepack`
+	MessagePack,
+	#[cfg(feature = "bson")]
+	/// [`Bson`](crate::Bson), `.bson`
+	Bson,
+	#[cfg(feature = "postcard")]
+	/// [`Postcard`](crate::Postcard), `.bin`
+	Postcard,
+}
+
+impl Format {
+	#[inline]
+	/// The file extension this format uses by default, without a leading `.`
+	///
+	/// This is the same extension the format's macro uses.
+	pub const fn file_ext(&self) -> &'static str {
+		match *self {
+			#[cfg(feature = "toml")]
+			Self::Toml        => "toml",
+			#[cfg(feature = "json")]
+			Self::Json        => "json",
+			#[cfg(feature = "yaml")]
+			Self::Yaml        => "yml",
+			#[cfg(feature = "ron")]
+			Self::Ron         => "ron",
+			#[cfg(feature = "plain")]
+			Self::Plain       => "",
+			#[cfg(feature = "pickle")]
+			Self::Pickle      => "pickle",
+			#[cfg(feature = "messagepack")]
+			Self::MessagePack => "messagepack",
+			#[cfg(feature = "bson")]
+			Self::Bson        => "bson",
+			#[cfg(feature = "postcard")]
+			Self::Postcard    => "bin",
+		}
+	}
+
+	/// Serialize `value` into bytes with this format.
+	///
+	/// The encoding options are the format trait's defaults, see [`Format`].
+	pub fn to_bytes<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, anyhow::Error> {
+		match *self {
+			#[cfg(feature = "toml")]
+			Self::Toml => Ok(common::convert_error(toml_edit::ser::to_string_pretty(value))?.into_bytes()),
+			#[cfg(feature = "json")]
+			Self::Json => {
+				let mut vec = Vec::with_capacity(128);
+				let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+				let mut ser = serde_json::ser::Serializer::with_formatter(&mut vec, formatter);
+				value.serialize(&mut ser)?;
+				Ok(vec)
+			},
+			#[cfg(feature = "yaml")]
+			Self::Yaml => {
+				let mut vec = Vec::with_capacity(128);
+				serde_yaml::to_writer(&mut vec, value)?;
+				Ok(vec)
+			},
+			#[cfg(feature = "ron")]
+			Self::Ron => {
+				let mut vec = vec![];
+				ron::ser::to_writer_pretty(&mut vec, value, ron::ser::PrettyConfig::new())?;
+				Ok(vec)
+			},
+			#[cfg(feature = "plain")]
+			Self::Plain       => Ok(format!("{}\n", common::convert_error(serde_plain::to_string(value))?).into_bytes()),
+			#[cfg(feature = "pickle")]
+			Self::Pickle      => common::convert_error(serde_pickle::ser::to_vec(value, serde_pickle::ser::SerOptions::new())),
+			#[cfg(feature = "messagepack")]
+			Self::MessagePack => common::convert_error(rmp_serde::encode::to_vec(value)),
+			#[cfg(feature = "bson")]
+			Self::Bson        => Ok(bson::to_vec(value)?),
+			#[cfg(feature = "postcard")]
+			Self::Postcard    => common::convert_error(postcard::to_stdvec(value)),
+		}
+	}
+
+	/// Deserialize bytes into a `T` with this format.
+	///
+	/// The decoding options are the format trait's defaults, see [`Format`].
+	pub fn from_bytes<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, anyhow::Error> {
+		match *self {
+			#[cfg(feature = "toml")]
+			Self::Toml        => common::convert_error(toml_edit::de::from_slice(&common::decode_text(bytes)?)),
+			#[cfg(feature = "json")]
+			Self::Json        => Ok(serde_json::de::from_slice(&common::decode_text(bytes)?)?),
+			#[cfg(feature = "yaml")]
+			Self::Yaml        => common::convert_error(serde_yaml::from_slice(&common::decode_text(bytes)?)),
+			#[cfg(feature = "ron")]
+			Self::Ron         => common::convert_error(ron::de::from_bytes(bytes)),
+			#[cfg(feature = "plain")]
+			Self::Plain => {
+				// `to_bytes()` appends a newline.
+				let string = std::str::from_utf8(bytes)?;
+				common::convert_error(serde_plain::from_str(string.strip_suffix('\n').unwrap_or(string)))
+			},
+			#[cfg(feature = "pickle")]
+			Self::Pickle => {
+				crate::limit::ensure_len(bytes.len() as u64)?;
+				common::convert_error(serde_pickle::de::from_slice(bytes, serde_pickle::de::DeOptions::new()))
+			},
+			#[cfg(feature = "messagepack")]
+			Self::MessagePack => {
+				crate::limit::ensure_len(bytes.len() as u64)?;
+				common::convert_error(rmp_serde::decode::from_slice(bytes))
+			},
+			#[cfg(feature = "bson")]
+			Self::Bson => {
+				crate::limit::ensure_len(bytes.len() as u64)?;
+				Ok(bson::from_slice(bytes)?)
+			},
+			#[cfg(feature = "postcard")]
+			Self::Postcard => {
+				crate::limit::ensure_len(bytes.len() as u64)?;
+				common::convert_error(postcard::from_bytes(bytes))
+			},
+		}
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- ErrorPolicy
+#[cfg(feature = "rayon")]
+/// How errors are handled when saving many files at once, see [`DiskConfig::save_all_keyed`].
+#[derive(Copy,Clone,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
+pub enum ErrorPolicy {
+	#[default]
+	/// Stop at the first error and return it.
+	///
+	/// Files already saved by other threads are left as-is.
+	FirstError,
+	/// Attempt every save, then return a single error listing all failures.
+	Aggregate,
+}
+
+//---------------------------------------------------------------------------------------------------- CaseCollision
+#[derive(Clone,Debug,Hash,PartialEq,Eq,PartialOrd,Ord)]
+/// Error returned when keys would overwrite each other on a case-insensitive filesystem.
+///
+/// This is returned (wrapped in [`anyhow::Error`]) by [`DiskConfig::save_all_keyed`],
+/// and can be retrieved with [`anyhow::Error::downcast_ref`], see [`DiskConfig::case_collisions`].
+pub struct CaseCollision {
+	collisions: Vec<(String, String)>,
+}
+
+impl CaseCollision {
+	/// Returns the colliding pairs of keys.
+	pub fn collisions(&self) -> &[(String, String)] {
+		&self.collisions
+	}
+}
+
+impl std::fmt::Display for CaseCollision {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "disk: keys collide on case-insensitive filesystems:")?;
+		for (a, b) in &self.collisions {
+			write!(f, " ('{a}', '{b}')")?;
+		}
+		Ok(())
+	}
+}
+
+impl std::error::Error for CaseCollision {}
+
+//---------------------------------------------------------------------------------------------------- DiskConfig
+/// A file location + [`Format`] checked at runtime
+///
+/// The macros and traits require the file's PATH to be known at compile time.
+///
+/// [`DiskConfig`] is for cases where it is only known at runtime (per-user, per-plugin, etc),
+/// without resorting to a manual `unsafe impl`.
+///
+/// The PATH is checked with the same rules as the macros (see [`crate::validate_path_components()`])
+/// when constructed, so an invalid [`DiskConfig`] cannot exist.
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// #[derive(Debug,PartialEq,Serialize,Deserialize)]
+/// struct Plugin {
+///     enabled: bool,
+/// }
+///
+/// let name   = String::from("my_plugin");
+/// let config = DiskConfig::new(Dir::Data, "disk_test", "config", &name, Format::Toml).unwrap();
+/// assert!(config.file_name() == "my_plugin.toml");
+///
+/// config.save(&Plugin { enabled: true }).unwrap();
+/// assert!(config.load::<Plugin>().unwrap() == Plugin { enabled: true });
+/// config.rm().unwrap();
+///
+/// // Invalid PATH's are caught.
+/// assert!(DiskConfig::new(Dir::Data, "", "", "state", Format::Toml).is_err());
+/// ```
+#[derive(Clone,Debug,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
+pub struct DiskConfig {
+	dir: Dir,
+	project_directory: String,
+	sub_directories: String,
+	file: String,
+	format: Format,
+}
+
+impl DiskConfig {
+	/// Create a new [`DiskConfig`].
+	///
+	/// ## Errors
+	/// This errors if the `project_directory`, `sub_directories`
+	/// or `file` are invalid, see [`crate::validate_path_components()`].
+	pub fn new(
+		dir: Dir,
+		project_directory: &str,
+		sub_directories: &str,
+		file: &str,
+		format: Format,
+	) -> Result<Self, anyhow::Error> {
+		if let Err(e) = crate::validate_path_components(project_directory, sub_directories, file) {
+			bail!(e);
+		}
+
+		Ok(Self {
+			dir,
+			project_directory: project_directory.into(),
+			sub_directories: sub_directories.into(),
+			file: file.into(),
+			format,
+		})
+	}
+
+	#[inline]
+	/// Which OS directory is used.
+	pub const fn dir(&self) -> Dir {
+		self.dir
+	}
+
+	#[inline]
+	/// The name of the top project folder.
+	pub fn project_directory(&self) -> &str {
+		&self.project_directory
+	}
+
+	#[inline]
+	/// The sub-directories before the file, this may be empty.
+	pub fn sub_directories(&self) -> &str {
+		&self.sub_directories
+	}
+
+	#[inline]
+	/// The file name, without extension.
+	pub fn file(&self) -> &str {
+		&self.file
+	}
+
+	#[inline]
+	/// The [`Format`] used.
+	pub const fn format(&self) -> Format {
+		self.format
+	}
+
+	/// Returns a copy of this [`DiskConfig`] with a different `file` name.
+	///
+	/// ## Errors
+	/// This errors if `file` is invalid, see [`crate::validate_path_components()`].
+	pub fn with_file(&self, file: &str) -> Result<Self, anyhow::Error> {
+		Self::new(self.dir, &self.project_directory, &self.sub_directories, file, self.format)
+	}
+
+	/// The file name, with extension (if the [`Format`] has one).
+	pub fn file_name(&self) -> String {
+		match self.format.file_ext() {
+			"" => self.file.clone(),
+			e  => format!("{}.{}", self.file, e),
+		}
+	}
+
+	/// Returns the full base path (PATH leading up to the file).
+	pub fn base_path(&self) -> Result<PathBuf, anyhow::Error> {
+		let mut base = common::get_projectdir(&self.dir, &self.project_directory)?;
+
+		self.sub_directories
+			.split_terminator(&['/', '\\'][..])
+			.filter(|dir| !dir.is_empty())
+			.for_each(|dir| base.push(dir));
+
+		Ok(crate::path::long_path(base))
+	}
+
+	/// Returns the absolute PATH of the file.
+	pub fn absolute_path(&self) -> Result<PathBuf, anyhow::Error> {
+		let mut base = self.base_path()?;
+		base.push(self.file_name());
+
+		common::assert_safe_path(&base)?;
+
+		Ok(crate::path::long_path(base))
+	}
+
+	/// Check if the file exists.
+	///
+	/// On success, this returns:
+	/// - The file size in bytes
+	/// - The [`PathBuf`] it's located at
+	pub fn exists(&self) -> Result<Metadata, anyhow::Error> {
+		let path = self.absolute_path()?;
+
+		match path.exists() {
+			true  => Ok(Metadata::new(common::filesize(&path), path)),
+			false => Err(anyhow!("{:?} does not exist", path)),
+		}
+	}
+
+	/// Check if the file exists.
+	///
+	/// This returns `Ok(false)` if the file does not exist and only
+	/// returns an error if existence could not be determined.
+	pub fn exists_bool(&self) -> Result<bool, anyhow::Error> {
+		Ok(self.absolute_path()?.try_exists()?)
+	}
+
+	/// Returns the file size in bytes and it's [`PathBuf`].
+	///
+	/// Unlike [`DiskConfig::exists()`], the underlying I/O error is returned as-is.
+	pub fn stat(&self) -> Result<Metadata, anyhow::Error> {
+		let path = self.absolute_path()?;
+		let size = std::fs::metadata(&path)?.len();
+		Ok(Metadata::new(size, path))
+	}
+
+	/// Returns the last modification time of the file.
+	pub fn last_modified(&self) -> Result<std::time::SystemTime, anyhow::Error> {
+		Ok(std::fs::metadata(self.absolute_path()?)?.modified()?)
+	}
+
+	/// Returns `true` if the file was modified after `time`.
+	pub fn modified_since(&self, time: std::time::SystemTime) -> Result<bool, anyhow::Error> {
+		Ok(self.last_modified()? > time)
+	}
+
+	/// Serialize `value` and atomically save it as the file.
+	///
+	/// This writes to a `.tmp` file first, then renames it, see `save_atomic()`.
+	///
+	/// Calling this will automatically create the directories leading up to the file.
+	pub fn save<T: Serialize>(&self, value: &T) -> Result<Metadata, anyhow::Error> {
+		use std::io::Write;
+
+		let bytes = self.format.to_bytes(value)?;
+
+		// Create PATH.
+		let path = self.absolute_path()?;
+		std::fs::create_dir_all(self.base_path()?)?;
+		let mut tmp = path.clone().into_os_string();
+		tmp.push(".tmp");
+		let tmp = PathBuf::from(tmp);
+
+		// Write to TMP.
+		if let Err(e) = common::file_bufw!(&tmp, bytes.len()).write_all(&bytes) {
+			std::fs::remove_file(&tmp)?;
+			bail!(e);
+		}
+
+		// Rename TMP to normal.
+		if let Err(e) = std::fs::rename(&tmp, &path) {
+			std::fs::remove_file(&tmp)?;
+			bail!(e);
+		}
+
+		Ok(Metadata::new(bytes.len() as u64, path))
+	}
+
+	#[cfg(feature = "rayon")]
+	/// Save many keyed values in parallel, each to its own file.
+	///
+	/// This [`DiskConfig`] is used as a template, each key replaces
+	/// the [`DiskConfig::file`] name (see [`DiskConfig::with_file`]),
+	/// and each value is saved with [`DiskConfig::save`].
+	///
+	/// Serialization and writes are done on the [`rayon`](https://docs.rs/rayon) global thread pool.
+	///
+	/// On success, the [`Metadata`] of every file is returned, in the same order as `entries`.
+	///
+	/// ```rust
+	/// # use disk::*;
+	/// let template = DiskConfig::new(Dir::Data, "disk_test", "keyed", "_", Format::Json).unwrap();
+	///
+	/// let users: Vec<(String, u32)> = (0..100).map(|i| (format!("user_{i}"), i)).collect();
+	/// let metadata = template.save_all_keyed(users, ErrorPolicy::FirstError).unwrap();
+	/// assert_eq!(metadata.len(), 100);
+	///
+	/// let user = template.with_file("user_50").unwrap();
+	/// assert_eq!(user.load::<u32>().unwrap(), 50);
+	/// # std::fs::remove_dir_all(template.base_path().unwrap()).unwrap();
+	/// ```
+	///
+	/// ## Errors
+	/// See [`ErrorPolicy`], errors are prefixed with the key that failed.
+	///
+	/// Nothing is saved if any keys collide on case-insensitive filesystems,
+	/// [`CaseCollision`] is returned instead, see [`DiskConfig::case_collisions`].
+	pub fn save_all_keyed<K, T, I>(&self, entries: I, policy: ErrorPolicy) -> Result<Vec<Metadata>, anyhow::Error>
+	where
+		K: AsRef<str> + Send,
+		T: Serialize + Send,
+		I: rayon::iter::IntoParallelIterator<Item = (K, T)>,
+	{
+		use rayon::iter::{IntoParallelIterator,ParallelIterator};
+
+		let entries: Vec<(K, T)> = entries.into_par_iter().collect();
+		let collisions = self.case_collisions(entries.iter().map(|(key, _)| key))?;
+		if !collisions.is_empty() {
+			return Err(CaseCollision { collisions }.into());
+		}
+
+		let save = |(key, value): (K, T)| -> Result<Metadata, anyhow::Error> {
+			let key = key.as_ref();
+			self.with_file(key)
+				.and_then(|config| config.save(&value))
+				.map_err(|e| anyhow!("{}: {}", key, e))
+		};
+
+		match policy {
+			ErrorPolicy::FirstError => entries.into_par_iter().map(save).collect(),
+			ErrorPolicy::Aggregate  => {
+				let results: Vec<Result<Metadata, anyhow::Error>> = entries.into_par_iter().map(save).collect();
+				let total = results.len();
+
+				let mut metadata = Vec::with_capacity(total);
+				let mut errors   = vec![];
+				for result in results {
+					match result {
+						Ok(m)  => metadata.push(m),
+						Err(e) => errors.push(e.to_string()),
+					}
+				}
+
+				match errors.is_empty() {
+					true  => Ok(metadata),
+					false => Err(anyhow!("{}/{} saves failed:\n{}", errors.len(), total, errors.join("\n"))),
+				}
+			},
+		}
+	}
+
+	/// Find `keys` that would be the same file on a case-insensitive filesystem.
+	///
+	/// This [`DiskConfig`] is used as a template, like [`DiskConfig::save_all_keyed`].
+	///
+	/// The default filesystems on macOS and Windows ignore case, so `Profile-A` and `profile-a`
+	/// would silently overwrite each other. Collisions are checked between `keys`, and between
+	/// `keys` and the files already in [`DiskConfig::base_path`] (with this [`Format`]'s extension).
+	///
+	/// Each collision is returned once, as the pair of keys (or existing file name, without extension).
+	/// The same key twice is not a collision.
+	///
+	/// This is checked on all platforms, so files saved on Linux can be moved to macOS/Windows.
+	/// Case is compared with [`str::to_lowercase`], which may differ from the filesystem for some non-ASCII text.
+	///
+	/// ```rust
+	/// # use disk::*;
+	/// let template = DiskConfig::new(Dir::Data, "disk_test", "case", "_", Format::Json).unwrap();
+	///
+	/// let collisions = template.case_collisions(["Profile-A", "profile-a", "profile-b", "profile-b"]).unwrap();
+	/// assert_eq!(collisions, [("Profile-A".to_string(), "profile-a".to_string())]);
+	/// ```
+	pub fn case_collisions<K, I>(&self, keys: I) -> Result<Vec<(String, String)>, anyhow::Error>
+	where
+		K: AsRef<str>,
+		I: IntoIterator<Item = K>,
+	{
+		let ext = match self.format.file_ext() {
+			"" => String::new(),
+			e  => format!(".{e}"),
+		};
+
+		// Lowercase file name -> key.
+		let mut seen = std::collections::HashMap::<String, String>::new();
+		let base = self.base_path()?;
+		if base.try_exists()? {
+			for entry in std::fs::read_dir(&base)? {
+				let name = entry?.file_name();
+				let Some(key) = name.to_str().and_then(|n| n.strip_suffix(ext.as_str())) else { continue };
+				seen.insert(format!("{key}{ext}").to_lowercase(), key.to_string());
+			}
+		}
+
+		let mut collisions = vec![];
+		for key in keys {
+			let key = key.as_ref();
+			let folded = format!("{key}{ext}").to_lowercase();
+			match seen.get(&folded) {
+				Some(other) if other == key => (),
+				Some(other) => {
+					let pair = (other.clone(), key.to_string());
+					if !collisions.contains(&pair) {
+						collisions.push(pair);
+					}
+				},
+				None => { seen.insert(folded, key.to_string()); },
+			}
+		}
+
+		Ok(collisions)
+	}
+
+	/// Read the file and deserialize it into a `T`.
+	pub fn load<T: DeserializeOwned>(&self) -> Result<T, anyhow::Error> {
+		let bytes = std::fs::read(self.absolute_path()?)?;
+		self.format.from_bytes(&bytes)
+	}
+
+	/// Try deleting the file.
+	///
+	/// This will return success if the file doesn't exist or if deleted.
+	///
+	/// On success, this returns:
+	/// - The amount of bytes removed
+	/// - The [`PathBuf`] that was removed
+	pub fn rm(&self) -> Result<Metadata, anyhow::Error> {
+		let path = self.absolute_path()?;
+
+		if !path.exists() { return Ok(Metadata::zero(path)) }
+
+		let size = common::filesize(&path);
+		std::fs::remove_file(&path)?;
+		Ok(Metadata::new(size, path))
+	}
+}
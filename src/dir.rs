@@ -3,7 +3,9 @@ use serde::{Serialize,Deserialize};
 
 //---------------------------------------------------------------------------------------------------- Types of User Dirs
 /// The different types of OS directories, provided by [`directories`](https://docs.rs/directories)
-#[derive(Copy,Clone,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
+///
+/// [`Dir::Path`] can be used instead for a fixed base directory.
+#[derive(Copy,Clone,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize)]
 pub enum Dir {
 	/// |Platform | Value                                                                 | Example                                             |
 	/// | ------- | --------------------------------------------------------------------- | --------------------------------------------------- |
@@ -49,4 +51,57 @@ pub enum Dir {
 	/// | macOS   | `$HOME`/Library/Preferences/`_project_path_`                            | /Users/Alice/Library/Preferences/com.Foo-Corp.Bar-App  |
 	/// | Windows | `{FOLDERID_RoamingAppData}`\\`_project_path_`\\config                   | C:\Users\Alice\AppData\Roaming\Foo Corp\Bar App\config |
 	Preference,
+
+	/// Runtime files (sockets, spools, etc), usually cleared on logout or reboot.
+	///
+	/// This falls back to [`Dir::Cache`] where there is no runtime directory.
+	///
+	/// |Platform | Value                                                            | Example                                             |
+	/// | ------- | ---------------------------------------------------------------- | --------------------------------------------------- |
+	/// | Linux   | `$XDG_RUNTIME_DIR`/`_project_path_`, or [`Dir::Cache`] if unset  | /run/user/1001/barapp                               |
+	/// | macOS   | [`Dir::Cache`]                                                   | /Users/Alice/Library/Caches/com.Foo-Corp.Bar-App    |
+	/// | Windows | [`Dir::Cache`]                                                   | C:\Users\Alice\AppData\Local\Foo Corp\Bar App\cache |
+	Runtime,
+
+	/// A fixed, absolute base directory, e.g. `/opt` or `/var/lib`.
+	///
+	/// This is for system services whose directory is mandated by
+	/// packaging policy, rather than the user's OS directories.
+	///
+	/// The project directory is used as-is (no lowercasing, etc), so
+	/// `Dir::Path("/opt")` with the project `MyApp` would be `/opt/MyApp`.
+	///
+	/// The macros validate the PATH at compile time with [`crate::validate_base_path`],
+	/// which can also be passed as `path = "/opt"` instead of `$dir`.
+	///
+	/// This variant can't be (de)serialized.
+	#[serde(skip)]
+	Path(&'static str),
+}
+
+// `Dir` without `Dir::Path`, which can't be deserialized.
+#[derive(Deserialize)]
+#[serde(rename = "Dir")]
+enum DirDe {
+	Project,
+	Cache,
+	Config,
+	Data,
+	DataLocal,
+	Preference,
+	Runtime,
+}
+
+impl<'de> Deserialize<'de> for Dir {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		Ok(match DirDe::deserialize(deserializer)? {
+			DirDe::Project    => Self::Project,
+			DirDe::Cache      => Self::Cache,
+			DirDe::Config     => Self::Config,
+			DirDe::Data       => Self::Data,
+			DirDe::DataLocal  => Self::DataLocal,
+			DirDe::Preference => Self::Preference,
+			DirDe::Runtime    => Self::Runtime,
+		})
+	}
 }
@@ -0,0 +1,96 @@
+//---------------------------------------------------------------------------------------------------- Use
+use std::path::Path;
+use std::process::{Command,ExitStatus};
+
+//---------------------------------------------------------------------------------------------------- Editor
+// Open `path` with the user's editor and wait for it to exit.
+//
+// This is `$VISUAL`, then `$EDITOR` (split on whitespace, so `code --wait` works),
+// falling back to the platform's default handler.
+pub(crate) fn open(path: &Path) -> Result<ExitStatus, anyhow::Error> {
+	let editor = ["VISUAL", "EDITOR"]
+		.into_iter()
+		.filter_map(std::env::var_os)
+		.find(|e| !e.is_empty());
+
+	let mut command = match editor {
+		Some(editor) => {
+			let editor = editor.to_string_lossy().into_owned();
+			let mut args = editor.split_whitespace();
+			let Some(program) = args.next() else {
+				anyhow::bail!("disk: the editor command is empty");
+			};
+			let mut command = Command::new(program);
+			command.args(args);
+			command
+		},
+		None => default_handler(),
+	};
+
+	match command.arg(path).status() {
+		Ok(status) => Ok(status),
+		Err(e) => Err(anyhow::Error::new(e).context(format!("disk: failed to start editor {:?}", command.get_program()))),
+	}
+}
+
+// The platform's default handler for files.
+fn default_handler() -> Command {
+	#[cfg(target_os = "windows")]
+	{
+		let mut command = Command::new("cmd");
+		command.args(["/C", "start", "/WAIT", ""]);
+		command
+	}
+
+	#[cfg(target_os = "macos")]
+	{
+		let mut command = Command::new("open");
+		command.args(["-W", "-t"]);
+		command
+	}
+
+	#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+	Command::new("xdg-open")
+}
+
+//---------------------------------------------------------------------------------------------------- Reveal
+// Open `dir` in the platform's file manager, selecting `file` if it exists.
+//
+// This does not wait for the file manager.
+pub(crate) fn reveal(dir: &Path, file: &Path) -> Result<(), anyhow::Error> {
+	let select = file.is_file();
+
+	#[cfg(target_os = "windows")]
+	let mut command = {
+		let mut command = Command::new("explorer");
+		match select {
+			true  => command.arg(format!("/select,{}", file.display())),
+			false => command.arg(dir),
+		};
+		command
+	};
+
+	#[cfg(target_os = "macos")]
+	let mut command = {
+		let mut command = Command::new("open");
+		match select {
+			true  => command.arg("-R").arg(file),
+			false => command.arg(dir),
+		};
+		command
+	};
+
+	// `xdg-open` can not select files.
+	#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+	let mut command = {
+		let _ = select;
+		let mut command = Command::new("xdg-open");
+		command.arg(dir);
+		command
+	};
+
+	match command.spawn() {
+		Ok(_)  => Ok(()),
+		Err(e) => Err(anyhow::Error::new(e).context(format!("disk: failed to start file manager {:?}", command.get_program()))),
+	}
+}
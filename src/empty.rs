@@ -1,5 +1,5 @@
 //---------------------------------------------------------------------------------------------------- Use
-use anyhow::{anyhow};
+use anyhow::{anyhow,bail};
 use std::path::PathBuf;
 use crate::common;
 
@@ -42,6 +42,7 @@ crate::common::impl_macro_no_ext!(Empty);
 ///
 /// // Make sure the file no longer exist.
 /// assert!(!Hello::exists().is_ok());
+/// assert!(!Hello::exists_bool().unwrap());
 /// ```
 /// This creates a file called `hello`, containing no data. The `bool` is ignored.
 ///
@@ -54,16 +55,67 @@ pub unsafe trait Empty {
 	///
 	/// Calling this will automatically create the directories leading up to the file.
 	fn touch() -> Result<(), anyhow::Error> {
+		crate::common::assert_writable!();
 		// Create PATH.
 		let mut path = Self::base_path()?;
 		std::fs::create_dir_all(&path)?;
 		path.push(Self::FILE_NAME);
+		crate::common::ensure_write_once!(path);
 
 		// Create file.
 		std::fs::File::create(path)?;
 		Ok(())
 	}
 
+	/// Same as [`Self::touch()`], but the file is created via a `.tmp` file and a rename.
+	///
+	/// An existing file is atomically replaced with an empty one,
+	/// other processes never see a partially truncated file.
+	///
+	/// Calling this will automatically create the directories leading up to the file.
+	fn touch_atomic() -> Result<(), anyhow::Error> {
+		crate::common::assert_writable!();
+		// Create PATH.
+		let mut path = Self::base_path()?;
+		std::fs::create_dir_all(&path)?;
+		let mut tmp = path.clone();
+		path.push(Self::FILE_NAME);
+		tmp.push(Self::FILE_NAME_TMP);
+		let (path, tmp) = crate::symlink::save_path_atomic(path, tmp)?;
+		crate::common::ensure_write_once!(path);
+
+		// Create TMP, then rename it to normal.
+		std::fs::File::create(&tmp)?;
+		if let Err(e) = std::fs::rename(&tmp, &path) {
+			std::fs::remove_file(&tmp)?;
+			bail!(e);
+		}
+		Ok(())
+	}
+
+	/// Create the empty file, only if it does not already exist.
+	///
+	/// This returns `true` if the file was created by this call and `false` if it already existed.
+	///
+	/// The check and creation are a single atomic operation (`O_EXCL`), so this can be
+	/// used as a cross-process "first writer wins" latch, only one caller ever gets `true`.
+	///
+	/// Calling this will automatically create the directories leading up to the file.
+	fn touch_exclusive() -> Result<bool, anyhow::Error> {
+		crate::common::assert_writable!();
+		// Create PATH.
+		let mut path = Self::base_path()?;
+		std::fs::create_dir_all(&path)?;
+		path.push(Self::FILE_NAME);
+
+		// Create file, failing if it exists.
+		match std::fs::OpenOptions::new().write(true).create_new(true).open(path) {
+			Ok(_) => Ok(true),
+			Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(false),
+			Err(e) => Err(e.into()),
+		}
+	}
+
 	// Common path methods.
 	common::impl_common!("");
 }
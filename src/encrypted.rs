@@ -0,0 +1,296 @@
+//---------------------------------------------------------------------------------------------------- Use
+use anyhow::{anyhow,bail};
+use base64::Engine;
+use chacha20poly1305::{KeyInit,XChaCha20Poly1305,XNonce};
+use chacha20poly1305::aead::{Aead,Payload};
+use serde::{Serialize,Deserialize,Serializer,Deserializer};
+use std::marker::PhantomData;
+use std::sync::{Arc,RwLock};
+
+//---------------------------------------------------------------------------------------------------- Key provider
+// A function returning the encryption key.
+type KeyProvider = Arc<dyn Fn() -> Result<[u8; 32], anyhow::Error> + Send + Sync + 'static>;
+
+// The key provider, if any.
+static KEY_PROVIDER: RwLock<Option<KeyProvider>> = RwLock::new(None);
+
+/// Set the function that returns the key of [`Encrypted`] fields, for your entire process.
+///
+/// It is called every time an [`Encrypted`] field is serialized or deserialized,
+/// so it can fetch the key from somewhere else (the OS keyring, an environment variable, etc).
+///
+/// The key should be 32 random bytes, not a password, for example
+/// one generated with `openssl rand -base64 32` and stored base64 encoded:
+/// ```rust
+/// use base64::Engine;
+///
+/// disk::set_key_provider(|| {
+///     let key   = std::env::var("MYAPP_KEY")?;
+///     let bytes = base64::engine::general_purpose::STANDARD.decode(key.trim())?;
+///     <[u8; 32]>::try_from(bytes)
+///         .map_err(|b| anyhow::anyhow!("MYAPP_KEY must be 32 bytes, got {}", b.len()))
+/// });
+/// # disk::clear_key_provider();
+/// ```
+pub fn set_key_provider<F>(provider: F)
+where
+	F: Fn() -> Result<[u8; 32], anyhow::Error> + Send + Sync + 'static,
+{
+	*KEY_PROVIDER.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(provider));
+}
+
+/// Same as [`set_key_provider()`], but always use `key`.
+pub fn set_encryption_key(key: [u8; 32]) {
+	set_key_provider(move || Ok(key));
+}
+
+/// Remove the key set with [`set_key_provider()`] or [`set_encryption_key()`].
+///
+/// [`Encrypted`] fields error when (de)serialized without a key.
+pub fn clear_key_provider() {
+	*KEY_PROVIDER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+//---------------------------------------------------------------------------------------------------- EncryptionContext
+/// The context an [`Encrypted`] value is bound to.
+///
+/// The context is authenticated along with the value (as AEAD associated data),
+/// so a value encrypted with one context fails to decrypt with any other.
+///
+/// Without a context (`()`, the default), an encrypted value can be copied from
+/// one [`Encrypted`] field (or file) into another with the same key and still decrypt.
+/// Give each field its own context to prevent that:
+/// ```rust
+/// # use disk::*;
+/// struct AccountToken;
+/// impl EncryptionContext for AccountToken {
+///     const CONTEXT: &'static str = "myapp/account/token";
+/// }
+///
+/// struct Account {
+///     token: Encrypted<String, AccountToken>,
+/// }
+/// ```
+pub trait EncryptionContext {
+	/// The context string, e.g. `myapp/account/token`.
+	const CONTEXT: &'static str;
+}
+
+impl EncryptionContext for () {
+	const CONTEXT: &'static str = "";
+}
+
+//---------------------------------------------------------------------------------------------------- Encrypted
+/// A field that is encrypted on disk.
+///
+/// Wrapping a field in [`Encrypted`] makes it serialize as an encrypted string, while the
+/// rest of the file stays human-readable, and it is decrypted when deserialized,
+/// with the key from [`set_key_provider()`] or [`set_encryption_key()`]:
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::json!(Account, Dir::Data, "disk_test", "encrypted", "account");
+/// #[derive(Serialize,Deserialize)]
+/// struct Account {
+///     user: String,
+///     token: Encrypted<String>,
+/// }
+///
+/// disk::set_encryption_key([7; 32]);
+///
+/// let account = Account { user: "alice".into(), token: Encrypted::new("hunter2".into()) };
+/// account.save().unwrap();
+///
+/// // Only the token is encrypted.
+/// let file = std::fs::read_to_string(Account::absolute_path().unwrap()).unwrap();
+/// assert!(file.contains("alice"));
+/// assert!(!file.contains("hunter2"));
+///
+/// assert_eq!(*Account::from_file().unwrap().token, "hunter2");
+///
+/// // The wrong key fails to load.
+/// disk::set_encryption_key([8; 32]);
+/// assert!(Account::from_file().is_err());
+/// # disk::clear_key_provider();
+/// # Account::rm_sub().unwrap();
+/// ```
+///
+/// The value is serialized as JSON and encrypted with `XChaCha20-Poly1305`,
+/// with a key derived from the 32 byte key with `HKDF-SHA256` and a random nonce every save.
+/// The context `C` (see [`EncryptionContext`]) is authenticated as associated data.
+/// The string is `disk:v2:` followed by the nonce and ciphertext in URL-safe `base64`,
+/// so it works with every format.
+///
+/// [`std::fmt::Debug`] never prints the value.
+pub struct Encrypted<T, C: EncryptionContext = ()>(pub T, PhantomData<fn() -> C>);
+
+impl<T, C: EncryptionContext> Encrypted<T, C> {
+	#[inline]
+	/// Wrap `value`.
+	pub const fn new(value: T) -> Self {
+		Self(value, PhantomData)
+	}
+
+	#[inline]
+	/// Returns the inner value.
+	pub fn into_inner(self) -> T {
+		self.0
+	}
+}
+
+// Manual impls, `C` is only a marker.
+impl<T: Clone, C: EncryptionContext> Clone for Encrypted<T, C> {
+	#[inline]
+	fn clone(&self) -> Self {
+		Self::new(self.0.clone())
+	}
+}
+
+impl<T: Copy, C: EncryptionContext> Copy for Encrypted<T, C> {}
+
+impl<T: Default, C: EncryptionContext> Default for Encrypted<T, C> {
+	#[inline]
+	fn default() -> Self {
+		Self::new(T::default())
+	}
+}
+
+impl<T: PartialEq, C: EncryptionContext> PartialEq for Encrypted<T, C> {
+	#[inline]
+	fn eq(&self, other: &Self) -> bool {
+		self.0 == other.0
+	}
+}
+
+impl<T: Eq, C: EncryptionContext> Eq for Encrypted<T, C> {}
+
+impl<T: PartialOrd, C: EncryptionContext> PartialOrd for Encrypted<T, C> {
+	#[inline]
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		self.0.partial_cmp(&other.0)
+	}
+}
+
+impl<T: Ord, C: EncryptionContext> Ord for Encrypted<T, C> {
+	#[inline]
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		self.0.cmp(&other.0)
+	}
+}
+
+impl<T: std::hash::Hash, C: EncryptionContext> std::hash::Hash for Encrypted<T, C> {
+	#[inline]
+	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+		self.0.hash(state);
+	}
+}
+
+impl<T, C: EncryptionContext> std::ops::Deref for Encrypted<T, C> {
+	type Target = T;
+	#[inline]
+	fn deref(&self) -> &T {
+		&self.0
+	}
+}
+
+impl<T, C: EncryptionContext> std::ops::DerefMut for Encrypted<T, C> {
+	#[inline]
+	fn deref_mut(&mut self) -> &mut T {
+		&mut self.0
+	}
+}
+
+impl<T, C: EncryptionContext> From<T> for Encrypted<T, C> {
+	#[inline]
+	fn from(value: T) -> Self {
+		Self::new(value)
+	}
+}
+
+impl<T, C: EncryptionContext> std::fmt::Debug for Encrypted<T, C> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("Encrypted(..)")
+	}
+}
+
+impl<T: Serialize, C: EncryptionContext> Serialize for Encrypted<T, C> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		use serde::ser::Error;
+		let plaintext = serde_json::to_vec(&self.0).map_err(S::Error::custom)?;
+		let string = encrypt(&plaintext, C::CONTEXT).map_err(S::Error::custom)?;
+		serializer.serialize_str(&string)
+	}
+}
+
+impl<'de, T: serde::de::DeserializeOwned, C: EncryptionContext> Deserialize<'de> for Encrypted<T, C> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		use serde::de::Error;
+		let string = String::deserialize(deserializer)?;
+		let plaintext = decrypt(&string, C::CONTEXT).map_err(D::Error::custom)?;
+		serde_json::from_slice(&plaintext).map(Self::new).map_err(D::Error::custom)
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Private
+// The prefix of encrypted strings.
+const PREFIX: &str = "disk:v2:";
+const NONCE_LEN: usize = 24;
+const TAG_LEN: usize = 16;
+
+// Returns the key from the key provider.
+fn key() -> Result<[u8; 32], anyhow::Error> {
+	let provider = KEY_PROVIDER.read().unwrap_or_else(|e| e.into_inner()).clone();
+	match provider {
+		Some(provider) => provider(),
+		None => bail!("disk: no encryption key, see disk::set_key_provider()"),
+	}
+}
+
+// The cipher with the key derived from the key provider's key.
+fn cipher() -> Result<XChaCha20Poly1305, anyhow::Error> {
+	let mut derived = [0; 32];
+	hkdf::Hkdf::<sha2::Sha256>::new(None, &key()?)
+		.expand(PREFIX.as_bytes(), &mut derived)
+		.map_err(|e| anyhow!("disk: failed to derive encryption key: {e}"))?;
+	Ok(XChaCha20Poly1305::new(&derived.into()))
+}
+
+// The associated data of `context`.
+fn aad(context: &str) -> Vec<u8> {
+	[PREFIX.as_bytes(), context.as_bytes()].concat()
+}
+
+// Encrypt `plaintext` bound to `context` into a `disk:v2:` string.
+fn encrypt(plaintext: &[u8], context: &str) -> Result<String, anyhow::Error> {
+	let cipher = cipher()?;
+
+	let mut nonce = [0; NONCE_LEN];
+	getrandom::getrandom(&mut nonce).map_err(|e| anyhow!("disk: failed to generate nonce: {e}"))?;
+
+	let aad = aad(context);
+	let ciphertext = cipher
+		.encrypt(XNonce::from_slice(&nonce), Payload { msg: plaintext, aad: &aad })
+		.map_err(|e| anyhow!("disk: failed to encrypt: {e}"))?;
+
+	let mut bytes = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+	bytes.extend_from_slice(&nonce);
+	bytes.extend_from_slice(&ciphertext);
+	Ok(format!("{PREFIX}{}", base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)))
+}
+
+// Verify and decrypt a `disk:v2:` string bound to `context`.
+fn decrypt(string: &str, context: &str) -> Result<Vec<u8>, anyhow::Error> {
+	let Some(encoded) = string.strip_prefix(PREFIX) else {
+		bail!("disk: encrypted value does not start with '{PREFIX}'");
+	};
+	let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(encoded)?;
+	if bytes.len() < NONCE_LEN + TAG_LEN {
+		bail!("disk: encrypted value is too short");
+	}
+
+	let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+	let aad = aad(context);
+	cipher()?
+		.decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: &aad })
+		.map_err(|_| anyhow!("disk: encrypted value failed authentication (wrong key, wrong context, or modified)"))
+}
@@ -0,0 +1,191 @@
+//---------------------------------------------------------------------------------------------------- Use
+#[allow(unused_imports)]
+use std::borrow::Cow;
+#[allow(unused_imports)]
+use std::sync::Arc;
+#[allow(unused_imports)]
+use crate::*;
+
+//---------------------------------------------------------------------------------------------------- Smart pointers
+// Every file format trait (except `Empty`) is implemented for `Box<T>`, `Arc<T>` and `Cow<'static, T>`
+// if `T` implements it, with everything `T`'s macro set (PATHs, header, options, etc) forwarded, e.g:
+//
+// Arc::<State>::from_file() // <- Reads `State`'s file.
+// arc_state.save()           // <- Saves `State`'s file.
+//
+// The bytes are the same as `T`'s, so they can be read by either.
+
+// Implement `$trait` for the smart pointers of `T`.
+//
+// `$extra` are the format-specific items to forward, see the `@` rules below.
+macro_rules! impl_forward {
+	($trait:ident $(, $extra:ident)*) => {
+		// SAFETY: The PATHs are `T`'s, which were verified by its implementation.
+		unsafe impl<T: $trait> $trait for Box<T> {
+			crate::forward::impl_forward!(@common $trait);
+			$(crate::forward::impl_forward!(@$extra $trait, Box::new);)*
+
+			#[inline(always)]
+			fn __journal(&mut self) -> Result<(), anyhow::Error> {
+				<T as $trait>::__journal(self)
+			}
+		}
+
+		// SAFETY: The PATHs are `T`'s, which were verified by its implementation.
+		unsafe impl<T: $trait> $trait for Arc<T> {
+			crate::forward::impl_forward!(@common $trait);
+			$(crate::forward::impl_forward!(@$extra $trait, Arc::new);)*
+
+			#[inline(always)]
+			fn __journal(&mut self) -> Result<(), anyhow::Error> {
+				match Arc::get_mut(self) {
+					Some(t) => <T as $trait>::__journal(t),
+					None    => anyhow::bail!("disk: the `Arc` is shared, its journal can not be replayed"),
+				}
+			}
+		}
+
+		// SAFETY: The PATHs are `T`'s, which were verified by its implementation.
+		unsafe impl<T: $trait + Clone + 'static> $trait for Cow<'static, T> {
+			crate::forward::impl_forward!(@common $trait);
+			$(crate::forward::impl_forward!(@$extra $trait, Cow::Owned);)*
+
+			#[inline(always)]
+			fn __journal(&mut self) -> Result<(), anyhow::Error> {
+				<T as $trait>::__journal(self.to_mut())
+			}
+		}
+	};
+
+	// Items of all formats.
+	(@common $trait:ident) => {
+		const OS_DIRECTORY:       crate::Dir   = <T as $trait>::OS_DIRECTORY;
+		const PROJECT_DIRECTORY:  &'static str = <T as $trait>::PROJECT_DIRECTORY;
+		const SUB_DIRECTORIES:    &'static str = <T as $trait>::SUB_DIRECTORIES;
+		const FILE:               &'static str = <T as $trait>::FILE;
+		const FILE_EXT:           &'static str = <T as $trait>::FILE_EXT;
+		const FILE_NAME:          &'static str = <T as $trait>::FILE_NAME;
+		const FILE_NAME_GZIP:     &'static str = <T as $trait>::FILE_NAME_GZIP;
+		const FILE_NAME_TMP:      &'static str = <T as $trait>::FILE_NAME_TMP;
+		const FILE_NAME_GZIP_TMP: &'static str = <T as $trait>::FILE_NAME_GZIP_TMP;
+		const WRITE_BUF_SIZE:     usize        = <T as $trait>::WRITE_BUF_SIZE;
+		const READ_ONLY:          bool         = <T as $trait>::READ_ONLY;
+		const WRITE_ONCE:         bool         = <T as $trait>::WRITE_ONCE;
+
+		#[inline(always)]
+		fn __validate(&self) -> Result<(), anyhow::Error> {
+			<T as $trait>::__validate(self)
+		}
+
+		#[inline(always)]
+		fn __journal_reset(metadata: &crate::Metadata) -> Result<(), anyhow::Error> {
+			<T as $trait>::__journal_reset(metadata)
+		}
+	};
+
+	// `Bincode`, `Bincode2`.
+	(@header $trait:ident, $wrap:path) => {
+		const HEADER:  [u8; 24]             = <T as $trait>::HEADER;
+		const VERSION: u8                   = <T as $trait>::VERSION;
+		const SEMVER:  Option<(u8, u8, u8)> = <T as $trait>::SEMVER;
+	};
+
+	// `Bincode2`.
+	(@bincode2 $trait:ident, $wrap:path) => {
+		#[inline(always)]
+		fn __encode_to_vec(&self) -> Result<Vec<u8>, ::bincode2::error::EncodeError> {
+			<T as $trait>::__encode_to_vec(self)
+		}
+
+		#[inline(always)]
+		fn __encode_value_to_vec<V: ::bincode2::Encode>(value: &V) -> Result<Vec<u8>, ::bincode2::error::EncodeError> {
+			<T as $trait>::__encode_value_to_vec(value)
+		}
+
+		#[inline(always)]
+		fn __encode_into_slice(&self, slice: &mut [u8]) -> Result<usize, ::bincode2::error::EncodeError> {
+			<T as $trait>::__encode_into_slice(self, slice)
+		}
+
+		#[inline(always)]
+		fn __encode_into_std_write<W: std::io::Write>(&self, writer: &mut W) -> Result<usize, ::bincode2::error::EncodeError> {
+			<T as $trait>::__encode_into_std_write(self, writer)
+		}
+
+		#[inline(always)]
+		fn __decode_from_slice(bytes: &[u8]) -> Result<(Self, usize), ::bincode2::error::DecodeError> {
+			<T as $trait>::__decode_from_slice(bytes).map(|(t, len)| ($wrap(t), len))
+		}
+
+		#[inline(always)]
+		fn __decode_from_std_read<R: std::io::Read>(reader: &mut R) -> Result<Self, ::bincode2::error::DecodeError> {
+			<T as $trait>::__decode_from_std_read(reader).map($wrap)
+		}
+	};
+
+	// `Toml`.
+	(@toml $trait:ident, $wrap:path) => {
+		const TOML_STYLE: crate::TomlStyle = <T as $trait>::TOML_STYLE;
+	};
+
+	// Text formats.
+	(@text $trait:ident, $wrap:path) => {
+		const NEWLINE: crate::Newline = <T as $trait>::NEWLINE;
+		const WRITE_STAMP: Option<&'static str> = <T as $trait>::WRITE_STAMP;
+	};
+
+	// `MessagePack`.
+	(@messagepack $trait:ident, $wrap:path) => {
+		const NAMED: bool = <T as $trait>::NAMED;
+	};
+
+	// `Pickle`.
+	(@pickle $trait:ident, $wrap:path) => {
+		#[inline(always)]
+		fn __ser_options() -> ::serde_pickle::SerOptions {
+			<T as $trait>::__ser_options()
+		}
+
+		#[inline(always)]
+		fn __de_options() -> ::serde_pickle::DeOptions {
+			<T as $trait>::__de_options()
+		}
+	};
+
+	// `Ron`.
+	(@ron $trait:ident, $wrap:path) => {
+		#[inline(always)]
+		fn __pretty_config() -> ::ron::ser::PrettyConfig {
+			<T as $trait>::__pretty_config()
+		}
+
+		#[inline(always)]
+		fn __options() -> ::ron::Options {
+			<T as $trait>::__options()
+		}
+	};
+}
+pub(crate) use impl_forward;
+
+#[cfg(feature = "bincode")]
+impl_forward!(Bincode, header);
+#[cfg(feature = "bincode2")]
+impl_forward!(Bincode2, header, bincode2);
+#[cfg(feature = "bson")]
+impl_forward!(Bson);
+#[cfg(feature = "json")]
+impl_forward!(Json, text);
+#[cfg(feature = "messagepack")]
+impl_forward!(MessagePack, messagepack);
+#[cfg(feature = "pickle")]
+impl_forward!(Pickle, pickle);
+#[cfg(feature = "plain")]
+impl_forward!(Plain, text);
+#[cfg(feature = "postcard")]
+impl_forward!(Postcard);
+#[cfg(feature = "ron")]
+impl_forward!(Ron, ron, text);
+#[cfg(feature = "toml")]
+impl_forward!(Toml, toml, text);
+#[cfg(feature = "yaml")]
+impl_forward!(Yaml, text);
@@ -1,21 +1,43 @@
+//---------------------------------------------------------------------------------------------------- Use
+use std::collections::HashMap;
+use std::sync::RwLock;
+use once_cell::sync::Lazy;
+
 //---------------------------------------------------------------------------------------------------- Header check/append.
 macro_rules! ensure_header {
 	($bytes:ident) => {
 		let len = $bytes.len();
 
-		// Ensure our `[u8; 25]` HEADER + VERSION bytes are there.
-		if len < 25 {
-			bail!("invalid header bytes, total byte length less than 25: {len}");
+		// Ensure our `[u8; 25]` HEADER + VERSION (+ `[u8; 3]` SEMVER) bytes are there.
+		let header_len = Self::header_len();
+		if len < header_len {
+			bail!("invalid header bytes, total byte length less than {header_len}: {len}");
 		}
 
 		// Ensure our HEADER is correct.
 		if $bytes[..24] != Self::HEADER {
-			bail!("incorrect header bytes\nexpected: {:?}\nfound: {:?}", Self::HEADER, &$bytes[..24],);
+			bail!(
+				"incorrect header bytes{}\nexpected: {:?}\nfound: {:?}",
+				crate::header::mismatch_hint(&$bytes[..25], &Self::full_header()), Self::HEADER, &$bytes[..24],
+			);
 		}
 
 		// Ensure our VERSION is correct.
 		if $bytes[24] != Self::VERSION {
-			bail!("incorrect version byte\nexpected: {:?}\nfound: {:?}", Self::VERSION, &$bytes[24],);
+			bail!(
+				"incorrect version byte{}\nexpected: {:?}\nfound: {:?}",
+				crate::header::mismatch_hint(&$bytes[..25], &Self::full_header()), Self::VERSION, &$bytes[24],
+			);
+		}
+
+		// Ensure our SEMVER is correct.
+		if let Some((major, minor, patch)) = Self::SEMVER {
+			if $bytes[25..28] != [major, minor, patch] {
+				bail!(
+					"incorrect semantic version\nexpected: {major}.{minor}.{patch}\nfound: {}.{}.{}",
+					$bytes[25], $bytes[26], $bytes[27],
+				);
+			}
 		}
 	}
 }
@@ -23,7 +45,7 @@ pub(crate) use ensure_header;
 
 macro_rules! header_return {
 	($buf:ident) => {{
-		let mut bytes = Self::full_header().to_vec();
+		let mut bytes = Self::header_bytes();
 		bytes.append(&mut $buf);
 
 		Ok(bytes)
@@ -31,6 +53,174 @@ macro_rules! header_return {
 }
 pub(crate) use header_return;
 
+//---------------------------------------------------------------------------------------------------- Magic registry
+// Human-readable descriptions of known headers.
+static MAGIC: Lazy<RwLock<HashMap<[u8; 24], String>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Register a human-readable description of a 24-byte header, for your entire process.
+///
+/// When [`crate::Bincode`] or [`crate::Bincode2`] fail to read a file because of
+/// a header mismatch, the error includes the description of the file's header (and
+/// the expected one) if they are registered, e.g:
+/// ```txt
+/// incorrect header bytes: file appears to be a 'MyApp v1 library file' (version 3), expected 'MyApp v2 library file' (version 0)
+/// ```
+///
+/// This is useful for registering old (or other apps') headers, so users (and support) can tell what a file is.
+///
+/// This replaces any previous description of `header`.
+///
+/// ```rust
+/// disk::register_magic(*b"myapp_v1_library_file___", "MyApp v1 library file");
+/// assert_eq!(disk::magic_description(b"myapp_v1_library_file___").as_deref(), Some("MyApp v1 library file"));
+///
+/// disk::unregister_magic(b"myapp_v1_library_file___");
+/// assert_eq!(disk::magic_description(b"myapp_v1_library_file___"), None);
+/// ```
+pub fn register_magic(header: [u8; 24], description: &str) {
+	MAGIC.write().unwrap_or_else(|e| e.into_inner()).insert(header, description.into());
+}
+
+/// Remove the description of `header` registered with [`register_magic()`], if any.
+pub fn unregister_magic(header: &[u8; 24]) {
+	MAGIC.write().unwrap_or_else(|e| e.into_inner()).remove(header);
+}
+
+/// Returns the description of `header` registered with [`register_magic()`], if any.
+pub fn magic_description(header: &[u8; 24]) -> Option<String> {
+	MAGIC.read().unwrap_or_else(|e| e.into_inner()).get(header).cloned()
+}
+
+#[cfg(any(feature = "bincode", feature = "bincode2"))]
+// Describe the `found` header + version against the `expected` one,
+// e.g: `: file appears to be a 'MyApp v1' (version 3), expected 'MyApp v2' (version 0)`.
+//
+// Returns an empty string if `found` is not registered.
+pub(crate) fn mismatch_hint(found: &[u8], expected: &[u8; 25]) -> String {
+	let Ok(header) = <&[u8; 24]>::try_from(&found[..24]) else {
+		return String::new();
+	};
+	let Some(description) = magic_description(header) else {
+		return String::new();
+	};
+
+	let mut expected_header = [0; 24];
+	expected_header.copy_from_slice(&expected[..24]);
+
+	match magic_description(&expected_header) {
+		Some(e) => format!(": file appears to be a '{description}' (version {}), expected '{e}' (version {})", found[24], expected[24]),
+		None    => format!(": file appears to be a '{description}' (version {}), expected version {}", found[24], expected[24]),
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Semver
+/// Add a semantic version to a binary type's header.
+///
+/// A single [`crate::Bincode::VERSION`] byte can be too coarse, if your type implements
+/// this trait _and_ [`crate::Bincode`] or [`crate::Bincode2`] via macro, 3 more bytes
+/// (`MAJOR`, `MINOR`, `PATCH`) are saved after the version byte, for a 28 byte header:
+/// ```txt
+/// | HEADER (24 bytes) | VERSION (1 byte) | MAJOR | MINOR | PATCH | data... |
+/// ```
+///
+/// Like `VERSION`, reading a file errors if its semantic version is not exactly [`Self::MAJOR`].[`Self::MINOR`].[`Self::PATCH`],
+/// use `file_version_semver()` and `from_versions_semver()` to read older files.
+///
+/// Files saved with and without this trait are not compatible, change [`crate::Bincode::HEADER`]
+/// or [`crate::Bincode::VERSION`] when adding it to an existing type.
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::bincode!(Library, Dir::Data, "disk_test", "semver", "library", [1_u8; 24], 0);
+/// #[derive(Serialize,Deserialize)]
+/// struct Library {
+///     songs: Vec<String>,
+/// }
+///
+/// impl disk::Semver for Library {
+///     const MAJOR: u8 = 1;
+///     const MINOR: u8 = 2;
+///     const PATCH: u8 = 0;
+/// }
+///
+/// assert_eq!(Library::SEMVER, Some((1, 2, 0)));
+///
+/// Library { songs: vec![] }.save().unwrap();
+/// assert_eq!(Library::file_version_semver().unwrap(), (1, 2, 0));
+/// # Library::rm_sub().unwrap();
+/// ```
+///
+/// Manual `unsafe impl`'s must set `const SEMVER: Option<(u8, u8, u8)>` themselves.
+pub trait Semver {
+	/// The major version.
+	const MAJOR: u8;
+	/// The minor version.
+	const MINOR: u8;
+	/// The patch version.
+	const PATCH: u8;
+}
+
+// Same as `__ReadOnlyProbe`, `__SemverProbe::<T>::SEMVER` resolves
+// to the inherent `Some` if `Semver` is implemented, else `__SemverNo::SEMVER` (`None`).
+#[doc(hidden)]
+pub struct __SemverProbe<T>(std::marker::PhantomData<T>);
+
+impl<T: Semver> __SemverProbe<T> {
+	pub const SEMVER: Option<(u8, u8, u8)> = Some((T::MAJOR, T::MINOR, T::PATCH));
+}
+
+#[doc(hidden)]
+pub trait __SemverNo {
+	const SEMVER: Option<(u8, u8, u8)> = None;
+}
+
+impl<T> __SemverNo for __SemverProbe<T> {}
+
+#[cfg(any(feature = "bincode", feature = "bincode2"))]
+// If `version` matches the requirement `req`, e.g: `>=1.2, <2.0`.
+//
+// `req` is a comma separated list of comparisons that must all match,
+// `>=`, `>`, `<=`, `<` or `=` followed by a version (missing parts are `0`).
+// A version without an operator is the same as `=`, `*` matches everything.
+pub(crate) fn semver_matches(req: &str, version: (u8, u8, u8)) -> Result<bool, anyhow::Error> {
+	for comparison in req.split(',').map(str::trim) {
+		if comparison == "*" {
+			continue;
+		}
+
+		let (op, v) = [">=", "<=", ">", "<", "="]
+			.into_iter()
+			.find_map(|op| comparison.strip_prefix(op).map(|v| (op, v.trim())))
+			.unwrap_or(("=", comparison));
+
+		let mut parts = v.split('.');
+		let mut part = || -> Result<u8, anyhow::Error> {
+			match parts.next() {
+				Some(p) => p.parse().map_err(|_| anyhow::anyhow!("invalid semantic version requirement: {req:?}")),
+				None    => Ok(0),
+			}
+		};
+		let v = (part()?, part()?, part()?);
+		if parts.next().is_some() {
+			anyhow::bail!("invalid semantic version requirement: {req:?}");
+		}
+
+		let matches = match op {
+			">=" => version >= v,
+			"<=" => version <= v,
+			">"  => version > v,
+			"<"  => version < v,
+			_    => version == v,
+		};
+		if !matches {
+			return Ok(false);
+		}
+	}
+
+	Ok(true)
+}
+
 //---------------------------------------------------------------------------------------------------- Header impl.
 macro_rules! impl_header {
 	() => {
@@ -42,6 +232,10 @@ macro_rules! impl_header {
 		const HEADER: [u8; 24];
 		/// What the version byte will be (0-255).
 		const VERSION: u8;
+		/// The `(major, minor, patch)` version saved after [`Self::VERSION`], if any.
+		///
+		/// This is `Some` if your type implements [`crate::Semver`].
+		const SEMVER: Option<(u8, u8, u8)> = None;
 
 		#[inline(always)]
 		/// Read the associated file and attempt to convert the first 24 bytes to a [`String`].
@@ -52,6 +246,36 @@ macro_rules! impl_header {
 			Ok(String::from_utf8(bytes)?)
 		}
 
+		#[inline]
+		/// Read the first 24 bytes of the associated file, as-is.
+		///
+		/// Unlike [`Self::file_version()`], this does not check them against [`Self::HEADER`].
+		///
+		/// ## Note
+		/// This only works on a non-compressed file.
+		fn file_header() -> Result<[u8; 24], anyhow::Error> {
+			use std::io::Read;
+
+			let mut bytes = [0; 24];
+			std::fs::File::open(Self::absolute_path()?)?.read_exact(&mut bytes)?;
+			Ok(bytes)
+		}
+
+		#[inline]
+		/// Read the first 25 bytes (header + version) of the associated file, as-is.
+		///
+		/// This can be compared against [`Self::full_header()`].
+		///
+		/// ## Note
+		/// This only works on a non-compressed file.
+		fn file_full_header() -> Result<[u8; 25], anyhow::Error> {
+			use std::io::Read;
+
+			let mut bytes = [0; 25];
+			std::fs::File::open(Self::absolute_path()?)?.read_exact(&mut bytes)?;
+			Ok(bytes)
+		}
+
 		#[inline]
 		/// Return the 25 bytes header bytes.
 		///
@@ -88,6 +312,57 @@ macro_rules! impl_header {
 			]
 		}
 
+		#[inline]
+		/// The length of the header prefixed to your file, `25`, or `28` with [`Self::SEMVER`].
+		fn header_len() -> usize {
+			match Self::SEMVER {
+				Some(_) => 28,
+				None    => 25,
+			}
+		}
+
+		#[inline]
+		/// Return the header bytes prefixed to your file.
+		///
+		/// This is [`Self::full_header()`], followed by [`Self::SEMVER`] (if any).
+		fn header_bytes() -> Vec<u8> {
+			let mut bytes = Self::full_header().to_vec();
+			if let Some((major, minor, patch)) = Self::SEMVER {
+				bytes.extend_from_slice(&[major, minor, patch]);
+			}
+			bytes
+		}
+
+		/// Read and check the header of `reader`, then return it positioned at the payload.
+		///
+		/// The header is checked the same way as [`Self::from_bytes()`] ([`Self::HEADER`], [`Self::VERSION`] and [`Self::SEMVER`]).
+		///
+		/// This is for streaming files (or bytes) of this type from other sources (network, archives, etc):
+		/// ```rust,ignore
+		/// let mut payload = State::header_stripping_reader(socket)?;
+		/// std::io::copy(&mut payload, &mut sink)?;
+		/// ```
+		fn header_stripping_reader<R: std::io::Read>(mut reader: R) -> Result<R, anyhow::Error> {
+			let mut bytes = [0_u8; 28];
+			let bytes = &mut bytes[..Self::header_len()];
+			reader.read_exact(bytes)?;
+			ensure_header!(bytes);
+			Ok(reader)
+		}
+
+		/// Write the header of this type ([`Self::header_bytes()`]) to `writer`, then return it for the payload.
+		///
+		/// This is for streaming files of this type to other sinks (network, archives, etc),
+		/// the output can be read with [`Self::from_reader()`] or [`Self::header_stripping_reader()`]:
+		/// ```rust,ignore
+		/// let mut writer = State::header_prepending_writer(socket)?;
+		/// std::io::copy(&mut payload, &mut writer)?;
+		/// ```
+		fn header_prepending_writer<W: std::io::Write>(mut writer: W) -> Result<W, anyhow::Error> {
+			writer.write_all(&Self::header_bytes())?;
+			Ok(writer)
+		}
+
 		#[inline]
 		/// Reads the first 24 bytes of the associated file and matches it against [`Self::HEADER`].
 		///
@@ -107,7 +382,10 @@ macro_rules! impl_header {
 			if bytes[0..24] == Self::HEADER {
 				Ok(bytes[24])
 			} else {
-				bail!("header bytes failed to match.\nexpected: {:?}\nfound: {:?}", Self::HEADER, &bytes[0..24]);
+				bail!(
+					"header bytes failed to match{}\nexpected: {:?}\nfound: {:?}",
+					crate::header::mismatch_hint(&bytes, &Self::full_header()), Self::HEADER, &bytes[0..24],
+				);
 			}
 		}
 
@@ -168,36 +446,186 @@ macro_rules! impl_header {
 		///
 		/// The output data is always `Self`, so the `fn()` constructors you
 		/// input are responsible for converting between the various types.
+		///
+		/// If a matching constructor fails, the next one matching the same version is attempted,
+		/// see [`Self::from_versions_with()`] to change this.
+		///
+		/// ## Errors
+		/// If the file's version could not be read, that error is returned.
+		///
+		/// If every constructor failed (or none matched), a [`crate::VersionsFailed`] with each error is returned.
 		fn from_versions(
 			versions_and_constructors: &'static [(u8, fn() -> Result<Self, anyhow::Error>)],
+		) -> Result<(u8, Self), anyhow::Error> {
+			Self::from_versions_with(versions_and_constructors, crate::VersionFallback::Matching)
+		}
+
+		/// Same as [`Self::from_versions()`], but `fallback` selects which constructors are attempted.
+		fn from_versions_with(
+			versions_and_constructors: &'static [(u8, fn() -> Result<Self, anyhow::Error>)],
+			fallback: crate::VersionFallback,
 		) -> Result<(u8, Self), anyhow::Error> {
 			// Get on-disk version.
 			let file = Self::file_version()?;
 
-//			// If target version, attempt deserialization and return.
-//			if file == Self::VERSION {
-//				return match constructor() {
-//					Ok(data) => Ok((file, data)),
-//					Err(e)   => Err(e),
-//				};
-//			}
-
-			// Else, attempt the other version constructors.
-			for (version, constructor) in versions_and_constructors {
-				// If not the matching version, continue.
-				if file != *version {
-					continue;
-				}
-
-				// If version matches, attempt to construct.
-				return match constructor() {
-					Ok(data) => Ok((*version, data)),
-					Err(e)   => Err(e),
-				};
+			let candidates: Vec<_> = versions_and_constructors
+				.iter()
+				.map(|(version, constructor)| (version.to_string(), *version == file, *constructor))
+				.collect();
+
+			let (i, data) = crate::versions::attempt(file.to_string(), &candidates, fallback)?;
+			Ok((versions_and_constructors[i].0, data))
+		}
+
+		#[inline]
+		/// Reads the first 28 bytes of the associated file, matches the first 24 against [`Self::HEADER`]
+		/// and returns the semantic version after the version byte.
+		///
+		/// This is only meaningful for files saved by a type implementing [`crate::Semver`].
+		///
+		/// ## Note
+		/// This only works on a non-compressed file.
+		fn file_version_semver() -> Result<(u8, u8, u8), anyhow::Error> {
+			use std::io::Read;
+
+			let mut bytes = [0; 28];
+			std::fs::File::open(Self::absolute_path()?)?.read_exact(&mut bytes)?;
+
+			if bytes[0..24] == Self::HEADER {
+				Ok((bytes[25], bytes[26], bytes[27]))
+			} else {
+				bail!(
+					"header bytes failed to match{}\nexpected: {:?}\nfound: {:?}",
+					crate::header::mismatch_hint(&bytes, &Self::full_header()), Self::HEADER, &bytes[0..24],
+				);
+			}
+		}
+
+		#[inline]
+		/// Same as [`Self::from_versions()`], but matches the file's [`Self::file_version_semver()`]
+		/// against semantic version requirements, e.g:
+		/// ```rust,ignore
+		/// let (version, data) = Data::from_versions_semver(&[
+		///     (">=1.2, <2.0", Data::from_file),
+		///     (">=1.0, <1.2", Data1::to_data),
+		///     ("0.9.1",       Data0::to_data),
+		/// ]).unwrap();
+		/// ```
+		///
+		/// A requirement is a comma separated list of comparisons that must all match,
+		/// `>=`, `>`, `<=`, `<` or `=` followed by a version, missing parts are `0`.
+		/// A version without an operator must match exactly, `*` matches everything.
+		///
+		/// Matching constructors are attempted in order until one succeeds, and the file's version is returned with it.
+		///
+		/// ## Errors
+		/// Any invalid requirement errors.
+		///
+		/// If every matching constructor failed (or none matched), a [`crate::VersionsFailed`] with each error is returned.
+		fn from_versions_semver(
+			requirements_and_constructors: &'static [(&'static str, fn() -> Result<Self, anyhow::Error>)],
+		) -> Result<((u8, u8, u8), Self), anyhow::Error> {
+			// Get on-disk version.
+			let file = Self::file_version_semver()?;
+
+			let mut candidates = Vec::with_capacity(requirements_and_constructors.len());
+			for (requirement, constructor) in requirements_and_constructors {
+				let matches = crate::header::semver_matches(requirement, file)?;
+				candidates.push((requirement.to_string(), matches, *constructor));
 			}
 
-			// Return error if nothing worked.
-			Err(anyhow!("all versions failed to match: {versions_and_constructors:#?}"))
+			let (major, minor, patch) = file;
+			let (_, data) = crate::versions::attempt(format!("{major}.{minor}.{patch}"), &candidates, crate::VersionFallback::Matching)?;
+			Ok((file, data))
+		}
+
+		#[cfg(feature = "shmem")]
+		/// Returns the PATH of the shared memory region, see [`Self::save_shmem()`].
+		///
+		/// | Platform                     | PATH                                                                      |
+		/// |------------------------------|---------------------------------------------------------------------------|
+		/// | Linux                        | `/dev/shm/disk-<hash>`, the POSIX shared memory object `/disk-<hash>`     |
+		/// | Others, or without `/dev/shm` | [`crate::Dir::Runtime`]/`PROJECT_DIRECTORY`/`SUB_DIRECTORIES`/`FILE_NAME` |
+		///
+		/// `<hash>` is derived from [`Self::absolute_path()`], so it is unique per file and user.
+		fn absolute_path_shmem() -> Result<std::path::PathBuf, anyhow::Error> {
+			crate::shmem::shmem_path(Self::PROJECT_DIRECTORY, Self::SUB_DIRECTORIES, Self::FILE_NAME, &Self::absolute_path()?)
+		}
+
+		#[cfg(feature = "shmem")]
+		/// Save to shared memory instead of disk.
+		///
+		/// This is meant for hot state shared between processes of the same app,
+		/// it does not survive a reboot, see [`Self::absolute_path_shmem()`] for where it is saved.
+		///
+		/// The bytes are the same as [`Self::save()`], including [`Self::HEADER`] and [`Self::VERSION`],
+		/// so [`Self::from_shmem()`] has the same checks as [`Self::from_file()`].
+		///
+		/// The region is written to a temporary region first then renamed, so readers
+		/// never see a partially written region, and it is only readable by the current user.
+		///
+		/// This will return the amount of `bytes` saved and the [`std::path::PathBuf`] on success.
+		fn save_shmem(&self) -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			self.__validate()?;
+			let now   = std::time::Instant::now();
+			let bytes = self.to_bytes()?;
+			let serialize_time = now.elapsed();
+
+			let path = Self::absolute_path_shmem()?;
+			crate::common::ensure_write_once!(path);
+			crate::shmem::save(&path, &bytes)?;
+			Ok(crate::Metadata::new(bytes.len() as u64, path).with_serialize_time(serialize_time))
+		}
+
+		#[cfg(feature = "shmem")]
+		/// Read the shared memory region saved with [`Self::save_shmem()`].
+		///
+		/// On Unix, this errors if the region is not owned by the current user.
+		fn from_shmem() -> Result<Self, anyhow::Error> {
+			use std::io::Read;
+			crate::common::traced!("from_shmem", "shmem", load, {
+				let mut bytes = vec![];
+				crate::shmem::open(&Self::absolute_path_shmem()?)?.read_to_end(&mut bytes)?;
+				let s = Self::from_bytes(&bytes)?;
+				s.__validate()?;
+				Ok(s)
+			})
+		}
+
+		#[cfg(feature = "shmem")]
+		/// Same as [`Self::from_shmem()`] but with [`memmap2`](https://docs.rs/memmap2).
+		///
+		/// ## Safety
+		/// You _must_ understand all the invariants that `memmap` comes with.
+		///
+		/// More details [here](https://docs.rs/memmap2/latest/memmap2/struct.Mmap.html).
+		unsafe fn from_shmem_memmap() -> Result<Self, anyhow::Error> {
+			crate::common::traced!("from_shmem_memmap", "shmem", load, {
+				let file = crate::shmem::open(&Self::absolute_path_shmem()?)?;
+				let mmap = unsafe { memmap2::Mmap::map(&file)? };
+				let s = Self::from_bytes(&*mmap)?;
+				s.__validate()?;
+				Ok(s)
+			})
+		}
+
+		#[cfg(feature = "shmem")]
+		/// Remove the shared memory region saved with [`Self::save_shmem()`].
+		///
+		/// This will return success if the region doesn't exist or if removed.
+		///
+		/// On success, this returns:
+		/// - The amount of bytes removed
+		/// - The [`std::path::PathBuf`] that was removed
+		fn rm_shmem() -> Result<crate::Metadata, anyhow::Error> {
+			crate::common::assert_writable!();
+			let path = Self::absolute_path_shmem()?;
+			match std::fs::metadata(&path) {
+				Ok(m) => { std::fs::remove_file(&path)?; Ok(crate::Metadata::new(m.len(), path)) },
+				Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(crate::Metadata::zero(path)),
+				Err(e) => Err(e.into()),
+			}
 		}
 	}
 }
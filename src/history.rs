@@ -0,0 +1,191 @@
+//---------------------------------------------------------------------------------------------------- Use
+use std::path::{Path,PathBuf};
+use std::time::{Duration,SystemTime,UNIX_EPOCH};
+
+//---------------------------------------------------------------------------------------------------- History
+// Historical copies of a file (snapshots, rotated saves) live next to it
+// with a UTC timestamp between the file name and extension:
+//
+// state.toml                       // <- Real file
+// state-2024-06-01T12-00-00.toml   // <- Historical version
+// state-2024-06-01T12-00-00-1.toml // <- Historical version, same second
+//
+// `:` is avoided since it is invalid on Windows.
+
+// Format `time` as `YYYY-MM-DDTHH-MM-SS` (UTC).
+//
+// Times before the UNIX epoch are clamped to it.
+pub(crate) fn timestamp(time: SystemTime) -> String {
+	let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+	let days = (secs / 86400) as i64;
+	let secs = secs % 86400;
+
+	let (y, m, d) = civil_from_days(days);
+	format!("{y:04}-{m:02}-{d:02}T{:02}-{:02}-{:02}", secs / 3600, secs % 3600 / 60, secs % 60)
+}
+
+// Parse a `timestamp()` back into a `SystemTime`.
+pub(crate) fn parse_timestamp(s: &str) -> Option<SystemTime> {
+	let b = s.as_bytes();
+	if b.len() != 19 || b[4] != b'-' || b[7] != b'-' || b[10] != b'T' || b[13] != b'-' || b[16] != b'-' {
+		return None;
+	}
+
+	let num = |range: std::ops::Range<usize>| -> Option<u64> {
+		let s = &s[range];
+		match s.bytes().all(|b| b.is_ascii_digit()) {
+			true  => s.parse().ok(),
+			false => None,
+		}
+	};
+
+	let (y, m, d) = (num(0..4)?, num(5..7)?, num(8..10)?);
+	let (h, min, sec) = (num(11..13)?, num(14..16)?, num(17..19)?);
+	if !(1..=12).contains(&m) || !(1..=31).contains(&d) || h > 23 || min > 59 || sec > 59 {
+		return None;
+	}
+
+	let days = u64::try_from(days_from_civil(y as i64, m, d)).ok()?;
+	Some(UNIX_EPOCH + Duration::from_secs(days * 86400 + h * 3600 + min * 60 + sec))
+}
+
+// The historical file name of `file` + `ext` at `time`, `n` disambiguates the same second.
+pub(crate) fn history_name(file: &str, ext: &str, time: SystemTime, n: usize) -> String {
+	let ts = timestamp(time);
+	match (n, ext) {
+		(0, "") => format!("{file}-{ts}"),
+		(0, _)  => format!("{file}-{ts}.{ext}"),
+		(n, "") => format!("{file}-{ts}-{n}"),
+		(n, _)  => format!("{file}-{ts}-{n}.{ext}"),
+	}
+}
+
+// The first historical PATH at `time` in `dir` that does not exist yet.
+pub(crate) fn free_history_path(dir: &Path, file: &str, ext: &str, time: SystemTime) -> PathBuf {
+	let mut n = 0;
+	loop {
+		let path = dir.join(history_name(file, ext, time, n));
+		if !path.exists() {
+			return path;
+		}
+		n += 1;
+	}
+}
+
+// If `name` is a historical version of `file` + `ext`, return its time and same second `n`.
+pub(crate) fn parse_history_name(name: &str, file: &str, ext: &str) -> Option<(SystemTime, usize)> {
+	let rest = name.strip_prefix(file)?.strip_prefix('-')?;
+	let rest = match ext {
+		"" => rest,
+		_  => rest.strip_suffix(ext)?.strip_suffix('.')?,
+	};
+
+	// Split off the same second `-N` suffix.
+	let n = match rest.len() {
+		19 => 0,
+		l if l > 20 && rest.as_bytes()[19] == b'-' && rest[20..].bytes().all(|b| b.is_ascii_digit()) => rest[20..].parse().ok()?,
+		_ => return None,
+	};
+
+	Some((parse_timestamp(&rest[..19])?, n))
+}
+
+// All historical versions of `file` + `ext` in `dir`, oldest first.
+pub(crate) fn history(dir: &Path, file: &str, ext: &str) -> Result<Vec<(SystemTime, PathBuf, u64)>, anyhow::Error> {
+	let read_dir = match std::fs::read_dir(dir) {
+		Ok(r) => r,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+		Err(e) => return Err(e.into()),
+	};
+
+	let mut vec = vec![];
+	for entry in read_dir {
+		let entry = entry?;
+		let name  = entry.file_name();
+		let Some(name) = name.to_str() else { continue };
+		let Some((time, n)) = parse_history_name(name, file, ext) else { continue };
+		let metadata = entry.metadata()?;
+		if metadata.is_file() {
+			vec.push((time, n, entry.path(), metadata.len()));
+		}
+	}
+
+	vec.sort();
+	Ok(vec.into_iter().map(|(time, _, path, len)| (time, path, len)).collect())
+}
+
+// Copy `from` to `to` as a copy-on-write clone if the filesystem
+// supports it (`FICLONE` on Linux, `clonefile()` on macOS), else a normal copy.
+//
+// `to` must not exist.
+//
+// Returns the amount of bytes copied.
+pub(crate) fn reflink_or_copy(from: &Path, to: &Path) -> Result<u64, anyhow::Error> {
+	#[cfg(all(target_os = "linux", any(
+		target_arch = "x86",
+		target_arch = "x86_64",
+		target_arch = "arm",
+		target_arch = "aarch64",
+		target_arch = "riscv64",
+	)))]
+	{
+		use std::os::unix::io::AsRawFd;
+
+		let src = std::fs::File::open(from)?;
+		let dst = std::fs::OpenOptions::new().write(true).create_new(true).open(to)?;
+
+		// SAFETY: both file descriptors are valid for the duration of the call.
+		if unsafe { libc::ioctl(dst.as_raw_fd(), libc::FICLONE as _, src.as_raw_fd()) } == 0 {
+			return Ok(src.metadata()?.len());
+		}
+
+		// Not supported, fallback to a normal copy.
+		drop(dst);
+		std::fs::remove_file(to)?;
+	}
+
+	#[cfg(target_os = "macos")]
+	{
+		use std::os::unix::ffi::OsStrExt;
+
+		let src = std::ffi::CString::new(from.as_os_str().as_bytes())?;
+		let dst = std::ffi::CString::new(to.as_os_str().as_bytes())?;
+
+		// SAFETY: both are valid NUL terminated strings.
+		if unsafe { libc::clonefile(src.as_ptr(), dst.as_ptr(), 0) } == 0 {
+			return Ok(std::fs::metadata(to)?.len());
+		}
+	}
+
+	Ok(std::fs::copy(from, to)?)
+}
+
+//---------------------------------------------------------------------------------------------------- Private
+// Days since the UNIX epoch -> (year, month, day).
+//
+// <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+pub(crate) const fn civil_from_days(days: i64) -> (i64, u64, u64) {
+	let z   = days + 719468;
+	let era = z.div_euclid(146097);
+	let doe = z.rem_euclid(146097) as u64;
+	let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp  = (5 * doy + 2) / 153;
+	let d   = doy - (153 * mp + 2) / 5 + 1;
+	let m   = if mp < 10 { mp + 3 } else { mp - 9 };
+	let y   = yoe as i64 + era * 400 + (m <= 2) as i64;
+	(y, m, d)
+}
+
+// (year, month, day) -> days since the UNIX epoch.
+//
+// <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+const fn days_from_civil(y: i64, m: u64, d: u64) -> i64 {
+	let y   = if m <= 2 { y - 1 } else { y };
+	let era = y.div_euclid(400);
+	let yoe = y.rem_euclid(400) as u64;
+	let mp  = if m > 2 { m - 3 } else { m + 9 };
+	let doy = (153 * mp + 2) / 5 + d - 1;
+	let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+	era * 146097 + doe as i64 - 719468
+}
@@ -0,0 +1,247 @@
+//---------------------------------------------------------------------------------------------------- Use
+use anyhow::bail;
+use serde::{Serialize,de::DeserializeOwned};
+use std::io::{Read,Seek,SeekFrom,Write};
+use std::path::{Path,PathBuf};
+use std::time::UNIX_EPOCH;
+use crate::{record,Format,Metadata};
+
+//---------------------------------------------------------------------------------------------------- Journal
+/// Write-ahead journaling for frequently updated data.
+///
+/// Rewriting a large file for every small change is expensive, implementing
+/// this trait allows saving only the change (a [`Journal::Record`]) instead.
+///
+/// If your type implements this trait _and_ one of the `disk` traits via macro:
+/// - `save_journal(&record)` appends the record to a journal file next to the file, e.g. `state.bin.wal`
+/// - `from_file()` replays any pending records on top of the file with [`Journal::apply()`]
+/// - `checkpoint()` folds the journal into the file atomically and removes the journal
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::json!(Counter, Dir::Data, "disk_test", "journal", "counter");
+/// #[derive(Serialize,Deserialize)]
+/// struct Counter {
+///     count: u64,
+/// }
+///
+/// impl disk::Journal for Counter {
+///     type Record = u64;
+///     const FORMAT: Format = Format::Json;
+///
+///     fn apply(&mut self, record: u64) -> Result<(), disk::Error> {
+///         self.count += record;
+///         Ok(())
+///     }
+/// }
+///
+/// Counter { count: 0 }.save().unwrap();
+///
+/// // Only the records are written.
+/// Counter::save_journal(&1).unwrap();
+/// Counter::save_journal(&2).unwrap();
+/// assert_eq!(Counter::from_file().unwrap().count, 3);
+///
+/// // Fold the journal into the file.
+/// Counter::checkpoint().unwrap();
+/// assert!(!Counter::absolute_path_journal().unwrap().exists());
+/// assert_eq!(Counter::from_file().unwrap().count, 3);
+///
+/// // Saving the whole file removes the journal.
+/// Counter::save_journal(&1).unwrap();
+/// Counter { count: 10 }.save().unwrap();
+/// assert!(!Counter::absolute_path_journal().unwrap().exists());
+/// assert_eq!(Counter::from_file().unwrap().count, 10);
+/// # Counter::rm().unwrap();
+/// ```
+///
+/// ## Consistency
+/// Saving or removing the file through `disk` (`save()`, `rm()`, etc) removes its journal.
+///
+/// The journal also records the file it was started on (its size and modification time).
+/// If the file is replaced in any other way (e.g. by another program), the journal
+/// no longer matches and is ignored, and `save_journal()` starts a new one.
+///
+/// Each record is checksummed and `fsync()`'ed, a record torn by a crash is ignored.
+///
+/// Manual `unsafe impl`'s do not replay the journal automatically.
+pub trait Journal: Sized {
+	/// A single change to [`Self`].
+	type Record: Serialize + DeserializeOwned;
+
+	/// The [`Format`] records are written in.
+	const FORMAT: Format;
+
+	/// Apply a `record` on top of [`Self`].
+	///
+	/// This is called in the same order the records were saved.
+	fn apply(&mut self, record: Self::Record) -> Result<(), anyhow::Error>;
+}
+
+//---------------------------------------------------------------------------------------------------- Private
+// The journal file layout:
+//
+// | `MAGIC` | base size (u64) | base modified secs (u64) | base modified nanos (u32) |
+// | record | record | ...
+//
+// Records are framed with `crate::record`.
+//
+// All integers are little endian.
+const MAGIC: [u8; 8] = *b"diskwal\0";
+const HEADER_LEN: u64 = 28;
+
+// The journal PATH for the file at `path`.
+pub(crate) fn journal_path(path: &Path) -> PathBuf {
+	let mut wal = path.to_path_buf().into_os_string();
+	wal.push(".wal");
+	PathBuf::from(wal)
+}
+
+// The header identifying the current state of the file at `path`.
+fn header(path: &Path) -> Result<[u8; HEADER_LEN as usize], anyhow::Error> {
+	let metadata = match std::fs::metadata(path) {
+		Ok(m) => m,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => bail!("{:?} does not exist, it must be saved before journaling", path),
+		Err(e) => return Err(e.into()),
+	};
+	let modified = metadata.modified()?.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+	let mut header = [0; HEADER_LEN as usize];
+	header[..8].copy_from_slice(&MAGIC);
+	header[8..16].copy_from_slice(&metadata.len().to_le_bytes());
+	header[16..24].copy_from_slice(&modified.as_secs().to_le_bytes());
+	header[24..28].copy_from_slice(&modified.subsec_nanos().to_le_bytes());
+	Ok(header)
+}
+
+// Append a record to the journal of the file at `path`.
+//
+// A missing, stale or torn journal is (re)started first.
+//
+// Returns the journal size in bytes.
+pub(crate) fn append<T: Journal>(path: &Path, record: &T::Record) -> Result<u64, anyhow::Error> {
+	let bytes  = T::FORMAT.to_bytes(record)?;
+	let header = header(path)?;
+	let wal    = journal_path(path);
+
+	let mut file = std::fs::OpenOptions::new()
+		.read(true)
+		.write(true)
+		.create(true)
+		.truncate(false)
+		.open(&wal)?;
+	let len = file.metadata()?.len();
+
+	// Start a new journal if it doesn't match the file.
+	let mut current = [0; HEADER_LEN as usize];
+	let matches = len >= HEADER_LEN && file.read_exact(&mut current).is_ok() && current == header;
+	let offset = if !matches {
+		file.set_len(0)?;
+		file.seek(SeekFrom::Start(0))?;
+		file.write_all(&header)?;
+		HEADER_LEN
+	} else {
+		// Drop the torn record.
+		let offset = record::valid_end(&mut file, HEADER_LEN, len)?;
+		if offset != len {
+			file.set_len(offset)?;
+		}
+		offset
+	};
+
+	let frame = record::encode(&bytes)?;
+	file.seek(SeekFrom::Start(offset))?;
+	file.write_all(&frame)?;
+	file.sync_data()?;
+
+	Ok(offset + frame.len() as u64)
+}
+
+// Apply the pending journal of the file at `path` to `value`.
+//
+// Returns the amount of records applied.
+pub(crate) fn replay<T: Journal>(value: &mut T, path: &Path) -> Result<usize, anyhow::Error> {
+	let bytes = match std::fs::read(journal_path(path)) {
+		Ok(b) => b,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+		Err(e) => return Err(e.into()),
+	};
+
+	// Stale or torn header, nothing to replay.
+	if bytes.len() < HEADER_LEN as usize || bytes[..HEADER_LEN as usize] != header(path)? {
+		return Ok(0);
+	}
+
+	let mut records = 0;
+	let mut rest = &bytes[HEADER_LEN as usize..];
+	while let Some((record, next)) = record::decode(rest) {
+		value.apply(T::FORMAT.from_bytes(record)?)?;
+		rest = next;
+		records += 1;
+	}
+
+	#[cfg(feature = "tracing")]
+	tracing::debug!("replayed {records} journal record(s) for {path:?}");
+
+	Ok(records)
+}
+
+// Remove the journal of the file at `path`, if it exists.
+//
+// Returns the amount of bytes removed.
+pub(crate) fn remove(path: &Path) -> Result<u64, anyhow::Error> {
+	let wal = journal_path(path);
+	match std::fs::metadata(&wal) {
+		Ok(m) => { std::fs::remove_file(&wal)?; Ok(m.len()) },
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+		Err(e) => Err(e.into()),
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Internals
+// Autoref specialization so that the macros can replay
+// the journal only if the type implements `Journal`,
+// see `crate::validate` for how this works.
+#[doc(hidden)]
+pub struct __JournalProbe<T>(pub std::marker::PhantomData<T>);
+
+#[doc(hidden)]
+pub trait __JournalYes<T> {
+	fn __disk_journal(&self, value: &mut T, path: fn() -> Result<PathBuf, anyhow::Error>) -> Result<(), anyhow::Error>;
+	fn __disk_journal_reset(&self, metadata: &Metadata, path: fn() -> Result<PathBuf, anyhow::Error>) -> Result<(), anyhow::Error>;
+}
+
+impl<T: Journal> __JournalYes<T> for __JournalProbe<T> {
+	#[inline(always)]
+	fn __disk_journal(&self, value: &mut T, path: fn() -> Result<PathBuf, anyhow::Error>) -> Result<(), anyhow::Error> {
+		replay(value, &path()?).map(|_| ())
+	}
+
+	#[inline(always)]
+	fn __disk_journal_reset(&self, metadata: &Metadata, path: fn() -> Result<PathBuf, anyhow::Error>) -> Result<(), anyhow::Error> {
+		let path = path()?;
+		if metadata.path_ref() == path {
+			remove(&path)?;
+		}
+		Ok(())
+	}
+}
+
+#[doc(hidden)]
+pub trait __JournalNo<T> {
+	fn __disk_journal(&self, value: &mut T, path: fn() -> Result<PathBuf, anyhow::Error>) -> Result<(), anyhow::Error>;
+	fn __disk_journal_reset(&self, metadata: &Metadata, path: fn() -> Result<PathBuf, anyhow::Error>) -> Result<(), anyhow::Error>;
+}
+
+impl<T> __JournalNo<T> for &__JournalProbe<T> {
+	#[inline(always)]
+	fn __disk_journal(&self, _: &mut T, _: fn() -> Result<PathBuf, anyhow::Error>) -> Result<(), anyhow::Error> {
+		Ok(())
+	}
+
+	#[inline(always)]
+	fn __disk_journal_reset(&self, _: &Metadata, _: fn() -> Result<PathBuf, anyhow::Error>) -> Result<(), anyhow::Error> {
+		Ok(())
+	}
+}
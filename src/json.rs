@@ -26,6 +26,38 @@ crate::common::impl_macro!(Json, "json");
 /// ```
 /// This is 4 spaces instead of the default 2.
 ///
+/// ## Upgrading
+/// `from_file_upgraded()` fills in missing struct fields from [`Default`],
+/// but the entries of maps are the user's data, removed entries stay removed:
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use std::collections::BTreeMap;
+/// # use disk::*;
+/// disk::json!(Config, Dir::Data, "disk_test", "upgraded", "config");
+/// #[derive(Serialize,Deserialize)]
+/// struct Config {
+///     volume: u8,
+///     profiles: BTreeMap<String, u8>,
+/// }
+///
+/// impl Default for Config {
+///     fn default() -> Self {
+///         let profiles = [("home".into(), 1), ("work".into(), 2)].into();
+///         Self { volume: 50, profiles }
+///     }
+/// }
+///
+/// // Saved by an older version without `volume`, the user removed the `work` profile.
+/// std::fs::create_dir_all(Config::base_path().unwrap()).unwrap();
+/// std::fs::write(Config::absolute_path().unwrap(), r#"{ "profiles": { "home": 1 } }"#).unwrap();
+///
+/// let (config, _) = Config::from_file_upgraded().unwrap();
+/// assert_eq!(config.volume, 50);
+/// assert_eq!(config.profiles.len(), 1);
+/// assert!(!config.profiles.contains_key("work"));
+/// # Config::rm_sub().unwrap();
+/// ```
+///
 /// ## Safety
 /// When manually implementing, you are **promising** that the `PATH`'s manually specified are correct.
 pub unsafe trait Json: serde::Serialize + serde::de::DeserializeOwned {
@@ -50,22 +82,52 @@ pub unsafe trait Json: serde::Serialize + serde::de::DeserializeOwned {
 	#[inline(always)]
 	/// Convert [`Self`] to bytes.
 	fn to_bytes(&self) -> Result<Vec<u8>, anyhow::Error> {
-		let mut vec = Vec::with_capacity(128);
-		let mut ser = Serializer::with_formatter(&mut vec, ENCODING_OPTIONS.clone());
-		self.serialize(&mut ser)?;
-		Ok(vec)
+		Ok(Self::__to_string(self)?.into_bytes())
 	}
 	#[inline(always)]
 	/// Create [`Self`] from bytes.
 	fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
-		Ok(serde_json::de::from_slice(bytes)?)
+		Ok(serde_json::de::from_slice(&common::decode_text(bytes)?)?)
+	}
+
+	#[inline(always)]
+	/// Same as [`Self::from_bytes`] but errors on unknown fields.
+	fn from_bytes_strict(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+		let bytes  = common::decode_text(bytes)?;
+		let mut de = serde_json::Deserializer::from_slice(&bytes);
+		let s = common::deserialize_strict(&mut de)?;
+		de.end()?;
+		Ok(s)
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	/// Internal function. Create [`Self`] from bytes, filling missing fields from [`Default`].
+	///
+	/// Returns `true` if any fields were missing.
+	fn __from_bytes_upgraded(bytes: &[u8]) -> Result<(Self, bool), anyhow::Error>
+	where
+		Self: Default,
+	{
+		let mut value: serde_json::Value = serde_json::from_slice(&common::decode_text(bytes)?)?;
+		let default  = Self::default();
+		let upgraded = common::merge_json(&mut value, serde_json::to_value(&default)?, &crate::shape::Shape::of(&default));
+		Ok((serde_json::from_value(value)?, upgraded))
 	}
 
 	// JSON operations.
 	#[inline(always)]
 	/// This uses [`serde_json::ser::to_string_pretty`];
 	fn to_string(&self) -> Result<String, anyhow::Error> {
-		let vec = self.to_bytes()?;
+		Self::__to_string(self)
+	}
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. Serialize any `value` the same way as [`Self::to_string()`].
+	fn __to_string<V: serde::Serialize>(value: &V) -> Result<String, anyhow::Error> {
+		let mut vec = Vec::with_capacity(128);
+		let mut ser = Serializer::with_formatter(&mut vec, ENCODING_OPTIONS.clone());
+		value.serialize(&mut ser)?;
 		// SAFETY
 		// `serde_json` doesn't emit
 		// invalid UTF-8 if vec is successful.
@@ -74,10 +136,15 @@ pub unsafe trait Json: serde::Serialize + serde::de::DeserializeOwned {
 	#[inline(always)]
 	/// Create [`Self`] from a [`String`].
 	fn from_string(string: &str) -> Result<Self, anyhow::Error> {
-		Ok(serde_json::de::from_str(string)?)
+		Ok(serde_json::de::from_str(common::strip_bom(string))?)
 	}
 
 	// Common functions.
+	common::impl_strict!();
+	common::impl_upgrade!();
+	common::impl_merge!();
+	#[cfg(feature = "schemars")]
+	common::impl_schema!();
 	common::impl_string!("json");
 }
 
@@ -0,0 +1,582 @@
+//---------------------------------------------------------------------------------------------------- Use
+use anyhow::bail;
+use serde::{Serialize,Deserialize,de::DeserializeOwned};
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::path::{Path,PathBuf};
+use std::sync::{Arc,Mutex};
+use crate::{common,Dir,Format,Metadata};
+
+//---------------------------------------------------------------------------------------------------- Kv
+/// A simple key-value store, with each value saved as its own file.
+///
+/// Values are stored under the project's OS directory, like the other `disk` types:
+/// ```text,ignore
+/// ~/.local/share/MyProject/users/                   // <- `Kv::open(Dir::Data, "MyProject", "users", ...)`
+/// ~/.local/share/MyProject/users/47/                // <- Shard directory
+/// ~/.local/share/MyProject/users/47/616c696365.json // <- The value of key `alice`
+/// ```
+///
+/// Keys are hex encoded into file names, so any string is a valid key (up to `120` bytes),
+/// and they are spread over up to `256` shard directories to keep directories small.
+///
+/// Keys that only differ in case (`Profile-A` and `profile-a`) are different file names,
+/// so they never collide on case-insensitive filesystems, see [`crate::DiskConfig::case_collisions`].
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// #[derive(Debug,PartialEq,Serialize,Deserialize)]
+/// struct User {
+///     age: u8,
+/// }
+///
+/// let kv = Kv::<User>::open(Dir::Data, "disk_test", "kv", Format::Json).unwrap();
+///
+/// kv.set("alice", &User { age: 30 }).unwrap();
+/// kv.set("bob/../..", &User { age: 40 }).unwrap();
+/// assert_eq!(kv.get("alice").unwrap(), Some(User { age: 30 }));
+/// assert_eq!(kv.get("carol").unwrap(), None);
+///
+/// let mut keys = kv.keys().unwrap();
+/// keys.sort();
+/// assert_eq!(keys, ["alice", "bob/../.."]);
+///
+/// kv.remove("alice").unwrap();
+/// assert_eq!(kv.get("alice").unwrap(), None);
+/// # kv.clear().unwrap();
+/// ```
+///
+/// ## Manifest
+/// [`Kv::with_manifest()`] enables a manifest file, which tracks the size,
+/// checksum and version of every value, see [`Kv::verify()`].
+pub struct Kv<V> {
+	root: PathBuf,
+	format: Format,
+	manifest: Option<Arc<Mutex<BTreeMap<String, ManifestEntry>>>>,
+	_value: PhantomData<fn() -> V>,
+}
+
+// Keys longer than this would make file names too long once hex encoded.
+const MAX_KEY_LEN: usize = 120;
+
+impl<V> Kv<V> {
+	/// Open the store in `dir`/`project_directory`/`sub_directories`, creating the directories if needed.
+	///
+	/// ## Errors
+	/// This errors if the `project_directory` or `sub_directories`
+	/// are invalid (see [`crate::validate_path_components()`]) or the directories could not be created.
+	pub fn open(
+		dir: Dir,
+		project_directory: &str,
+		sub_directories: &str,
+		format: Format,
+	) -> Result<Self, anyhow::Error> {
+		// The file name is not used, only the directories are validated.
+		if let Err(e) = crate::validate_path_components(project_directory, sub_directories, "kv") {
+			bail!(e);
+		}
+
+		let mut root = common::get_projectdir(&dir, project_directory)?;
+		sub_directories
+			.split_terminator(&['/', '\\'][..])
+			.filter(|dir| !dir.is_empty())
+			.for_each(|dir| root.push(dir));
+		common::assert_safe_path(&root)?;
+		std::fs::create_dir_all(&root)?;
+
+		Ok(Self { root, format, manifest: None, _value: PhantomData })
+	}
+
+	/// Enable the manifest.
+	///
+	/// The manifest is a file in the store's directory (`manifest` + the [`Format`]'s extension)
+	/// recording the [`ManifestEntry`] of every value, it is updated on every [`Kv::set()`] and [`Kv::remove()`].
+	///
+	/// This allows [`Kv::verify()`] to find missing or corrupted values at
+	/// startup, without opening every file.
+	///
+	/// If the manifest doesn't exist yet, it is built from the existing values.
+	///
+	/// ## Errors
+	/// The manifest is saved with the store's [`Format`],
+	/// so it must support maps (every format except [`Format::Plain`]).
+	pub fn with_manifest(mut self) -> Result<Self, anyhow::Error> {
+		let path = self.manifest_path();
+		let manifest = match std::fs::read(&path) {
+			Ok(bytes) => self.format.from_bytes(&bytes)?,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+				let manifest = self.scan()?;
+				self.save_manifest(&manifest)?;
+				manifest
+			},
+			Err(e) => return Err(e.into()),
+		};
+
+		self.manifest = Some(Arc::new(Mutex::new(manifest)));
+		Ok(self)
+	}
+
+	/// Returns a copy of the manifest, or [`None`] if it is not enabled.
+	pub fn manifest(&self) -> Option<BTreeMap<String, ManifestEntry>> {
+		self.manifest.as_ref().map(|m| lock(m).clone())
+	}
+
+	/// Rebuild the manifest from the values on disk.
+	///
+	/// Values changed outside of this [`Kv`] are re-recorded with their version reset to `1`.
+	///
+	/// This does nothing if the manifest is not enabled.
+	pub fn rebuild_manifest(&self) -> Result<(), anyhow::Error> {
+		let Some(manifest) = &self.manifest else { return Ok(()) };
+
+		let mut manifest = lock(manifest);
+		let mut scan = self.scan()?;
+		for (key, entry) in &mut scan {
+			if let Some(old) = manifest.get(key) {
+				if old.size == entry.size && old.crc32 == entry.crc32 {
+					entry.version = old.version;
+				}
+			}
+		}
+
+		self.save_manifest(&scan)?;
+		*manifest = scan;
+		Ok(())
+	}
+
+	/// Compare the values on disk against the manifest.
+	///
+	/// By default, only the file sizes are compared (no files are opened).
+	/// If `checksums` is `true`, every value is also read and checksummed.
+	///
+	/// ## Errors
+	/// This errors if the manifest is not enabled, see [`Kv::with_manifest()`].
+	pub fn verify(&self, checksums: bool) -> Result<KvReport, anyhow::Error> {
+		let Some(manifest) = &self.manifest else {
+			bail!("disk: Kv manifest is not enabled");
+		};
+		let manifest = lock(manifest);
+
+		let mut report = KvReport::default();
+		for (key, entry) in manifest.iter() {
+			let path = self.path(key)?;
+			let size = match std::fs::metadata(&path) {
+				Ok(m) => m.len(),
+				Err(e) if e.kind() == std::io::ErrorKind::NotFound => { report.missing.push(key.clone()); continue; },
+				Err(e) => return Err(e.into()),
+			};
+
+			if size != entry.size || (checksums && common::crc32(&std::fs::read(&path)?) != entry.crc32) {
+				report.corrupted.push(key.clone());
+			} else {
+				report.ok += 1;
+			}
+		}
+
+		for key in self.keys()? {
+			if !manifest.contains_key(&key) {
+				report.untracked.push(key);
+			}
+		}
+		report.untracked.sort();
+
+		Ok(report)
+	}
+
+	#[inline]
+	/// The directory the store is in.
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	#[inline]
+	/// The [`Format`] values are saved in.
+	pub const fn format(&self) -> Format {
+		self.format
+	}
+
+	/// Returns the PATH the value of `key` is (or would be) saved at.
+	///
+	/// ## Errors
+	/// This errors if `key` is empty or longer than `120` bytes.
+	pub fn path(&self, key: &str) -> Result<PathBuf, anyhow::Error> {
+		if key.is_empty() {
+			bail!("disk: Kv key must not be empty");
+		}
+		if key.len() > MAX_KEY_LEN {
+			bail!("disk: Kv key must be {MAX_KEY_LEN} bytes or less, found: {}", key.len());
+		}
+
+		let mut name = String::with_capacity(key.len() * 2 + 1 + self.format.file_ext().len());
+		for b in key.bytes() {
+			name.push_str(&format!("{b:02x}"));
+		}
+		if !self.format.file_ext().is_empty() {
+			name.push('.');
+			name.push_str(self.format.file_ext());
+		}
+
+		let mut crc = flate2::Crc::new();
+		crc.update(key.as_bytes());
+
+		let mut path = self.root.join(format!("{:02x}", crc.sum() & 0xff));
+		path.push(name);
+		Ok(path)
+	}
+
+	/// Check if `key` has a value.
+	pub fn contains_key(&self, key: &str) -> Result<bool, anyhow::Error> {
+		Ok(self.path(key)?.try_exists()?)
+	}
+
+	/// Remove the value of `key`.
+	///
+	/// This will return success if the value doesn't exist or if removed.
+	///
+	/// On success, this returns:
+	/// - The amount of bytes removed
+	/// - The [`PathBuf`] that was removed
+	pub fn remove(&self, key: &str) -> Result<Metadata, anyhow::Error> {
+		let path = self.path(key)?;
+
+		match std::fs::metadata(&path) {
+			Ok(m) => {
+				let manifest = self.manifest.as_ref().map(|m| lock(m));
+				std::fs::remove_file(&path)?;
+				if let Some(mut manifest) = manifest {
+					manifest.remove(key);
+					self.save_manifest(&manifest)?;
+				}
+				Ok(Metadata::new(m.len(), path))
+			},
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Metadata::zero(path)),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	/// Returns all keys in the store, in no particular order.
+	pub fn keys(&self) -> Result<Vec<String>, anyhow::Error> {
+		let ext = self.format.file_ext();
+		let mut keys = vec![];
+
+		for shard in std::fs::read_dir(&self.root)? {
+			let shard = shard?;
+			if !shard.file_type()?.is_dir() {
+				continue;
+			}
+
+			for entry in std::fs::read_dir(shard.path())? {
+				let name = entry?.file_name();
+				let Some(name) = name.to_str() else { continue };
+				let hex = match ext {
+					"" => name,
+					_  => match name.strip_suffix(ext).and_then(|n| n.strip_suffix('.')) {
+						Some(hex) => hex,
+						None => continue,
+					},
+				};
+				if let Some(key) = decode_hex(hex) {
+					keys.push(key);
+				}
+			}
+		}
+
+		Ok(keys)
+	}
+
+	/// Remove every value in the store, and the store's directory.
+	///
+	/// On success, this returns:
+	/// - The amount of bytes removed
+	/// - The [`PathBuf`] that was removed
+	pub fn clear(&self) -> Result<Metadata, anyhow::Error> {
+		if !self.root.try_exists()? {
+			return Ok(Metadata::zero(self.root.clone()));
+		}
+
+		let size = common::filesize(&self.root);
+		std::fs::remove_dir_all(&self.root)?;
+		if let Some(manifest) = &self.manifest {
+			lock(manifest).clear();
+		}
+		Ok(Metadata::new(size, self.root.clone()))
+	}
+
+	/// Remove the least-recently-used values until all values total `max_bytes` or less.
+	///
+	/// A value's last use is its file's access time, or its modification time
+	/// if that is later (or access times are not supported), so on filesystems
+	/// mounted with `noatime` this is the oldest [`Kv::set()`] first.
+	///
+	/// This pairs with [`crate::set_quota()`] for caches:
+	/// ```rust
+	/// # use disk::*;
+	/// let kv = Kv::<Vec<u8>>::open(Dir::Cache, "disk_test", "kv_evict", Format::Json).unwrap();
+	///
+	/// kv.set("old", &vec![0; 64]).unwrap();
+	/// std::thread::sleep(std::time::Duration::from_millis(10));
+	/// let size = kv.set("new", &vec![0; 64]).unwrap().size();
+	///
+	/// // Only room for 1 value.
+	/// let removed = kv.evict_to(size).unwrap();
+	/// assert_eq!(removed.len(), 1);
+	/// assert_eq!(removed[0].0, "old");
+	/// assert_eq!(kv.keys().unwrap(), ["new"]);
+	///
+	/// // Everything was used before now.
+	/// kv.evict_older_than(std::time::Duration::ZERO).unwrap();
+	/// assert!(kv.keys().unwrap().is_empty());
+	/// # kv.clear().unwrap();
+	/// ```
+	///
+	/// On success, this returns the key and [`Metadata`] of every removed value, least-recently-used first.
+	pub fn evict_to(&self, max_bytes: u64) -> Result<Vec<(String, Metadata)>, anyhow::Error> {
+		let entries = self.entries_by_use()?;
+		let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+
+		let mut removed = vec![];
+		for (key, size, _) in entries {
+			if total <= max_bytes {
+				break;
+			}
+			let metadata = self.remove(&key)?;
+			total = total.saturating_sub(size);
+			removed.push((key, metadata));
+		}
+
+		Ok(removed)
+	}
+
+	/// Remove every value that was not used within `duration`.
+	///
+	/// See [`Kv::evict_to()`] for what counts as a use.
+	///
+	/// On success, this returns the key and [`Metadata`] of every removed value, least-recently-used first.
+	pub fn evict_older_than(&self, duration: std::time::Duration) -> Result<Vec<(String, Metadata)>, anyhow::Error> {
+		let cutoff = std::time::SystemTime::now()
+			.checked_sub(duration)
+			.unwrap_or(std::time::UNIX_EPOCH);
+
+		let mut removed = vec![];
+		for (key, _, used) in self.entries_by_use()? {
+			if used >= cutoff {
+				break;
+			}
+			let metadata = self.remove(&key)?;
+			removed.push((key, metadata));
+		}
+
+		Ok(removed)
+	}
+
+	// Every key, its file size and last use, least-recently-used first.
+	fn entries_by_use(&self) -> Result<Vec<(String, u64, std::time::SystemTime)>, anyhow::Error> {
+		let mut entries = vec![];
+		for key in self.keys()? {
+			let metadata = match std::fs::metadata(self.path(&key)?) {
+				Ok(m) => m,
+				Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+				Err(e) => return Err(e.into()),
+			};
+			let modified = metadata.modified()?;
+			let used = metadata.accessed().map_or(modified, |a| a.max(modified));
+			entries.push((key, metadata.len(), used));
+		}
+
+		entries.sort_by_key(|(_, _, used)| *used);
+		Ok(entries)
+	}
+
+	// The manifest PATH.
+	fn manifest_path(&self) -> PathBuf {
+		match self.format.file_ext() {
+			"" => self.root.join("manifest"),
+			e  => self.root.join(format!("manifest.{e}")),
+		}
+	}
+
+	// Atomically save the manifest.
+	fn save_manifest(&self, manifest: &BTreeMap<String, ManifestEntry>) -> Result<(), anyhow::Error> {
+		let bytes = self.format.to_bytes(manifest)?;
+		write_atomic(&self.manifest_path(), &bytes)
+	}
+
+	// Build a manifest from the values on disk.
+	fn scan(&self) -> Result<BTreeMap<String, ManifestEntry>, anyhow::Error> {
+		let mut manifest = BTreeMap::new();
+		for key in self.keys()? {
+			let bytes = std::fs::read(self.path(&key)?)?;
+			manifest.insert(key, ManifestEntry {
+				size: bytes.len() as u64,
+				crc32: common::crc32(&bytes),
+				version: 1,
+			});
+		}
+		Ok(manifest)
+	}
+}
+
+impl<V: DeserializeOwned> Kv<V> {
+	/// Returns the value of `key`, or [`None`] if it doesn't exist.
+	pub fn get(&self, key: &str) -> Result<Option<V>, anyhow::Error> {
+		let bytes = match std::fs::read(self.path(key)?) {
+			Ok(b) => b,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+			Err(e) => return Err(e.into()),
+		};
+		Ok(Some(self.format.from_bytes(&bytes)?))
+	}
+}
+
+impl<V: Serialize> Kv<V> {
+	/// Set the value of `key`, replacing any existing value.
+	///
+	/// The value is written to a `.tmp` file first, then renamed.
+	///
+	/// This will return the amount of `bytes` saved and the [`PathBuf`] on success.
+	pub fn set(&self, key: &str, value: &V) -> Result<Metadata, anyhow::Error> {
+		let path  = self.path(key)?;
+		let bytes = self.format.to_bytes(value)?;
+
+		// Held across the write so the manifest is updated in the same order.
+		let manifest = self.manifest.as_ref().map(|m| lock(m));
+
+		if let Some(shard) = path.parent() {
+			std::fs::create_dir_all(shard)?;
+		}
+		write_atomic(&path, &bytes)?;
+
+		if let Some(mut manifest) = manifest {
+			let version = manifest.get(key).map_or(0, |e| e.version) + 1;
+			manifest.insert(key.into(), ManifestEntry {
+				size: bytes.len() as u64,
+				crc32: common::crc32(&bytes),
+				version,
+			});
+			self.save_manifest(&manifest)?;
+		}
+
+		Ok(Metadata::new(bytes.len() as u64, path))
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- ManifestEntry
+/// What the [`Kv`] manifest records about a value, see [`Kv::with_manifest()`].
+#[derive(Copy,Clone,Debug,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
+pub struct ManifestEntry {
+	size: u64,
+	crc32: u32,
+	version: u64,
+}
+
+impl ManifestEntry {
+	#[inline]
+	/// The file size in bytes.
+	pub const fn size(&self) -> u64 {
+		self.size
+	}
+
+	#[inline]
+	/// The CRC-32 checksum of the file.
+	pub const fn crc32(&self) -> u32 {
+		self.crc32
+	}
+
+	#[inline]
+	/// How many times the value was set, starting at `1`.
+	pub const fn version(&self) -> u64 {
+		self.version
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- KvReport
+/// The result of [`Kv::verify()`].
+///
+/// All keys are sorted.
+#[derive(Clone,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
+pub struct KvReport {
+	/// The amount of values matching the manifest.
+	pub ok: usize,
+	/// Keys in the manifest whose file doesn't exist.
+	pub missing: Vec<String>,
+	/// Keys whose file doesn't match the manifest's size (or checksum).
+	pub corrupted: Vec<String>,
+	/// Keys with a file that are not in the manifest.
+	pub untracked: Vec<String>,
+}
+
+impl KvReport {
+	#[inline]
+	/// Returns `true` if there are no missing, corrupted or untracked values.
+	pub fn is_ok(&self) -> bool {
+		self.missing.is_empty() && self.corrupted.is_empty() && self.untracked.is_empty()
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Traits
+// Manual impls, deriving would require `V` to implement them too.
+impl<V> Clone for Kv<V> {
+	fn clone(&self) -> Self {
+		Self { root: self.root.clone(), format: self.format, manifest: self.manifest.clone(), _value: PhantomData }
+	}
+}
+
+impl<V> std::fmt::Debug for Kv<V> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Kv")
+			.field("root", &self.root)
+			.field("format", &self.format)
+			.field("manifest", &self.manifest.is_some())
+			.finish()
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Private
+// A poisoned lock only means another thread panicked, the manifest itself is still valid.
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+	mutex.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+// Write `bytes` to a `.tmp` file, then rename it to `path`.
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), anyhow::Error> {
+	use std::io::Write;
+
+	let mut tmp = path.to_path_buf().into_os_string();
+	tmp.push(".tmp");
+	let tmp = PathBuf::from(tmp);
+
+	// Write to TMP.
+	if let Err(e) = common::file_bufw!(&tmp, bytes.len()).write_all(bytes) {
+		std::fs::remove_file(&tmp)?;
+		bail!(e);
+	}
+
+	// Rename TMP to normal.
+	if let Err(e) = std::fs::rename(&tmp, path) {
+		std::fs::remove_file(&tmp)?;
+		bail!(e);
+	}
+
+	Ok(())
+}
+
+// Lowercase hex -> UTF-8 string.
+fn decode_hex(hex: &str) -> Option<String> {
+	let chunks = hex.as_bytes().chunks_exact(2);
+	if hex.is_empty() || !chunks.remainder().is_empty() {
+		return None;
+	}
+
+	let nibble = |b: u8| match b {
+		b'0'..=b'9' => Some(b - b'0'),
+		b'a'..=b'f' => Some(b - b'a' + 10),
+		_ => None,
+	};
+
+	let bytes = chunks
+		.map(|c| Some(nibble(c[0])? << 4 | nibble(c[1])?))
+		.collect::<Option<Vec<u8>>>()?;
+
+	String::from_utf8(bytes).ok()
+}
@@ -105,6 +105,8 @@
 //! config.json.gz // File name when using `.save_gzip()`
 //! ```
 //! To recover data from this file, you _must_ also use the matching `.from_file_gzip()` when reading the data.
+//!
+//! If you're not sure which variant was saved, `.from_file_auto()` will detect `gzip` and decompress if needed.
 
 //------------------------------------------------------------------------------------------------------------------------
 //! ### Sub-Directories
@@ -191,8 +193,31 @@
 //! bincode = "2.0.0-rc.3"
 //! ```
 //! and add `#[derive(Encode, Decode)]` to your types, like you would with `serde`.
+//!
+//! Types that only implement `serde` can instead pass a trailing `serde` argument to [`bincode2!`].
+//!
+//! The encoding configuration (integer encoding, endianness, size limit) can be set per type with a `config = ...` argument.
 
 //------------------------------------------------------------------------------------------------------------------------
+//! ### Smart pointers
+//! If `State` implements a `disk` trait, so do `Box<State>`, `Arc<State>` and `Cow<'static, State>`,
+//! using the same file, so shared data can be saved and loaded without cloning:
+//! ```rust
+//! # use serde::{Serialize, Deserialize};
+//! # use disk::*;
+//! # use std::sync::Arc;
+//! # disk::toml!(State, disk::Dir::Data, "disk_test", "arc", "state");
+//! # #[derive(Serialize,Deserialize)]
+//! # struct State {
+//! #    number: u32,
+//! # }
+//! let state = Arc::new(State { number: 1 });
+//! state.save().unwrap();
+//!
+//! let state = Arc::<State>::from_file().unwrap();
+//! # State::rm_sub().unwrap();
+//! ```
+//!
 //! ### Manually implementing `disk`
 //! The macros **verify and sanity check** the input data at compile time,
 //! while manual `unsafe impl` **does not,** and gives you full control over the data definitions,
@@ -229,6 +254,16 @@
 //! // This deletes `~/.local/share`...!
 //! State::rm_rf();
 //! ```
+//!
+//! If the `PATH` is only known at runtime, [`DiskConfig`] provides a checked alternative to `unsafe impl`.
+//!
+//! The same compile time checks the macros use are available as [`validate_path_components()`] and [`validate_file_extension()`]:
+//! ```rust,ignore
+//! const _: () = match disk::validate_path_components("MyProject", "", "state") {
+//!     Ok(()) => (),
+//!     Err(e) => panic!("{}", e),
+//! };
+//! ```
 
 //------------------------------------------------------------------------------------------------------------------------
 //! ### Feature Flags
@@ -245,6 +280,31 @@
 //! 312.4 KB @ /my/file/path
 //! ```
 //!
+//! Enabling the `schemars` feature adds `write_schema()` to [`Json`], [`Toml`] and [`Yaml`],
+//! which saves a [JSON Schema](https://json-schema.org) of your type next to the file (requires [`schemars::JsonSchema`](https://docs.rs/schemars)).
+//!
+//! Enabling the `tracing` feature wraps every save/load/remove in a [`tracing`](https://docs.rs/tracing) `debug` span
+//! named `disk`, with the operation, format, file, type, duration and (for saves/removes) the PATH and bytes as fields.
+//! Failures and fallbacks (e.g. `from_file_resilient()`) emit `warn!()`/`debug!()` events.
+//!
+//! Enabling the `rayon` feature adds [`DiskConfig::save_all_keyed()`], which saves many files in parallel.
+//!
+//! Enabling the `base64` feature adds `to_string_gzip_base64()` and `from_string_gzip_base64()`
+//! for exporting/importing data as a compact, text-safe string.
+//!
+//! Enabling the `signing` feature adds `save_signed()` and `from_file_verified()`, which
+//! sign files with [`ed25519`](https://docs.rs/ed25519-dalek) and refuse to load tampered ones.
+//!
+//! Enabling the `encryption` feature adds [`Encrypted`], a field wrapper that keeps
+//! individual fields (tokens, passwords) encrypted on disk, while the rest of the file stays readable.
+//!
+//! Enabling the `shmem` feature adds `save_shmem()` and `from_shmem()` to [`Bincode`] and [`Bincode2`], which
+//! save to shared memory (`/dev/shm` on Linux, [`Dir::Runtime`] elsewhere) for hot state shared between processes.
+//!
+//! [`Toml`], [`Json`] and [`Yaml`] always ignore a UTF-8 byte order mark when reading (e.g. files saved with Notepad).
+//! Enabling the `encoding` feature also transcodes UTF-16 files (e.g. from PowerShell's `Out-File`) to UTF-8 when reading,
+//! without it, UTF-16 files error.
+//!
 //! Use the `full` feature flag to enable _everything_.
 //!
 //! | File Format | Feature flag to enable |
@@ -312,32 +372,125 @@ compile_error!("disk is only compatible with 64-bit/32bit CPUs");
 compile_error!("disk is only compatible with Window/macOS/Linux/WASM");
 
 //------ Common
+mod access;
+mod audit;
+mod chunks;
 mod common;
+mod config;
 mod dir;
+mod editor;
+#[cfg(feature = "encryption")]
+mod encrypted;
+mod forward;
 mod header;
+mod history;
+mod journal;
+mod kv;
+mod limit;
+mod mailbox;
 mod metadata;
+mod migrate;
+#[cfg(any(feature = "toml", feature = "json", feature = "yaml"))]
+mod merge;
+mod network;
+mod newline;
+mod observer;
+mod path;
+mod profile;
+mod project;
+mod quarantine;
+mod quota;
+mod record;
+mod redact;
+#[cfg(feature = "signing")]
+mod signing;
+#[cfg(any(feature = "toml", feature = "json", feature = "yaml"))]
+mod shape;
+#[cfg(all(feature = "shmem", any(feature = "bincode", feature = "bincode2")))]
+mod shmem;
+mod source;
+mod space;
+mod stamp;
+mod symlink;
+mod tmp;
 mod umask;
+mod validate;
+mod verify;
+mod version_dir;
+#[cfg(any(feature = "bincode", feature = "bincode2"))]
+mod versions;
+mod xattr;
+pub use crate::access::{ReadOnly,WriteOnce};
+pub use crate::audit::{AuditLog,AuditRecords};
+pub use crate::chunks::Chunks;
+pub use crate::config::{CaseCollision,DiskConfig,Format};
+#[cfg(feature = "rayon")]
+pub use crate::config::ErrorPolicy;
 pub use crate::dir::Dir;
+#[cfg(feature = "encryption")]
+pub use crate::encrypted::{Encrypted,EncryptionContext,set_key_provider,set_encryption_key,clear_key_provider};
+pub use crate::header::{register_magic,unregister_magic,magic_description,Semver};
+pub use crate::journal::Journal;
+pub use crate::kv::{Kv,KvReport,ManifestEntry};
+pub use crate::limit::{set_decode_limit,clear_decode_limit,decode_limit};
+pub use crate::mailbox::{Mailbox,Drain,Rejected};
+pub use crate::migrate::Migrate;
+pub use crate::project::{Project,ProjectFiles,UninstallPlan};
+pub use crate::redact::Redact;
+pub use crate::source::Source;
+pub use crate::stamp::WriteStamp;
+pub use crate::space::{InsufficientSpace,free_space,set_min_free_space,clear_min_free_space,min_free_space};
+#[cfg(feature = "signing")]
+pub use ed25519_dalek::{SigningKey,VerifyingKey};
+pub use crate::symlink::{SymlinkPolicy,set_symlink_policy,clear_symlink_policy,symlink_policy};
+pub use crate::tmp::{RmTmpReport,TmpFile};
 pub use anyhow::Error;
+pub use memmap2::Mmap;
 pub use metadata::*;
+pub use network::is_network_fs;
+pub use newline::{Newline,LineEnding};
+pub use observer::{DiskObserver,set_observer,clear_observer,set_sync_hook,clear_sync_hook};
+pub use path::{validate_path_components,validate_file_extension,validate_base_path,sanitize_component,ProjectName,FilePaths};
+pub use profile::*;
+pub use quarantine::Quarantined;
+pub use quota::{QuotaExceeded,set_quota,clear_quota,quota};
 pub use umask::*;
+pub use validate::Validate;
+pub use verify::{verify_project,VerifyReport};
+pub use version_dir::*;
+#[cfg(any(feature = "bincode", feature = "bincode2"))]
+pub use versions::{VersionFallback,VersionsFailed};
 
 //------ Hidden re-exports
 #[doc(hidden)]
-pub use const_format::assertcp as const_assert;
-#[doc(hidden)]
 pub use const_format::formatcp as const_format;
 #[doc(hidden)]
-pub use const_str::{
-	starts_with,
-	ends_with,
-	contains,
-	split,
-};
+pub use paste::paste;
+#[cfg(feature = "bincode2")]
 #[doc(hidden)]
-pub use seq_macro::seq;
+pub use ::bincode2 as __bincode2;
+#[cfg(feature = "pickle")]
 #[doc(hidden)]
-pub use paste::paste;
+pub use ::serde_pickle as __serde_pickle;
+#[cfg(feature = "ron")]
+#[doc(hidden)]
+pub use ::ron as __ron;
+#[doc(hidden)]
+pub use access::{__ReadOnlyProbe,__ReadOnlyNo,__WriteOnceProbe,__WriteOnceNo};
+#[doc(hidden)]
+pub use header::{__SemverProbe,__SemverNo};
+#[doc(hidden)]
+pub use newline::{__NewlineProbe,__NewlineNo};
+#[doc(hidden)]
+pub use stamp::{__WriteStampProbe,__WriteStampNo};
+#[doc(hidden)]
+pub use project::{__ProjectEntry,__str_eq};
+#[doc(hidden)]
+pub use path::{__near_max_path,__LongPath};
+#[doc(hidden)]
+pub use validate::{__ValidateProbe,__ValidateYes,__ValidateNo};
+#[doc(hidden)]
+pub use journal::{__JournalProbe,__JournalYes,__JournalNo};
 
 //------ File formats
 #[cfg(feature = "bincode")]
@@ -363,7 +516,7 @@ pub use crate::json::Json;
 #[cfg(feature = "toml")]
 mod toml;
 #[cfg(feature = "toml")]
-pub use crate::toml::Toml;
+pub use crate::toml::{Toml,TomlStyle};
 
 #[cfg(feature = "yaml")]
 mod yaml;
@@ -383,7 +536,7 @@ pub use crate::messagepack::MessagePack;
 #[cfg(feature = "bson")]
 mod bson;
 #[cfg(feature = "bson")]
-pub use crate::bson::Bson;
+pub use crate::bson::{Bson,BsonDocuments};
 
 #[cfg(feature = "ron")]
 mod ron;
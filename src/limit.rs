@@ -0,0 +1,62 @@
+//---------------------------------------------------------------------------------------------------- Use
+use std::sync::RwLock;
+
+//---------------------------------------------------------------------------------------------------- Decode limit
+// The current decode limit, if any.
+static DECODE_LIMIT: RwLock<Option<u64>> = RwLock::new(None);
+
+/// Set the maximum amount of bytes binary formats will decode, for your entire process.
+///
+/// This is meant for loading files that could have been tampered with (caches, downloads, etc)
+/// where a malicious length prefix should not be able to make `disk` allocate gigabytes of memory.
+///
+/// When set, `from_bytes()`, `from_reader()`, `from_file()` and `from_path()` of:
+/// - [`crate::Bincode`] error if decoding would read _or allocate_ more than `bytes`
+/// - [`crate::Bincode2`], [`crate::Bson`], [`crate::MessagePack`], [`crate::Pickle`] and [`crate::Postcard`] error if the input (or file) is larger than `bytes`
+///
+/// [`crate::Bincode2`] can only limit allocations at compile-time,
+/// see its `config = ...` macro argument and `bincode::config::Configuration::with_limit()`.
+///
+/// Text formats are never affected.
+///
+/// ```rust
+/// // Refuse to decode anything larger than 16 MiB.
+/// disk::set_decode_limit(16 * 1024 * 1024);
+/// assert_eq!(disk::decode_limit(), Some(16 * 1024 * 1024));
+///
+/// disk::clear_decode_limit();
+/// assert_eq!(disk::decode_limit(), None);
+/// ```
+pub fn set_decode_limit(bytes: u64) {
+	*DECODE_LIMIT.write().unwrap_or_else(|e| e.into_inner()) = Some(bytes);
+}
+
+/// Opt-out of the limit set with [`set_decode_limit()`].
+pub fn clear_decode_limit() {
+	*DECODE_LIMIT.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Returns the limit set with [`set_decode_limit()`], if any.
+pub fn decode_limit() -> Option<u64> {
+	*DECODE_LIMIT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+//---------------------------------------------------------------------------------------------------- Private
+#[cfg(any(feature = "bincode2", feature = "bson", feature = "messagepack", feature = "pickle", feature = "postcard"))]
+// Error if `len` bytes of input are over the decode limit.
+pub(crate) fn ensure_len(len: u64) -> Result<(), anyhow::Error> {
+	if let Some(limit) = decode_limit() {
+		if len > limit {
+			anyhow::bail!("disk: input of {len} bytes is over the decode limit of {limit} bytes");
+		}
+	}
+	Ok(())
+}
+
+#[cfg(feature = "bincode2")]
+// Wrap `reader` so that it can not read past the decode limit.
+//
+// Input over the limit is cut off, so decoding it errors.
+pub(crate) fn take<R: std::io::Read>(reader: R) -> std::io::Take<R> {
+	reader.take(decode_limit().unwrap_or(u64::MAX))
+}
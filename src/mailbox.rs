@@ -0,0 +1,356 @@
+//---------------------------------------------------------------------------------------------------- Use
+use anyhow::bail;
+use serde::{Serialize,Deserialize,de::DeserializeOwned};
+use std::marker::PhantomData;
+use std::path::{Path,PathBuf};
+use std::sync::atomic::{AtomicU64,Ordering};
+use std::time::UNIX_EPOCH;
+use crate::{common,Dir,Format,Metadata};
+
+//---------------------------------------------------------------------------------------------------- Mailbox
+/// A file-based message queue between processes.
+///
+/// Each message is saved as its own file in a spool directory under the project's OS directory,
+/// [`Dir::Runtime`] is usually the right one:
+/// ```text,ignore
+/// /run/user/1001/myproject/inbox/                        // <- `Mailbox::open(Dir::Runtime, "MyProject", "inbox", ...)`
+/// /run/user/1001/myproject/inbox/<time>-<pid>-<seq>.json // <- A message
+/// ```
+///
+/// Messages are written to a `.tmp` file and renamed into place, so receivers never see a partial message.
+///
+/// Messages are received in the order they were sent (by time, then process and send order),
+/// each message is received exactly once, even with multiple receivers.
+///
+/// ```rust
+/// # use disk::*;
+/// let mailbox = Mailbox::<String>::open(Dir::Runtime, "disk_test", "mailbox", Format::Json).unwrap();
+/// # mailbox.clear().unwrap();
+///
+/// // Possibly from another process.
+/// mailbox.send(&"hello".to_string()).unwrap();
+/// mailbox.send(&"world".to_string()).unwrap();
+/// assert_eq!(mailbox.len().unwrap(), 2);
+///
+/// assert_eq!(mailbox.recv().unwrap().as_deref(), Some("hello"));
+/// assert_eq!(mailbox.drain().unwrap().messages, ["world"]);
+/// assert_eq!(mailbox.recv().unwrap(), None);
+/// # mailbox.clear().unwrap();
+/// ```
+pub struct Mailbox<T> {
+	root: PathBuf,
+	format: Format,
+	_value: PhantomData<fn() -> T>,
+}
+
+impl<T> Mailbox<T> {
+	/// Open the mailbox in `dir`/`project_directory`/`sub_directories`, creating the directories if needed.
+	///
+	/// ## Errors
+	/// This errors if the `project_directory` or `sub_directories`
+	/// are invalid (see [`crate::validate_path_components()`]) or the directories could not be created.
+	pub fn open(
+		dir: Dir,
+		project_directory: &str,
+		sub_directories: &str,
+		format: Format,
+	) -> Result<Self, anyhow::Error> {
+		// The file name is not used, only the directories are validated.
+		if let Err(e) = crate::validate_path_components(project_directory, sub_directories, "mailbox") {
+			bail!(e);
+		}
+
+		let mut root = common::get_projectdir(&dir, project_directory)?;
+		sub_directories
+			.split_terminator(&['/', '\\'][..])
+			.filter(|dir| !dir.is_empty())
+			.for_each(|dir| root.push(dir));
+		common::assert_safe_path(&root)?;
+		std::fs::create_dir_all(&root)?;
+
+		Ok(Self { root, format, _value: PhantomData })
+	}
+
+	#[inline]
+	/// The spool directory of the mailbox.
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	#[inline]
+	/// The [`Format`] messages are saved in.
+	pub const fn format(&self) -> Format {
+		self.format
+	}
+
+	/// Returns the amount of messages waiting to be received.
+	pub fn len(&self) -> Result<usize, anyhow::Error> {
+		Ok(self.pending()?.len())
+	}
+
+	/// Returns `true` if there are no messages waiting to be received.
+	pub fn is_empty(&self) -> Result<bool, anyhow::Error> {
+		Ok(self.pending()?.is_empty())
+	}
+
+	/// Remove every message in the mailbox, and the mailbox's directory.
+	///
+	/// This also removes `.tmp` files left by crashed senders.
+	///
+	/// On success, this returns:
+	/// - The amount of bytes removed
+	/// - The [`PathBuf`] that was removed
+	pub fn clear(&self) -> Result<Metadata, anyhow::Error> {
+		if !self.root.try_exists()? {
+			return Ok(Metadata::zero(self.root.clone()));
+		}
+
+		let size = common::filesize(&self.root);
+		std::fs::remove_dir_all(&self.root)?;
+		Ok(Metadata::new(size, self.root.clone()))
+	}
+
+	// The file names of all messages, oldest first.
+	fn pending(&self) -> Result<Vec<String>, anyhow::Error> {
+		let read_dir = match std::fs::read_dir(&self.root) {
+			Ok(r) => r,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+			Err(e) => return Err(e.into()),
+		};
+
+		let mut names = vec![];
+		for entry in read_dir {
+			let name = entry?.file_name();
+			let Some(name) = name.to_str() else { continue };
+			if is_message(name, self.format.file_ext()) {
+				names.push(name.to_string());
+			}
+		}
+
+		// The names are fixed width, so this is also time order.
+		names.sort_unstable();
+		Ok(names)
+	}
+
+	// Take the message `name` so that no other receiver can.
+	//
+	// Returns the claimed PATH, or `None` if another receiver took it first.
+	fn claim(&self, name: &str) -> Result<Option<PathBuf>, anyhow::Error> {
+		let path    = self.root.join(name);
+		let claimed = self.root.join(format!("{name}.recv-{:08x}-{:016x}", std::process::id(), next_seq()));
+
+		match std::fs::rename(&path, &claimed) {
+			Ok(()) => Ok(Some(claimed)),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	// Move the claimed message `name` aside as rejected, returns the rejected PATH.
+	//
+	// If that fails, the message stays claimed (which is also never received again).
+	fn reject(&self, name: &str, claimed: PathBuf) -> PathBuf {
+		let rejected = self.root.join(format!("{name}.{REJECTED}"));
+		match std::fs::rename(&claimed, &rejected) {
+			Ok(()) => rejected,
+			Err(_) => claimed,
+		}
+	}
+}
+
+impl<T: Serialize> Mailbox<T> {
+	/// Send a message.
+	///
+	/// On success, this returns:
+	/// - The amount of bytes saved
+	/// - The [`PathBuf`] of the message
+	pub fn send(&self, value: &T) -> Result<Metadata, anyhow::Error> {
+		use std::io::Write;
+
+		let bytes = self.format.to_bytes(value)?;
+
+		// The mailbox could have been cleared (or the runtime directory cleaned) since opening.
+		std::fs::create_dir_all(&self.root)?;
+
+		let name = message_name(self.format.file_ext());
+		let path = self.root.join(&name);
+		let tmp  = self.root.join(format!("{name}.tmp"));
+
+		// Write to TMP.
+		if let Err(e) = common::file_bufw!(&tmp, bytes.len()).write_all(&bytes) {
+			std::fs::remove_file(&tmp)?;
+			bail!(e);
+		}
+
+		// Rename TMP to normal.
+		if let Err(e) = std::fs::rename(&tmp, &path) {
+			std::fs::remove_file(&tmp)?;
+			bail!(e);
+		}
+
+		Ok(Metadata::new(bytes.len() as u64, path))
+	}
+}
+
+impl<T: DeserializeOwned> Mailbox<T> {
+	/// Receive the oldest message, or `None` if there are none.
+	///
+	/// The message is removed from the mailbox.
+	///
+	/// ## Errors
+	/// A message that fails to be read or deserialized is moved aside to `<message>.rejected`
+	/// (so it does not block the mailbox), and an error with its PATH is returned.
+	///
+	/// Rejected messages are not counted by [`Mailbox::len()`], and are removed by [`Mailbox::clear()`].
+	pub fn recv(&self) -> Result<Option<T>, anyhow::Error> {
+		for name in self.pending()? {
+			if let Some(claimed) = self.claim(&name)? {
+				return match self.take(&claimed) {
+					Ok(value) => Ok(Some(value)),
+					Err(e) => Err(e.context(format!("rejected message: {}", self.reject(&name, claimed).display()))),
+				};
+			}
+		}
+
+		Ok(None)
+	}
+
+	/// Receive all messages, oldest first.
+	///
+	/// The messages are removed from the mailbox.
+	///
+	/// Each message is claimed and deserialized on its own, a message that fails to
+	/// be received is moved aside like in [`Mailbox::recv()`] and reported in [`Drain::rejected`],
+	/// the other messages are still received.
+	///
+	/// ```rust
+	/// # use disk::*;
+	/// let mailbox = Mailbox::<u8>::open(Dir::Runtime, "disk_test", "mailbox_drain", Format::Json).unwrap();
+	/// # mailbox.clear().unwrap();
+	/// mailbox.send(&1).unwrap();
+	///
+	/// // Not a `u8`.
+	/// Mailbox::<u16>::open(Dir::Runtime, "disk_test", "mailbox_drain", Format::Json).unwrap().send(&1000).unwrap();
+	/// mailbox.send(&3).unwrap();
+	///
+	/// let drain = mailbox.drain().unwrap();
+	/// assert_eq!(drain.messages, [1, 3]);
+	/// assert_eq!(drain.rejected.len(), 1);
+	/// assert!(drain.rejected[0].path.exists());
+	/// assert!(mailbox.is_empty().unwrap());
+	/// # mailbox.clear().unwrap();
+	/// ```
+	///
+	/// ## Errors
+	/// This only errors if the mailbox's directory could not be read.
+	pub fn drain(&self) -> Result<Drain<T>, anyhow::Error> {
+		let mut drain = Drain { messages: vec![], rejected: vec![] };
+
+		for name in self.pending()? {
+			let claimed = match self.claim(&name) {
+				Ok(Some(c)) => c,
+				// Received by someone else.
+				Ok(None) => continue,
+				Err(e) => {
+					drain.rejected.push(Rejected { path: self.root.join(&name), error: e.to_string() });
+					continue;
+				},
+			};
+
+			match self.take(&claimed) {
+				Ok(value) => drain.messages.push(value),
+				Err(e) => drain.rejected.push(Rejected { path: self.reject(&name, claimed), error: e.to_string() }),
+			}
+		}
+
+		Ok(drain)
+	}
+
+	// Read and deserialize the `claimed` message, and remove it on success.
+	fn take(&self, claimed: &Path) -> Result<T, anyhow::Error> {
+		let value = self.format.from_bytes(&std::fs::read(claimed)?)?;
+		std::fs::remove_file(claimed)?;
+		Ok(value)
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Drain
+/// The result of [`Mailbox::drain()`].
+#[derive(Clone,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord)]
+pub struct Drain<T> {
+	/// The received messages, oldest first.
+	pub messages: Vec<T>,
+	/// The messages that could not be received, oldest first.
+	pub rejected: Vec<Rejected>,
+}
+
+/// A message that could not be received, see [`Mailbox::recv()`].
+#[derive(Clone,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
+pub struct Rejected {
+	/// The PATH of the message, `<message>.rejected` if it was moved aside.
+	pub path: PathBuf,
+	/// The error.
+	pub error: String,
+}
+
+//---------------------------------------------------------------------------------------------------- Traits
+// Manual impls, deriving would require `T` to implement them too.
+impl<T> Clone for Mailbox<T> {
+	fn clone(&self) -> Self {
+		Self { root: self.root.clone(), format: self.format, _value: PhantomData }
+	}
+}
+
+impl<T> std::fmt::Debug for Mailbox<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Mailbox")
+			.field("root", &self.root)
+			.field("format", &self.format)
+			.finish()
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Private
+// Message file names are:
+//
+// | nanoseconds since the UNIX epoch (32 hex) | `-` | process ID (8 hex) | `-` | sequence (16 hex) | `.` extension |
+//
+// All fixed width, so sorting them sorts by time, then process, then send order.
+const NAME_LEN: usize = 32 + 1 + 8 + 1 + 16;
+
+// The extension added to rejected messages.
+const REJECTED: &str = "rejected";
+
+// This process's message/claim counter.
+static SEQ: AtomicU64 = AtomicU64::new(0);
+
+fn next_seq() -> u64 {
+	SEQ.fetch_add(1, Ordering::Relaxed)
+}
+
+// A new unique message file name.
+fn message_name(ext: &str) -> String {
+	let nanos = std::time::SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+	let name  = format!("{nanos:032x}-{:08x}-{:016x}", std::process::id(), next_seq());
+	match ext {
+		"" => name,
+		_  => format!("{name}.{ext}"),
+	}
+}
+
+// If `name` is a message file name, and not a `.tmp`, claimed or rejected message.
+fn is_message(name: &str, ext: &str) -> bool {
+	let stem = match ext {
+		"" => name,
+		_  => match name.strip_suffix(ext).and_then(|n| n.strip_suffix('.')) {
+			Some(stem) => stem,
+			None => return false,
+		},
+	};
+
+	let b = stem.as_bytes();
+	b.len() == NAME_LEN
+		&& b[32] == b'-'
+		&& b[41] == b'-'
+		&& b.iter().enumerate().all(|(i, b)| i == 32 || i == 41 || b.is_ascii_hexdigit())
+}
@@ -0,0 +1,91 @@
+//---------------------------------------------------------------------------------------------------- Use
+use anyhow::bail;
+use serde_json::{Map,Value};
+
+//---------------------------------------------------------------------------------------------------- Merge
+// Three-way merge `ours` (in-memory) and `theirs` (on disk), both derived from `base`.
+//
+// For each value:
+// - If only one side changed it (compared to `base`), that change is taken
+// - If both sides changed it the same way, that is taken
+// - If both are maps, they are merged key by key
+// - Else it is a conflict
+//
+// Arrays and other values are compared as a whole.
+//
+// All conflicts are returned in the error, as dotted key paths.
+pub(crate) fn merge3(base: Value, ours: Value, theirs: Value) -> Result<Value, anyhow::Error> {
+	let mut conflicts = vec![];
+	let merged = merge(&mut String::new(), Some(base), Some(ours), Some(theirs), &mut conflicts);
+
+	if !conflicts.is_empty() {
+		bail!("merge conflict, both sides changed: {}", conflicts.join(", "));
+	}
+
+	Ok(merged.unwrap_or(Value::Null))
+}
+
+// Merge a single (possibly missing) value at `path`.
+fn merge(
+	path: &mut String,
+	base: Option<Value>,
+	ours: Option<Value>,
+	theirs: Option<Value>,
+	conflicts: &mut Vec<String>,
+) -> Option<Value> {
+	if ours == theirs || theirs == base {
+		return ours;
+	}
+	if ours == base {
+		return theirs;
+	}
+
+	match (base, ours, theirs) {
+		(base, Some(Value::Object(ours)), Some(Value::Object(theirs))) => {
+			let base = match base {
+				Some(Value::Object(map)) => map,
+				_ => Map::new(),
+			};
+			Some(Value::Object(merge_map(path, base, ours, theirs, conflicts)))
+		},
+		(_, ours, _) => {
+			conflicts.push(match path.is_empty() {
+				true  => "<root>".into(),
+				false => path.clone(),
+			});
+			ours
+		},
+	}
+}
+
+// Merge two maps key by key.
+fn merge_map(
+	path: &mut String,
+	mut base: Map<String, Value>,
+	mut ours: Map<String, Value>,
+	mut theirs: Map<String, Value>,
+	conflicts: &mut Vec<String>,
+) -> Map<String, Value> {
+	// All keys of all three maps.
+	let mut keys: Vec<String> = ours.keys().cloned().collect();
+	keys.extend(theirs.keys().filter(|k| !ours.contains_key(*k)).cloned());
+	keys.extend(base.keys().filter(|k| !ours.contains_key(*k) && !theirs.contains_key(*k)).cloned());
+
+	let mut merged = Map::new();
+	for key in keys {
+		let len = path.len();
+		if !path.is_empty() {
+			path.push('.');
+		}
+		path.push_str(&key);
+
+		let value = merge(path, base.remove(&key), ours.remove(&key), theirs.remove(&key), conflicts);
+		path.truncate(len);
+
+		if let Some(value) = value {
+			merged.insert(key, value);
+		}
+	}
+
+	merged
+}
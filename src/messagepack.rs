@@ -11,21 +11,82 @@ use std::io::{
 };
 
 //---------------------------------------------------------------------------------------------------- Rmp
-crate::common::impl_macro!(MessagePack, "messagepack");
+crate::common::impl_macro!(MessagePack, "messagepack", {
+	($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, named) => {
+		$crate::messagepack!($data, $dir, $project_directory, $sub_directories, $file_name, ext = "messagepack", named);
+	};
+	($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, ext = $ext:expr, named) => {
+		$crate::assert_str!($project_directory, $sub_directories, $file_name, $ext);
+		$crate::assert_dir!($dir);
+		$crate::assert_ext!($ext);
+
+		// SAFETY: The input to this `MessagePack` implementation was verified and sanity-checked via macro.
+		unsafe impl $crate::MessagePack for $data {
+			const OS_DIRECTORY:       $crate::Dir  = $dir;
+			const PROJECT_DIRECTORY:  &'static str = $project_directory;
+			const SUB_DIRECTORIES:    &'static str = $sub_directories;
+			const FILE:               &'static str = $file_name;
+			const FILE_EXT:           &'static str = $ext;
+			const FILE_NAME:          &'static str = $crate::const_format!("{}.{}", $file_name, $ext);
+			const FILE_NAME_GZIP:     &'static str = $crate::const_format!("{}.{}.gz", $file_name, $ext);
+			const FILE_NAME_TMP:      &'static str = $crate::const_format!("{}.{}.tmp", $file_name, $ext);
+			const FILE_NAME_GZIP_TMP: &'static str = $crate::const_format!("{}.{}.gz.tmp", $file_name, $ext);
+			const READ_ONLY:          bool         = { #[allow(unused_imports)] use $crate::__ReadOnlyNo; $crate::__ReadOnlyProbe::<Self>::YES };
+			const WRITE_ONCE:         bool         = { #[allow(unused_imports)] use $crate::__WriteOnceNo; $crate::__WriteOnceProbe::<Self>::YES };
+			const NAMED:              bool         = true;
+
+			#[inline(always)]
+			fn __validate(&self) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__ValidateYes,__ValidateNo};
+				(&$crate::__ValidateProbe(self)).__disk_validate()
+			}
+
+			#[inline(always)]
+			fn __journal(&mut self) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__JournalYes,__JournalNo};
+				(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal(self, Self::absolute_path)
+			}
+
+			#[inline(always)]
+			fn __journal_reset(metadata: &$crate::Metadata) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__JournalYes,__JournalNo};
+				(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal_reset(metadata, Self::absolute_path)
+			}
+		}
+	};
+});
 
 /// [`MessagePack`](https://docs.rs/rmp-serde) (binary) file format
 ///
 /// File extension is `.messagepack`.
 ///
+/// ## Named fields
+/// By default, structs are saved as arrays without their field names,
+/// so reordering, adding or removing fields breaks existing files.
+///
+/// Passing a trailing `named` argument to [`crate::messagepack!`] (or setting [`MessagePack::NAMED`])
+/// saves structs as maps with their field names instead, like [`crate::Json`]:
+/// ```rust,ignore
+/// disk::messagepack!(State, disk::Dir::Data, "MyProject", "", "state", named);
+/// ```
+/// Both layouts can always be read, so existing files keep working after switching.
+///
 /// ## Safety
 /// When manually implementing, you are **promising** that the `PATH`'s manually specified are correct.
 pub unsafe trait MessagePack: serde::Serialize + serde::de::DeserializeOwned {
+	/// If `true`, structs are saved as maps with their field names, see [`MessagePack#named-fields`].
+	const NAMED: bool = false;
+
 	#[doc(hidden)]
 	#[inline(always)]
 	/// Internal function. Most efficient `from_file()` impl.
 	fn __from_file() -> Result<Self, anyhow::Error> {
 		let path = Self::absolute_path()?;
 		let file = std::fs::File::open(path)?;
+		crate::limit::ensure_len(file.metadata()?.len())?;
 		Ok(rmp_serde::decode::from_read(BufReader::new(file))?)
 	}
 
@@ -34,19 +95,47 @@ pub unsafe trait MessagePack: serde::Serialize + serde::de::DeserializeOwned {
 	/// Internal function. Most efficient `from_path()` impl.
 	fn __from_path(path: &std::path::Path) -> Result<Self, anyhow::Error> {
 		let file = std::fs::File::open(path)?;
+		crate::limit::ensure_len(file.metadata()?.len())?;
 		Ok(rmp_serde::decode::from_read(BufReader::new(file))?)
 	}
 
 	#[inline(always)]
 	/// Create [`Self`] from bytes.
 	fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+		crate::limit::ensure_len(bytes.len() as u64)?;
 		common::convert_error(rmp_serde::decode::from_slice(bytes))
 	}
 
 	#[inline(always)]
 	/// Convert [`Self`] to bytes.
 	fn to_bytes(&self) -> Result<Vec<u8>, anyhow::Error> {
-		common::convert_error(rmp_serde::encode::to_vec(self))
+		Self::__to_bytes(self)
+	}
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. Serialize any `value` the same way as [`Self::to_bytes()`].
+	fn __to_bytes<V: serde::Serialize>(value: &V) -> Result<Vec<u8>, anyhow::Error> {
+		match Self::NAMED {
+			true  => common::convert_error(rmp_serde::encode::to_vec_named(value)),
+			false => common::convert_error(rmp_serde::encode::to_vec(value)),
+		}
+	}
+
+	#[inline(always)]
+	/// Convert [`Self`] directly to the given [`slice`].
+	///
+	/// The amount of bytes written is returned on success.
+	///
+	/// ## Error
+	/// The slice must be large enough to hold the resulting serialized bytes.
+	fn to_slice(&self, slice: &mut [u8]) -> Result<usize, anyhow::Error> {
+		let len = slice.len();
+		let mut writer = &mut slice[..];
+		match Self::NAMED {
+			true  => rmp_serde::encode::write_named(&mut writer, self)?,
+			false => rmp_serde::encode::write(&mut writer, self)?,
+		}
+		Ok(len - writer.len())
 	}
 
 	// Common data/functions.
@@ -1,17 +1,13 @@
 //---------------------------------------------------------------------------------------------------- Use
 
-use std::path::PathBuf;
+use std::path::{Path,PathBuf};
+use std::time::Duration;
 
 use serde::{Serialize,Deserialize};
 
-
-// TODO:
-// Fix import resolution errors.
-//#[cfg(feature = "bincode2")]
-//use bincode2::{Encode,Decode};
-
 //---------------------------------------------------------------------------------------------------- Metadata
-//#[cfg_attr(feature = "bincode2", derive(::bincode2::Encode, ::bincode2::Decode))]
+// `bincode_derive` refers to `::bincode` by default, `disk` renames it to `bincode2`.
+#[cfg_attr(feature = "bincode2", derive(::bincode2::Encode, ::bincode2::Decode), bincode(crate = "::bincode2"))]
 #[derive(Clone,Hash,Debug,Serialize,Deserialize,PartialEq,Eq,PartialOrd,Ord)]
 /// Metadata collected about a file/directory.
 ///
@@ -19,6 +15,12 @@ use serde::{Serialize,Deserialize};
 /// - [`u64`]: the amount of bytes (saved|removed) (to|from) disk.
 /// - [`PathBuf`]: the PATH where the (file|directory) (is|was) (saved|removed).
 ///
+/// Saves also record how long they took, see [`Metadata::serialize_time`] and [`Metadata::io_time`].
+///
+/// `gzip` saves also record the size before compression, see [`Metadata::uncompressed_size`].
+///
+/// Sparse saves also record the size allocated on disk, see [`Metadata::allocated_size`].
+///
 /// ## Display
 /// This implements a more human readable [`Display`].
 ///
@@ -29,17 +31,49 @@ use serde::{Serialize,Deserialize};
 pub struct Metadata {
 	size: u64,
 	path: PathBuf,
+	#[serde(default)]
+	serialize_time: Duration,
+	#[serde(default)]
+	io_time: Duration,
+	#[serde(default)]
+	uncompressed_size: Option<u64>,
+	#[serde(default)]
+	allocated_size: Option<u64>,
 }
 
 impl Metadata {
 	/// Create a new [`Metadata`].
 	pub(crate) const fn new(size: u64, path: PathBuf) -> Self {
-		Self { size, path }
+		Self { size, path, serialize_time: Duration::ZERO, io_time: Duration::ZERO, uncompressed_size: None, allocated_size: None }
 	}
 
 	/// Create a new `0` byte size [`Metadata`].
 	pub(crate) const fn zero(path: PathBuf) -> Self {
-		Self { size: 0, path }
+		Self::new(0, path)
+	}
+
+	/// Set the time spent serializing.
+	pub(crate) const fn with_serialize_time(mut self, serialize_time: Duration) -> Self {
+		self.serialize_time = serialize_time;
+		self
+	}
+
+	/// Set the time spent on I/O.
+	pub(crate) const fn with_io_time(mut self, io_time: Duration) -> Self {
+		self.io_time = io_time;
+		self
+	}
+
+	/// Set the size before compression.
+	pub(crate) const fn with_uncompressed_size(mut self, uncompressed_size: u64) -> Self {
+		self.uncompressed_size = Some(uncompressed_size);
+		self
+	}
+
+	/// Set the amount of bytes allocated on disk.
+	pub(crate) const fn with_allocated_size(mut self, allocated_size: u64) -> Self {
+		self.allocated_size = Some(allocated_size);
+		self
 	}
 
 	/// Returns the amount of bytes removed/saved to disk.
@@ -52,6 +86,64 @@ impl Metadata {
 		self.path
 	}
 
+	/// Returns the [`Path`] of the file/directory, without consuming [`Metadata`].
+	pub(crate) fn path_ref(&self) -> &Path {
+		&self.path
+	}
+
+	/// Returns the time spent serializing (and compressing, for `gzip`) before saving.
+	///
+	/// This is [`Duration::ZERO`] for operations that do not serialize,
+	/// e.g. `save_bytes()` or `rm()`.
+	pub const fn serialize_time(&self) -> Duration {
+		self.serialize_time
+	}
+
+	/// Returns the time spent on the actual I/O, e.g. creating directories, writing and renaming.
+	pub const fn io_time(&self) -> Duration {
+		self.io_time
+	}
+
+	/// Returns the amount of bytes before compression.
+	///
+	/// This is only [`Some`] for compressed saves, e.g. `save_gzip()`,
+	/// in which case [`Metadata::size`] is the compressed size.
+	pub const fn uncompressed_size(&self) -> Option<u64> {
+		self.uncompressed_size
+	}
+
+	/// Returns the compressed size divided by the uncompressed size.
+	///
+	/// `0.25` means the file on disk is a quarter of the original size,
+	/// anything close to (or above) `1.0` means compression was not worth it.
+	///
+	/// This is [`None`] if [`Metadata::uncompressed_size`] is [`None`] or `0`.
+	pub fn compression_ratio(&self) -> Option<f64> {
+		match self.uncompressed_size {
+			Some(0) | None => None,
+			Some(u) => Some(self.size as f64 / u as f64),
+		}
+	}
+
+	/// Returns the amount of bytes the file actually takes up on disk.
+	///
+	/// This is only [`Some`] on Unix, for `file_size()` and sparse saves, e.g. `save_sparse()`.
+	///
+	/// It is rounded up to the filesystem's block size, so it can be larger than [`Metadata::size`].
+	pub const fn allocated_size(&self) -> Option<u64> {
+		self.allocated_size
+	}
+
+	/// Returns `true` if less bytes are allocated on disk than [`Metadata::size`], i.e. the file is sparse.
+	///
+	/// This is `false` if [`Metadata::allocated_size`] is [`None`].
+	pub const fn is_sparse(&self) -> bool {
+		match self.allocated_size {
+			Some(a) => a < self.size,
+			None => false,
+		}
+	}
+
 	/// Clone and returns the inner parts.
 	pub fn to_parts(&self) -> (u64, PathBuf) {
 		(self.size, self.path.clone())
@@ -61,6 +153,13 @@ impl Metadata {
 	pub fn into_parts(self) -> (u64, PathBuf) {
 		(self.size, self.path)
 	}
+
+	#[cfg(feature = "tracing")]
+	// Record the `path` and `bytes` fields of a `tracing` span.
+	pub(crate) fn trace_record(&self, span: &tracing::Span) {
+		span.record("path", tracing::field::debug(&self.path));
+		span.record("bytes", self.size);
+	}
 }
 
 
@@ -0,0 +1,104 @@
+//---------------------------------------------------------------------------------------------------- Migrate
+/// Convert the previous version of a type into [`Self`].
+///
+/// Each version only needs to know how to migrate from the version right before it,
+/// [`crate::migrations!`] chains them together for `from_versions()`:
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::bincode!(Data0, Dir::Data, "disk_test", "migrate", "data", [7_u8; 24], 0);
+/// #[derive(Serialize,Deserialize)]
+/// struct Data0 {
+///     volume: u8,
+/// }
+///
+/// disk::bincode!(Data1, Dir::Data, "disk_test", "migrate", "data", [7_u8; 24], 1);
+/// #[derive(Serialize,Deserialize)]
+/// struct Data1 {
+///     volume: u8,
+///     muted: bool,
+/// }
+///
+/// disk::bincode!(Data2, Dir::Data, "disk_test", "migrate", "data", [7_u8; 24], 2);
+/// #[derive(Serialize,Deserialize,PartialEq,Debug)]
+/// struct Data2 {
+///     volume: f32,
+///     muted: bool,
+/// }
+///
+/// impl disk::Migrate<Data0> for Data1 {
+///     fn migrate_from(prev: Data0) -> Self {
+///         Self { volume: prev.volume, muted: false }
+///     }
+/// }
+///
+/// impl disk::Migrate<Data1> for Data2 {
+///     fn migrate_from(prev: Data1) -> Self {
+///         Self { volume: f32::from(prev.volume) / 100.0, muted: prev.muted }
+///     }
+/// }
+///
+/// // An old file.
+/// Data0 { volume: 50 }.save().unwrap();
+///
+/// // `Data0` -> `Data1` -> `Data2`.
+/// let (version, data) = Data2::from_versions(disk::migrations!(Data0 -> Data1 -> Data2)).unwrap();
+/// assert_eq!(version, 0);
+/// assert_eq!(data, Data2 { volume: 0.5, muted: false });
+/// # Data2::rm_sub().unwrap();
+/// ```
+pub trait Migrate<Prev>: Sized {
+	/// Convert `prev` into [`Self`].
+	fn migrate_from(prev: Prev) -> Self;
+}
+
+//---------------------------------------------------------------------------------------------------- migrations!
+#[macro_export]
+/// Build the `from_versions()` table of a chain of [`crate::Migrate`] types.
+///
+/// The input is the types of every version, oldest first, separated by `->`:
+/// ```rust,ignore
+/// disk::migrations!(Data0 -> Data1 -> Data2 -> Data3 -> Data4 -> Data5)
+/// ```
+///
+/// The output is a `&'static [(u8, fn() -> Result<Data5, anyhow::Error>)]`, with an entry for each type's `VERSION`.
+///
+/// Each entry reads the file as that type with `from_file()`,
+/// then applies every migration after it, e.g. the `Data0` entry is:
+/// ```rust,ignore
+/// Data5::migrate_from(Data4::migrate_from(Data3::migrate_from(Data2::migrate_from(Data1::migrate_from(Data0::from_file()?)))))
+/// ```
+/// so only `Migrate<Data0> for Data1`, `Migrate<Data1> for Data2`, etc, must be implemented.
+///
+/// The types must be plain identifiers (use a `type` alias for paths) and all have a different `VERSION`.
+macro_rules! migrations {
+	($($version:ident)->+) => {
+		$crate::migrations!(@last [$($version)+] $($version)+)
+	};
+
+	// Find the last (newest) type.
+	(@last [$($version:ident)+] $last:ident) => {
+		$crate::migrations!(@table $last [] $($version)+)
+	};
+	(@last [$($version:ident)+] $head:ident $($rest:ident)+) => {
+		$crate::migrations!(@last [$($version)+] $($rest)+)
+	};
+
+	// Add the entry of each type, migrating through all the types after it.
+	(@table $last:ident [$($entries:tt)*] $head:ident $($rest:ident)*) => {
+		$crate::migrations!(@table $last [$($entries)*
+			(
+				<$head>::VERSION,
+				(|| -> ::std::result::Result<$last, $crate::Error> {
+					let data = <$head>::from_file()?;
+					$(let data = <$rest as $crate::Migrate<_>>::migrate_from(data);)*
+					Ok(data)
+				}) as fn() -> ::std::result::Result<$last, $crate::Error>,
+			),
+		] $($rest)*)
+	};
+	(@table $last:ident [$($entries:tt)*]) => {{
+		const MIGRATIONS: &[(u8, fn() -> ::std::result::Result<$last, $crate::Error>)] = &[$($entries)*];
+		MIGRATIONS
+	}};
+}
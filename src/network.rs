@@ -0,0 +1,139 @@
+//---------------------------------------------------------------------------------------------------- Use
+use std::path::Path;
+
+//---------------------------------------------------------------------------------------------------- Network filesystems
+/// Returns `true` if `path` is on a network filesystem (NFS, SMB/CIFS, etc).
+///
+/// Renames and `mmap` flushes on these are not reliable (they can fail spuriously,
+/// or succeed without the data reaching the server), see `save_atomic_networksafe()`.
+///
+/// This is checked with:
+/// - Linux: the filesystem type of `statfs()` (NFS, SMB/CIFS, AFS, Coda, NCP, 9P, Ceph)
+/// - macOS: the filesystem name of `statfs()` (`nfs`, `smbfs`, `afpfs`, `webdav`, `cifs`)
+/// - Windows: UNC PATHs (`\\server\share`) and mapped network drives
+///
+/// If `path` does not exist, its closest existing parent is checked.
+///
+/// Other platforms always return `false`.
+///
+/// ```rust
+/// // Usually not a network filesystem.
+/// let _ = disk::is_network_fs(&std::env::temp_dir()).unwrap();
+/// ```
+pub fn is_network_fs(path: &Path) -> Result<bool, anyhow::Error> {
+	let Some(path) = path.ancestors().find(|p| p.exists()) else {
+		anyhow::bail!("disk: no parent of {path:?} exists");
+	};
+
+	#[cfg(target_os = "linux")]
+	{
+		use std::os::unix::ffi::OsStrExt;
+
+		// From `statfs(2)`.
+		const NETWORK: [u32; 10] = [
+			0x6969,     // NFS
+			0x517B,     // SMB
+			0xFF534D42, // CIFS
+			0xFE534D42, // SMB2
+			0x5346414F, // AFS
+			0x6B414653, // kAFS
+			0x73757245, // Coda
+			0x564C,     // NCP
+			0x01021997, // 9P
+			0x00C36400, // Ceph
+		];
+
+		let path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+
+		// SAFETY: `path` is a valid C string, `stat` is only read if the call succeeded.
+		let stat = unsafe {
+			let mut stat: libc::statfs = std::mem::zeroed();
+			if libc::statfs(path.as_ptr(), &mut stat) != 0 {
+				return Err(std::io::Error::last_os_error().into());
+			}
+			stat
+		};
+
+		// `f_type`'s type differs per platform, the magic numbers are 32-bit.
+		#[allow(clippy::unnecessary_cast)]
+		Ok(NETWORK.contains(&(stat.f_type as u32)))
+	}
+
+	#[cfg(target_os = "macos")]
+	{
+		use std::os::unix::ffi::OsStrExt;
+
+		const NETWORK: [&[u8]; 5] = [b"nfs", b"smbfs", b"afpfs", b"webdav", b"cifs"];
+
+		let path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+
+		// SAFETY: `path` is a valid C string, `stat` is only read if the call succeeded.
+		let stat = unsafe {
+			let mut stat: libc::statfs = std::mem::zeroed();
+			if libc::statfs(path.as_ptr(), &mut stat) != 0 {
+				return Err(std::io::Error::last_os_error().into());
+			}
+			stat
+		};
+
+		// SAFETY: `f_fstypename` is null-terminated.
+		let name = unsafe { std::ffi::CStr::from_ptr(stat.f_fstypename.as_ptr()) };
+		Ok(NETWORK.contains(&name.to_bytes()))
+	}
+
+	#[cfg(windows)]
+	{
+		use std::os::windows::ffi::OsStrExt;
+		use std::path::{Component,Prefix};
+
+		// From `GetDriveTypeW()`.
+		const DRIVE_REMOTE: u32 = 4;
+
+		let path = std::fs::canonicalize(path)?;
+		let root = match path.components().next() {
+			Some(Component::Prefix(p)) => match p.kind() {
+				Prefix::UNC(..) | Prefix::VerbatimUNC(..) => return Ok(true),
+				Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => format!("{}:\\", letter as char),
+				_ => return Ok(false),
+			},
+			_ => return Ok(false),
+		};
+
+		let root: Vec<u16> = std::ffi::OsStr::new(&root).encode_wide().chain(Some(0)).collect();
+		// SAFETY: `root` is a valid null-terminated wide string.
+		let kind = unsafe { windows_sys::Win32::Storage::FileSystem::GetDriveTypeW(root.as_ptr()) };
+		Ok(kind == DRIVE_REMOTE)
+	}
+
+	#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+	{
+		let _ = path;
+		Ok(false)
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Private
+// Write `bytes` to `path` without renaming, for network filesystems.
+//
+// 1. `bytes` are written to `tmp` and `fsync()`'ed
+// 2. `path` is overwritten in place and `fsync()`'ed
+// 3. `tmp` is removed
+//
+// If interrupted during 2, `tmp` is a complete copy of the new data.
+pub(crate) fn write_copy(path: &Path, tmp: &Path, bytes: &[u8]) -> Result<(), anyhow::Error> {
+	use std::io::Write;
+
+	let write = |path: &Path| -> std::io::Result<()> {
+		let mut file = std::fs::File::create(path)?;
+		file.write_all(bytes)?;
+		file.sync_all()
+	};
+
+	if let Err(e) = write(tmp) {
+		std::fs::remove_file(tmp)?;
+		return Err(e.into());
+	}
+	write(path)?;
+	std::fs::remove_file(tmp)?;
+	Ok(())
+}
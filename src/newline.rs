@@ -0,0 +1,124 @@
+//---------------------------------------------------------------------------------------------------- Newline
+/// The line ending used when saving text formats.
+///
+/// Each text format ([`crate::Toml`], [`crate::Json`], [`crate::Yaml`], etc) has a `NEWLINE`
+/// constant, [`Newline::Lf`] by default, set per type with [`LineEnding`].
+/// Binary formats are never affected.
+///
+/// Reading always accepts both `\n` and `\r\n`.
+///
+/// ```rust
+/// use disk::Newline;
+///
+/// assert_eq!(Newline::Crlf.apply("a\nb\r\nc\n".into()), "a\r\nb\r\nc\r\n");
+/// assert_eq!(Newline::Lf.apply("a\r\nb\n".into()), "a\nb\n");
+/// ```
+#[derive(Copy,Clone,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord)]
+pub enum Newline {
+	#[default]
+	/// `\n`, this is the default.
+	Lf,
+	/// `\r\n`.
+	Crlf,
+	/// `\r\n` on Windows, `\n` everywhere else.
+	Native,
+}
+
+impl Newline {
+	#[inline]
+	/// Returns the line ending string, [`Newline::Native`] is resolved for the current platform.
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Lf => "\n",
+			Self::Crlf => "\r\n",
+			#[cfg(target_os = "windows")]
+			Self::Native => "\r\n",
+			#[cfg(not(target_os = "windows"))]
+			Self::Native => "\n",
+		}
+	}
+
+	/// Convert all line endings in `string` to this style.
+	///
+	/// Both `\n` and `\r\n` are converted, a lone `\r` is left as-is.
+	pub fn apply(self, string: String) -> String {
+		let crlf = self.as_str() == "\r\n";
+
+		// Already in the right style, don't allocate.
+		let lf_count   = string.matches('\n').count();
+		let crlf_count = string.matches("\r\n").count();
+		if (crlf && lf_count == crlf_count) || (!crlf && crlf_count == 0) {
+			return string;
+		}
+
+		let lf = string.replace("\r\n", "\n");
+		match crlf {
+			true  => lf.replace('\n', "\r\n"),
+			false => lf,
+		}
+	}
+}
+
+/// Save a type with a [`Newline`] style other than [`Newline::Lf`].
+///
+/// If your type implements this trait _and_ one of the text `disk` traits via macro,
+/// its `NEWLINE` constant is [`LineEnding::STYLE`] and all saves use it.
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::toml!(Settings, Dir::Data, "disk_test", "newline", "settings");
+/// #[derive(Serialize,Deserialize)]
+/// struct Settings {
+///     volume: u8,
+///     muted: bool,
+/// }
+///
+/// impl disk::LineEnding for Settings {
+///     const STYLE: Newline = Newline::Crlf;
+/// }
+///
+/// assert_eq!(Settings::NEWLINE, Newline::Crlf);
+///
+/// Settings { volume: 0, muted: false }.save().unwrap();
+/// assert_eq!(Settings::read_to_string().unwrap(), "volume = 0\r\nmuted = false\r\n");
+/// # Settings::rm_sub().unwrap();
+/// ```
+///
+/// Manual `unsafe impl`'s must set `const NEWLINE: Newline` themselves.
+pub trait LineEnding {
+	/// The line ending used when saving.
+	const STYLE: Newline;
+}
+
+// Same as `__ReadOnlyProbe`, `__NewlineProbe::<T>::NEWLINE` resolves
+// to the inherent `LineEnding::STYLE` if implemented, else `__NewlineNo::NEWLINE` (`Lf`).
+#[doc(hidden)]
+pub struct __NewlineProbe<T>(std::marker::PhantomData<T>);
+
+impl<T: LineEnding> __NewlineProbe<T> {
+	pub const NEWLINE: Newline = T::STYLE;
+}
+
+#[doc(hidden)]
+pub trait __NewlineNo {
+	const NEWLINE: Newline = Newline::Lf;
+}
+
+impl<T> __NewlineNo for __NewlineProbe<T> {}
+
+// The per-type options of the text format `$trait`, nothing for other formats.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __text_options {
+	(Toml) => { $crate::__text_options!(@text); };
+	(Json) => { $crate::__text_options!(@text); };
+	(Yaml) => { $crate::__text_options!(@text); };
+	(Ron) => { $crate::__text_options!(@text); };
+	(Plain) => { $crate::__text_options!(@text); };
+	(@text) => {
+		const NEWLINE: $crate::Newline = { #[allow(unused_imports)] use $crate::__NewlineNo; $crate::__NewlineProbe::<Self>::NEWLINE };
+		const WRITE_STAMP: Option<&'static str> = { #[allow(unused_imports)] use $crate::__WriteStampNo; $crate::__WriteStampProbe::<Self>::WRITE_STAMP };
+	};
+	($trait:ident) => {};
+}
@@ -0,0 +1,188 @@
+//---------------------------------------------------------------------------------------------------- Use
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc,RwLock};
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use crate::Metadata;
+
+//---------------------------------------------------------------------------------------------------- DiskObserver
+// The current observer, if any.
+static OBSERVER: RwLock<Option<Arc<dyn DiskObserver>>> = RwLock::new(None);
+
+/// Observe all I/O operations done by `disk`
+///
+/// Once set with [`set_observer()`], these functions are called
+/// after every save, load and removal done through the `disk` traits, for all types.
+///
+/// Operations built on top of others are reported once, e.g. `from_file_or_embedded_write()`
+/// is 1 load, not a load plus the save of the embedded bytes.
+///
+/// This can be used to feed metrics (file sizes, latencies, failure counts) to Prometheus, StatsD, etc.
+///
+/// All functions do nothing by default, so only the ones needed must be implemented.
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// use std::sync::atomic::{AtomicU64,Ordering};
+/// use std::time::Duration;
+///
+/// static BYTES_SAVED: AtomicU64 = AtomicU64::new(0);
+///
+/// struct Metrics;
+/// impl DiskObserver for Metrics {
+///     fn on_save(&self, metadata: &Metadata, _: Duration) {
+///         BYTES_SAVED.fetch_add(metadata.size(), Ordering::Relaxed);
+///     }
+/// }
+///
+/// disk::set_observer(Metrics);
+///
+/// disk::toml!(State, Dir::Data, "disk_test", "observer", "state");
+/// #[derive(Serialize,Deserialize)]
+/// struct State {
+///     volume: u8,
+/// }
+///
+/// let metadata = State { volume: 0 }.save().unwrap();
+/// assert_eq!(BYTES_SAVED.load(Ordering::Relaxed), metadata.size());
+/// # State::rm_sub().unwrap();
+/// ```
+pub trait DiskObserver: Send + Sync + 'static {
+	#[allow(unused_variables)]
+	/// Called after a successful save, with the [`Metadata`] returned and the time it took.
+	fn on_save(&self, metadata: &Metadata, duration: Duration) {}
+
+	#[allow(unused_variables)]
+	/// Called after a successful load, with the PATH of the associated file and the time it took.
+	fn on_load(&self, path: &Path, duration: Duration) {}
+
+	#[allow(unused_variables)]
+	/// Called after a successful removal, with the [`Metadata`] returned and the time it took.
+	fn on_remove(&self, metadata: &Metadata, duration: Duration) {}
+
+	#[allow(unused_variables)]
+	/// Called after any failed operation, with the name of the function (e.g. `save_bytes`) and the error.
+	fn on_error(&self, operation: &'static str, error: &anyhow::Error) {}
+}
+
+/// Set the [`DiskObserver`] of your entire process.
+///
+/// This replaces any previously set observer.
+pub fn set_observer<O: DiskObserver>(observer: O) {
+	*OBSERVER.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(observer));
+}
+
+/// Remove the [`DiskObserver`] set with [`set_observer()`], if any.
+pub fn clear_observer() {
+	*OBSERVER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+// Call `f` with the current observer, if any.
+//
+// The lock is not held while `f` runs, so observers may use `disk` themselves.
+pub(crate) fn notify<F: FnOnce(&dyn DiskObserver)>(f: F) {
+	let observer = OBSERVER.read().unwrap_or_else(|e| e.into_inner()).clone();
+	if let Some(observer) = observer {
+		f(&*observer);
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Scope
+thread_local! {
+	// How many traced operations are running on this thread.
+	static DEPTH: Cell<usize> = Cell::new(0);
+}
+
+// A traced operation running on this thread.
+//
+// Only the outermost operation notifies the observer and sync hooks,
+// e.g. `from_file_or_embedded()` calling `from_file()` is 1 load, not 2.
+pub(crate) struct Scope(());
+
+impl Scope {
+	pub(crate) fn enter() -> Self {
+		DEPTH.with(|d| d.set(d.get() + 1));
+		Self(())
+	}
+
+	// End the operation, returns `true` if it was the outermost one.
+	//
+	// This is called before notifying, so observers and hooks that use `disk` are notified normally.
+	pub(crate) fn exit(self) -> bool {
+		drop(self);
+		DEPTH.with(|d| d.get() == 0)
+	}
+}
+
+impl Drop for Scope {
+	fn drop(&mut self) {
+		DEPTH.with(|d| d.set(d.get() - 1));
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Sync hooks
+// The sync hook of each project, keyed by `PROJECT_DIRECTORY`.
+type SyncHook = Arc<dyn Fn(&Metadata) + Send + Sync + 'static>;
+static SYNC_HOOKS: Lazy<RwLock<HashMap<String, SyncHook>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Set the sync hook of a project.
+///
+/// `hook` is called after every successful save or removal of any type
+/// whose `PROJECT_DIRECTORY` is `project_directory`, with the [`Metadata`] returned.
+///
+/// This is meant to trigger a sync engine (Dropbox, `rclone`, etc) exactly when files change, instead of polling.
+///
+/// The [`Metadata`] PATH is the file (or directory, for `rm_sub()`, etc) that changed,
+/// if it no longer exists, it was removed.
+///
+/// `project_directory` is the compile-time value passed to the macro,
+/// even if it was overridden with `set_project_name()`.
+///
+/// This replaces any previously set hook for `project_directory`.
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// use std::sync::Mutex;
+///
+/// static CHANGED: Mutex<Vec<std::path::PathBuf>> = Mutex::new(vec![]);
+///
+/// disk::set_sync_hook("disk_test", |metadata| {
+///     CHANGED.lock().unwrap().push(metadata.clone().path());
+/// });
+///
+/// disk::toml!(Synced, Dir::Data, "disk_test", "sync_hook", "synced");
+/// #[derive(Serialize,Deserialize)]
+/// struct Synced {
+///     volume: u8,
+/// }
+///
+/// let metadata = Synced { volume: 0 }.save().unwrap();
+/// assert!(CHANGED.lock().unwrap().contains(&metadata.path()));
+/// # disk::clear_sync_hook("disk_test");
+/// # Synced::rm_sub().unwrap();
+/// ```
+pub fn set_sync_hook<F>(project_directory: &str, hook: F)
+where
+	F: Fn(&Metadata) + Send + Sync + 'static,
+{
+	SYNC_HOOKS.write().unwrap_or_else(|e| e.into_inner()).insert(project_directory.into(), Arc::new(hook));
+}
+
+/// Remove the sync hook of `project_directory` set with [`set_sync_hook()`], if any.
+pub fn clear_sync_hook(project_directory: &str) {
+	SYNC_HOOKS.write().unwrap_or_else(|e| e.into_inner()).remove(project_directory);
+}
+
+// Call the sync hook of `project_directory`, if any.
+//
+// The lock is not held while the hook runs, so hooks may use `disk` themselves.
+pub(crate) fn notify_sync(project_directory: &str, metadata: &Metadata) {
+	let hook = SYNC_HOOKS.read().unwrap_or_else(|e| e.into_inner()).get(project_directory).cloned();
+	if let Some(hook) = hook {
+		hook(metadata);
+	}
+}
@@ -0,0 +1,561 @@
+//---------------------------------------------------------------------------------------------------- Use
+use std::path::PathBuf;
+
+//---------------------------------------------------------------------------------------------------- Path validation
+/// Validate the PATH components of a `disk` file.
+///
+/// This is what the `toml!()`, `json!()`, etc. macros evaluate at compile time,
+/// it can also be used by manual `unsafe impl`'s to get the same guarantees:
+///
+/// ```rust
+/// const _: () = match disk::validate_path_components("MyProject", "some/dirs", "state") {
+///     Ok(()) => (),
+///     Err(e) => panic!("{}", e),
+/// };
+///
+/// assert!(disk::validate_path_components("", "", "state").is_err());
+/// assert!(disk::validate_path_components("MyProject", "a/ b", "state").is_err());
+/// assert!(disk::validate_path_components("MyProject", "", "CON").is_err());
+/// ```
+///
+/// The `sub` directories may be empty and can be arbitrarily deep,
+/// they are delimited with either `/` or `\`.
+///
+/// # Errors
+/// A human readable error message is returned if:
+/// - `project` or `file` are empty
+/// - `project` or `file` contain a `/` or `\`
+/// - `sub` starts or ends with `/` or `\`
+/// - Any component is `255` bytes or longer
+/// - All components combined are `4000` bytes or longer
+/// - Any component starts or ends with ` `, or ends with `.`
+/// - Any component contains `<`, `>`, `:`, `"`, `'`, `|`, `?`, `*`, `^`, `$`, `&`, `(`, `)`
+/// - Any component is a reserved file name on Windows (`CON`, `PRN`, `AUX`, `NUL`, `COM1-9`, `LPT1-9`)
+///
+/// # Long PATHs
+/// Windows limits PATHs to `260` characters (`MAX_PATH`), including the OS directory
+/// (e.g. `C:\Users\alice\AppData\Roaming\`). `disk` uses extended-length (`\\?\`) PATHs
+/// when needed, but other programs (editors, file managers, shells) may fail to open the file,
+/// so keep the components well under `200` characters combined.
+///
+/// When targeting Windows, the implementation macros emit a (deprecation) warning if the project directory,
+/// sub directories, file name and extension are `200` characters or more combined.
+pub const fn validate_path_components(project: &str, sub: &str, file: &str) -> Result<(), &'static str> {
+	tri!(validate_project_directory(project));
+
+	if file.is_empty() {
+		return Err("disk: 'File Name' must not be an empty string");
+	}
+	if project.len() + sub.len() + file.len() >= 4000 {
+		return Err("disk: Directories combined must be less than 4000 bytes long");
+	}
+
+	let bytes = file.as_bytes();
+	if component_end(bytes, 0) != bytes.len() {
+		return Err("disk: 'File Name' must not contain '/' or '\\'");
+	}
+	tri!(validate_component(bytes, 0, bytes.len(), Part::File));
+
+	let bytes = sub.as_bytes();
+	if let [b'/' | b'\\', ..] = bytes {
+		return Err("disk: 'Sub Directories' must not start with '/' or '\\'");
+	}
+	if let [.., b'/' | b'\\'] = bytes {
+		return Err("disk: 'Sub Directories' must not end with '/' or '\\'");
+	}
+	let mut start = 0;
+	while start < bytes.len() {
+		let end = component_end(bytes, start);
+		// Empty components (`a//b`) are ignored, same as `PathBuf`.
+		if end > start {
+			tri!(validate_component(bytes, start, end, Part::Sub));
+		}
+		start = end + 1;
+	}
+
+	Ok(())
+}
+
+/// Validate a file extension.
+///
+/// This is what the macros evaluate at compile time when given `ext = "..."`.
+///
+/// ```rust
+/// assert!(disk::validate_file_extension("yml").is_ok());
+/// assert!(disk::validate_file_extension("tar.gz").is_ok());
+/// assert!(disk::validate_file_extension(".yml").is_err());
+/// assert!(disk::validate_file_extension("").is_err());
+/// ```
+///
+/// # Errors
+/// A human readable error message is returned if `ext`:
+/// - Is empty or `64` bytes or longer
+/// - Starts or ends with `.`
+/// - Contains `/`, `\`, ` ` or any of the symbols rejected by [`validate_path_components`]
+pub const fn validate_file_extension(ext: &str) -> Result<(), &'static str> {
+	let bytes = ext.as_bytes();
+	if bytes.is_empty() {
+		return Err("disk: 'File Extension' must not be an empty string, use a literal `ext = \"\"` for no extension");
+	}
+	if bytes.len() >= 64 {
+		return Err("disk: 'File Extension' must be less than 64 bytes long");
+	}
+	if let [b'.', ..] = bytes {
+		return Err("disk: 'File Extension' must not start with '.'");
+	}
+	let mut i = 0;
+	while i < bytes.len() {
+		match bytes[i] {
+			b'/' | b'\\' => return Err("disk: 'File Extension' must not contain '/' or '\\'"),
+			b' '         => return Err("disk: 'File Extension' must not contain ' '"),
+			_ => (),
+		}
+		i += 1;
+	}
+	validate_component(bytes, 0, bytes.len(), Part::Ext)
+}
+
+/// Validate a [`crate::Dir::Path`] base directory.
+///
+/// This is what the macros evaluate at compile time when given `path = "..."` or [`crate::Dir::Path`].
+///
+/// ```rust
+/// # #[cfg(unix)] {
+/// assert!(disk::validate_base_path("/opt").is_ok());
+/// assert!(disk::validate_base_path("/var/lib/my-service").is_ok());
+/// assert!(disk::validate_base_path("opt").is_err());
+/// assert!(disk::validate_base_path("/opt/../etc").is_err());
+/// # }
+/// ```
+///
+/// # Errors
+/// A human readable error message is returned if `path`:
+/// - Is empty or `4000` bytes or longer
+/// - Is not absolute (starting with `/` on UNIX, or a drive letter like `C:\` on Windows)
+/// - Contains `.` or `..` components
+/// - Contains a component rejected by [`validate_path_components`]
+pub const fn validate_base_path(path: &str) -> Result<(), &'static str> {
+	let bytes = path.as_bytes();
+	if bytes.is_empty() {
+		return Err("disk: 'Base Path' must not be an empty string");
+	}
+	if bytes.len() >= 4000 {
+		return Err("disk: 'Base Path' must be less than 4000 bytes long");
+	}
+
+	// Skip the root.
+	let mut start = match bytes {
+		#[cfg(windows)]
+		[b'A'..=b'Z' | b'a'..=b'z', b':', b'/' | b'\\', ..] => 3,
+		#[cfg(not(windows))]
+		[b'/', ..] => 1,
+		_ => return Err("disk: 'Base Path' must be an absolute PATH"),
+	};
+
+	while start < bytes.len() {
+		let end = component_end(bytes, start);
+		match end - start {
+			// Empty components (`a//b`) are ignored, same as `PathBuf`.
+			0 => (),
+			1 | 2 if bytes[start] == b'.' && bytes[end - 1] == b'.' => {
+				return Err("disk: 'Base Path' must not contain '.' or '..' components");
+			},
+			_ => tri!(validate_component(bytes, start, end, Part::Base)),
+		}
+		start = end + 1;
+	}
+
+	Ok(())
+}
+
+/// Turn a user-provided name into a single valid PATH component.
+///
+/// This is for names that become file names at runtime, e.g. a playlist title
+/// used as the `file` of a [`crate::DiskConfig`], so that it always passes [`validate_path_components`]:
+/// - `/`, `\`, control characters and the symbols rejected by [`validate_path_components`] become `_`
+/// - Leading ` ` and trailing ` ` and `.` are removed
+/// - Reserved file names on Windows (`CON`, `NUL`, etc) are prefixed with `_`
+/// - Names `255` bytes or longer are truncated (on a `char` boundary)
+///
+/// Everything else, including non-ASCII text and case, is kept as-is.
+///
+/// ```rust
+/// assert_eq!(disk::sanitize_component("My Playlist").unwrap(), "My Playlist");
+/// assert_eq!(disk::sanitize_component("AC/DC: Live?").unwrap(), "AC_DC_ Live_");
+/// assert_eq!(disk::sanitize_component("con.txt").unwrap(), "_con.txt");
+/// assert_eq!(disk::sanitize_component(" 日本語... ").unwrap(), "日本語");
+/// assert_eq!(disk::sanitize_component(&"a".repeat(300)).unwrap().len(), 254);
+///
+/// assert!(disk::sanitize_component("").is_err());
+/// assert!(disk::sanitize_component("..").is_err());
+/// ```
+///
+/// # Errors
+/// An error is returned if nothing is left of `name`, e.g. it is empty or only contains ` ` and `.`.
+pub fn sanitize_component(name: &str) -> Result<String, anyhow::Error> {
+	let mapped: String = name
+		.chars()
+		.map(|c| match c {
+			'/' | '\\' | '<' | '>' | ':' | '"' | '\'' | '|' | '?' | '*' | '^' | '$' | '&' | '(' | ')' => '_',
+			c if c.is_control() => '_',
+			c => c,
+		})
+		.collect();
+
+	let trim = |s: &str| s.trim_end_matches([' ', '.']).len();
+	let mut s = mapped.trim_start_matches(' ').to_string();
+	s.truncate(trim(&s));
+
+	let prefix = if reserved_component(s.as_bytes(), 0, s.len()) { "_" } else { "" };
+
+	// Truncate, then trim again (truncating can expose trailing ` ` or `.`).
+	if s.len() + prefix.len() >= 255 {
+		let mut end = 254 - prefix.len();
+		while !s.is_char_boundary(end) {
+			end -= 1;
+		}
+		s.truncate(end);
+		s.truncate(trim(&s));
+	}
+
+	if s.is_empty() {
+		anyhow::bail!("disk: {name:?} has no valid PATH characters");
+	}
+	s.insert_str(0, prefix);
+
+	if let Err(e) = validate_component(s.as_bytes(), 0, s.len(), Part::File) {
+		anyhow::bail!("{e}: {s:?}");
+	}
+	Ok(s)
+}
+
+//---------------------------------------------------------------------------------------------------- ProjectName
+/// The project directory name you expect to be removed, for `rm_project_confirm()`.
+///
+/// This is the same string passed to the `disk` macros, e.g. `ProjectName("MyProject")`.
+///
+/// The whole PATH about to be removed must be the project directory of this name:
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::toml!(State, Dir::Path("/tmp"), "disk_test_confirm", "", "state");
+/// #[derive(Serialize,Deserialize)]
+/// struct State {
+///     volume: u8,
+/// }
+///
+/// State { volume: 0 }.save().unwrap();
+///
+/// // Errors, nothing is removed.
+/// assert!(State::rm_project_confirm(ProjectName("SomeOtherProject")).is_err());
+/// assert!(State::absolute_path().unwrap().exists());
+///
+/// // Removes the project directory.
+/// let metadata = State::rm_project_confirm(ProjectName("disk_test_confirm")).unwrap();
+/// assert_eq!(metadata.path(), std::path::Path::new("/tmp/disk_test_confirm"));
+/// assert!(!State::absolute_path().unwrap().exists());
+/// ```
+#[derive(Copy,Clone,Debug,Hash,PartialEq,Eq,PartialOrd,Ord)]
+pub struct ProjectName<'a>(pub &'a str);
+
+//---------------------------------------------------------------------------------------------------- FilePaths
+/// All PATHs associated with a file, as strings, returned by `print_paths()`.
+///
+/// This is meant for `myapp config path` style commands, the PATHs use the
+/// platform's separators and may not exist, e.g. on Linux with
+/// `toml!(State, Dir::Config, "MyProject", "", "state")`:
+/// ```text,ignore
+/// project_directory: /home/user/.config/myproject
+/// directory:         /home/user/.config/myproject
+/// file:              /home/user/.config/myproject/state.toml
+/// gzip:              /home/user/.config/myproject/state.toml.gz
+/// tmp:               /home/user/.config/myproject/state.toml.tmp
+/// gzip_tmp:          /home/user/.config/myproject/state.toml.gz.tmp
+/// backup:            /home/user/.config/myproject/state.toml.bak
+/// journal:           /home/user/.config/myproject/state.toml.wal
+/// audit:             /home/user/.config/myproject/state.toml.audit
+/// ```
+/// [`Self::history`] only contains backups that exist (see `save_rotated()`), oldest first.
+///
+/// The [`std::fmt::Display`] implementation prints the above (and every `history` backup).
+#[derive(Clone,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord,serde::Serialize,serde::Deserialize)]
+pub struct FilePaths {
+	/// The project directory, see `project_dir_path()`.
+	pub project_directory: String,
+	/// The directory the file is in, see `base_path()`.
+	pub directory: String,
+	/// The file, see `absolute_path()`.
+	pub file: String,
+	/// The `gzip` file, see `absolute_path_gzip()`.
+	pub gzip: String,
+	/// The temporary file of atomic saves.
+	pub tmp: String,
+	/// The temporary file of atomic `gzip` saves.
+	pub gzip_tmp: String,
+	/// The backup file, see `from_file_resilient()`.
+	pub backup: String,
+	/// The journal, see `absolute_path_journal()`.
+	pub journal: String,
+	/// The audit log, see `absolute_path_audit()`.
+	pub audit: String,
+	/// The existing history backups, see `list_history()`.
+	pub history: Vec<String>,
+}
+
+impl std::fmt::Display for FilePaths {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		writeln!(f, "project_directory: {}", self.project_directory)?;
+		writeln!(f, "directory:         {}", self.directory)?;
+		writeln!(f, "file:              {}", self.file)?;
+		writeln!(f, "gzip:              {}", self.gzip)?;
+		writeln!(f, "tmp:               {}", self.tmp)?;
+		writeln!(f, "gzip_tmp:          {}", self.gzip_tmp)?;
+		writeln!(f, "backup:            {}", self.backup)?;
+		writeln!(f, "journal:           {}", self.journal)?;
+		write!(f,   "audit:             {}", self.audit)?;
+		for history in &self.history {
+			write!(f, "\nhistory:           {history}")?;
+		}
+		Ok(())
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Long PATHs
+// Windows limits PATHs to `MAX_PATH` (260) UTF-16 units unless they are
+// "extended-length" PATHs starting with `\\?\`, directories to `MAX_PATH - 12`.
+#[cfg(windows)]
+const MAX_PATH: usize = 260;
+
+// If the constant parts of a PATH are this long, the OS directory will likely push it over `MAX_PATH`.
+const LONG_PATH_WARNING: usize = 200;
+
+#[doc(hidden)]
+/// Internal function. Are the constant PATH components close to `MAX_PATH` on Windows?
+pub const fn __near_max_path(project: &str, sub: &str, file: &str, ext: &str) -> bool {
+	// `project/sub/file.ext`
+	let separators = if ext.is_empty() { 2 } else { 3 };
+	project.len() + sub.len() + file.len() + ext.len() + separators >= LONG_PATH_WARNING
+}
+
+#[doc(hidden)]
+/// Internal type. The macros call `__LongPath::<{ __near_max_path(...) }>::check()`,
+/// only the `true` version is deprecated (on Windows), so only long PATHs emit a warning.
+pub struct __LongPath<const LONG: bool>;
+
+impl __LongPath<false> {
+	#[doc(hidden)]
+	pub const fn check() {}
+}
+
+impl __LongPath<true> {
+	#[doc(hidden)]
+	#[cfg_attr(windows, deprecated(note = "disk: the project directory, sub directories, file name and extension are close to Windows' 260 character PATH limit (MAX_PATH) on their own. disk uses extended-length (\\\\?\\) PATHs when needed, but other programs (editors, file managers, shells) may fail to open the file."))]
+	pub const fn check() {}
+}
+
+// On Windows, turn the absolute `path` into an extended-length (`\\?\C:\...`) PATH
+// if it is long enough to hit `MAX_PATH`, otherwise (and on other platforms) return it as-is.
+//
+// Extended-length PATHs are not normalized by Windows, so this only
+// rebuilds PATHs made of normal components, with `\` separators.
+pub(crate) fn long_path(path: PathBuf) -> PathBuf {
+	#[cfg(windows)]
+	{
+		use std::ffi::OsString;
+		use std::os::windows::ffi::OsStrExt;
+		use std::path::{Component,Prefix};
+
+		if path.as_os_str().encode_wide().count() < MAX_PATH - 12 {
+			return path;
+		}
+
+		let mut components = path.components();
+		let mut long = match components.next() {
+			Some(Component::Prefix(p)) => match p.kind() {
+				Prefix::Disk(letter) => OsString::from(format!(r"\\?\{}:", letter as char)),
+				Prefix::UNC(server, share) => {
+					let mut long = OsString::from(r"\\?\UNC\");
+					long.push(server);
+					long.push(r"\");
+					long.push(share);
+					long
+				},
+				// Already extended-length, or a device.
+				_ => return path,
+			},
+			_ => return path,
+		};
+
+		for component in components {
+			match component {
+				Component::RootDir | Component::CurDir => (),
+				Component::Normal(c) => {
+					long.push(r"\");
+					long.push(c);
+				},
+				Component::Prefix(_) | Component::ParentDir => return path,
+			}
+		}
+
+		PathBuf::from(long)
+	}
+
+	#[cfg(not(windows))]
+	path
+}
+
+//---------------------------------------------------------------------------------------------------- Private
+// Validate a profile name, see [`crate::set_profile`].
+pub(crate) const fn validate_profile(profile: &str) -> Result<(), &'static str> {
+	let bytes = profile.as_bytes();
+	if bytes.is_empty() {
+		return Err("disk: 'Profile' must not be an empty string");
+	}
+	if component_end(bytes, 0) != bytes.len() {
+		return Err("disk: 'Profile' must not contain '/' or '\\'");
+	}
+	validate_component(bytes, 0, bytes.len(), Part::Profile)
+}
+
+// Validate only the `project` part of [`validate_path_components`].
+pub(crate) const fn validate_project_directory(project: &str) -> Result<(), &'static str> {
+	let bytes = project.as_bytes();
+	if bytes.is_empty() {
+		return Err("disk: 'Project Directory' must not be an empty string");
+	}
+	if component_end(bytes, 0) != bytes.len() {
+		return Err("disk: 'Project Directory' must not contain '/' or '\\'");
+	}
+	validate_component(bytes, 0, bytes.len(), Part::Project)
+}
+
+// Which part of the PATH is being validated.
+#[derive(Copy,Clone)]
+enum Part {
+	Project,
+	Sub,
+	File,
+	Ext,
+	Profile,
+	Base,
+}
+
+// `?` for `const fn`.
+macro_rules! tri {
+	($result:expr) => {
+		match $result {
+			Ok(()) => (),
+			Err(e) => return Err(e),
+		}
+	};
+}
+use tri;
+
+// Create the error message for a `Part`.
+macro_rules! err {
+	($part:expr, $msg:literal) => {
+		match $part {
+			Part::Project => concat!("disk: 'Project Directory' ", $msg),
+			Part::Sub     => concat!("disk: one of the 'Sub Directories' ", $msg),
+			Part::File    => concat!("disk: 'File Name' ", $msg),
+			Part::Ext     => concat!("disk: 'File Extension' ", $msg),
+			Part::Profile => concat!("disk: 'Profile' ", $msg),
+			Part::Base    => concat!("disk: one of the 'Base Path' components ", $msg),
+		}
+	};
+}
+
+// File names Windows reserves for devices, regardless of extension.
+const RESERVED: [&str; 22] = [
+	"CON", "PRN", "AUX", "NUL",
+	"COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+	"LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+#[inline]
+// Returns the (exclusive) end index of the `/` or `\`
+// separated path component that starts at `start`.
+const fn component_end(bytes: &[u8], start: usize) -> usize {
+	let mut i = start;
+	while i < bytes.len() && bytes[i] != b'/' && bytes[i] != b'\\' {
+		i += 1;
+	}
+	i
+}
+
+// Validate the single, non-empty component `bytes[start..end]`.
+const fn validate_component(bytes: &[u8], start: usize, end: usize, part: Part) -> Result<(), &'static str> {
+	if end - start >= 255 {
+		return Err(err!(part, "must be less than 255 bytes long"));
+	}
+	if bytes[start] == b' ' {
+		return Err(err!(part, "must not start with ' '"));
+	}
+	match bytes[end - 1] {
+		b' ' => return Err(err!(part, "must not end with ' '")),
+		b'.' => return Err(err!(part, "must not end with '.'")),
+		_ => (),
+	}
+
+	let mut i = start;
+	while i < end {
+		match bytes[i] {
+			b'<'  => return Err(err!(part, "must not contain '<'")),
+			b'>'  => return Err(err!(part, "must not contain '>'")),
+			b':'  => return Err(err!(part, "must not contain ':'")),
+			b'"'  => return Err(err!(part, "must not contain '\"'")),
+			b'\'' => return Err(err!(part, "must not contain '\\''")),
+			b'|'  => return Err(err!(part, "must not contain '|'")),
+			b'?'  => return Err(err!(part, "must not contain '?'")),
+			b'*'  => return Err(err!(part, "must not contain '*'")),
+			b'^'  => return Err(err!(part, "must not contain '^'")),
+			b'$'  => return Err(err!(part, "must not contain '$'")),
+			b'&'  => return Err(err!(part, "must not contain '&'")),
+			b'('  => return Err(err!(part, "must not contain '('")),
+			b')'  => return Err(err!(part, "must not contain ')'")),
+			_ => (),
+		}
+		i += 1;
+	}
+
+	// File extensions are not reserved, e.g. `state.con` is fine.
+	if !matches!(part, Part::Ext) && reserved_component(bytes, start, end) {
+		return Err(err!(part, "must not be a reserved filename on Windows (CON, PRN, AUX, NUL, COM1-9, LPT1-9)"));
+	}
+
+	Ok(())
+}
+
+#[inline]
+// Is `bytes[start..end]` a reserved file name?
+//
+// Windows ignores everything after the first `.`
+// along with trailing spaces, so `con .txt` is `CON`.
+const fn reserved_component(bytes: &[u8], start: usize, end: usize) -> bool {
+	let mut stem_end = start;
+	while stem_end < end && bytes[stem_end] != b'.' {
+		stem_end += 1;
+	}
+	while stem_end > start && bytes[stem_end - 1] == b' ' {
+		stem_end -= 1;
+	}
+
+	let mut r = 0;
+	'outer: while r < RESERVED.len() {
+		let name = RESERVED[r].as_bytes();
+		r += 1;
+		if name.len() != stem_end - start {
+			continue;
+		}
+		let mut i = 0;
+		while i < name.len() {
+			if bytes[start + i].to_ascii_uppercase() != name[i] {
+				continue 'outer;
+			}
+			i += 1;
+		}
+		return true;
+	}
+
+	false
+}
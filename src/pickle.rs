@@ -10,7 +10,62 @@ use std::io::{
 };
 
 //---------------------------------------------------------------------------------------------------- Rmp
-crate::common::impl_macro!(Pickle, "pickle");
+crate::common::impl_macro!(Pickle, "pickle", {
+	($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, ser = $ser:expr, de = $de:expr) => {
+		$crate::pickle!($data, $dir, $project_directory, $sub_directories, $file_name, ext = "pickle", ser = $ser, de = $de);
+	};
+	($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, ext = $ext:expr, ser = $ser:expr, de = $de:expr) => {
+		$crate::assert_str!($project_directory, $sub_directories, $file_name, $ext);
+		$crate::assert_dir!($dir);
+		$crate::assert_ext!($ext);
+
+		// SAFETY: The input to this `Pickle` implementation was verified and sanity-checked via macro.
+		unsafe impl $crate::Pickle for $data {
+			const OS_DIRECTORY:       $crate::Dir  = $dir;
+			const PROJECT_DIRECTORY:  &'static str = $project_directory;
+			const SUB_DIRECTORIES:    &'static str = $sub_directories;
+			const FILE:               &'static str = $file_name;
+			const FILE_EXT:           &'static str = $ext;
+			const FILE_NAME:          &'static str = $crate::const_format!("{}.{}", $file_name, $ext);
+			const FILE_NAME_GZIP:     &'static str = $crate::const_format!("{}.{}.gz", $file_name, $ext);
+			const FILE_NAME_TMP:      &'static str = $crate::const_format!("{}.{}.tmp", $file_name, $ext);
+			const FILE_NAME_GZIP_TMP: &'static str = $crate::const_format!("{}.{}.gz.tmp", $file_name, $ext);
+			const READ_ONLY:          bool         = { #[allow(unused_imports)] use $crate::__ReadOnlyNo; $crate::__ReadOnlyProbe::<Self>::YES };
+			const WRITE_ONCE:         bool         = { #[allow(unused_imports)] use $crate::__WriteOnceNo; $crate::__WriteOnceProbe::<Self>::YES };
+
+			#[inline(always)]
+			fn __validate(&self) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__ValidateYes,__ValidateNo};
+				(&$crate::__ValidateProbe(self)).__disk_validate()
+			}
+
+			#[inline(always)]
+			fn __journal(&mut self) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__JournalYes,__JournalNo};
+				(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal(self, Self::absolute_path)
+			}
+
+			#[inline(always)]
+			fn __journal_reset(metadata: &$crate::Metadata) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__JournalYes,__JournalNo};
+				(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal_reset(metadata, Self::absolute_path)
+			}
+
+			#[inline(always)]
+			fn __ser_options() -> $crate::__serde_pickle::SerOptions {
+				$ser
+			}
+
+			#[inline(always)]
+			fn __de_options() -> $crate::__serde_pickle::DeOptions {
+				$de
+			}
+		}
+	};
+});
 
 /// [`Pickle`](https://docs.rs/serde_pickle) (binary) file format
 ///
@@ -20,19 +75,48 @@ crate::common::impl_macro!(Pickle, "pickle");
 /// The encoding option used is:
 /// ```txt
 /// serde_pickle::de::DeOptions::new();
-/// serde_pickle::ser::DeOptions::new();
+/// serde_pickle::ser::SerOptions::new();
+/// ```
+///
+/// This writes protocol 3 pickles, which Python 2 can't read.
+///
+/// The options can be changed per type with trailing `ser = ...` and `de = ...` arguments to [`crate::pickle!`]:
+/// ```rust,ignore
+/// use serde_pickle::{SerOptions, DeOptions};
+///
+/// // Protocol 2, Python 2 `str` decoded as `String`.
+/// disk::pickle!(
+///     State, disk::Dir::Data, "MyProject", "", "state",
+///     ser = SerOptions::new().proto_v2(),
+///     de  = DeOptions::new().decode_strings()
+/// );
 /// ```
 ///
 /// ## Safety
 /// When manually implementing, you are **promising** that the `PATH`'s manually specified are correct.
 pub unsafe trait Pickle: serde::Serialize + serde::de::DeserializeOwned {
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. The options used when serializing.
+	fn __ser_options() -> serde_pickle::SerOptions {
+		serde_pickle::SerOptions::new()
+	}
+
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. The options used when deserializing.
+	fn __de_options() -> serde_pickle::DeOptions {
+		serde_pickle::DeOptions::new()
+	}
+
 	#[doc(hidden)]
 	#[inline(always)]
 	/// Internal function. Most efficient `from_file()` impl.
 	fn __from_file() -> Result<Self, anyhow::Error> {
 		let path = Self::absolute_path()?;
 		let file = std::fs::File::open(path)?;
-		Ok(serde_pickle::de::from_reader(BufReader::new(file), serde_pickle::de::DeOptions::new())?)
+		crate::limit::ensure_len(file.metadata()?.len())?;
+		Ok(serde_pickle::de::from_reader(BufReader::new(file), Self::__de_options())?)
 	}
 
 	#[doc(hidden)]
@@ -40,19 +124,27 @@ pub unsafe trait Pickle: serde::Serialize + serde::de::DeserializeOwned {
 	/// Internal function. Most efficient `from_path()` impl.
 	fn __from_path(path: &std::path::Path) -> Result<Self, anyhow::Error> {
 		let file = std::fs::File::open(path)?;
-		Ok(serde_pickle::de::from_reader(BufReader::new(file), serde_pickle::de::DeOptions::new())?)
+		crate::limit::ensure_len(file.metadata()?.len())?;
+		Ok(serde_pickle::de::from_reader(BufReader::new(file), Self::__de_options())?)
 	}
 
 	#[inline(always)]
 	/// Create [`Self`] from bytes.
 	fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
-		common::convert_error(serde_pickle::de::from_slice(bytes, serde_pickle::de::DeOptions::new()))
+		crate::limit::ensure_len(bytes.len() as u64)?;
+		common::convert_error(serde_pickle::de::from_slice(bytes, Self::__de_options()))
 	}
 
 	#[inline(always)]
 	/// Convert [`Self`] to bytes.
 	fn to_bytes(&self) -> Result<Vec<u8>, anyhow::Error> {
-		common::convert_error(serde_pickle::ser::to_vec(self, serde_pickle::ser::SerOptions::new()))
+		Self::__to_bytes(self)
+	}
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. Serialize any `value` the same way as [`Self::to_bytes()`].
+	fn __to_bytes<V: serde::Serialize>(value: &V) -> Result<Vec<u8>, anyhow::Error> {
+		common::convert_error(serde_pickle::ser::to_vec(value, Self::__ser_options()))
 	}
 
 	// Common data/functions.
@@ -49,8 +49,14 @@ pub unsafe trait Plain: serde::Serialize + serde::de::DeserializeOwned {
 	///
 	/// This uses [`toml_edit::ser::to_string_pretty`];
 	fn to_string(&self) -> Result<String, anyhow::Error> {
+		Self::__to_string(self)
+	}
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. Serialize any `value` the same way as [`Self::to_string()`].
+	fn __to_string<V: serde::Serialize>(value: &V) -> Result<String, anyhow::Error> {
 		// Newline must be appended.
-		Ok(format!("{}\n", serde_plain::to_string(self)?))
+		Ok(format!("{}\n", serde_plain::to_string(value)?))
 	}
 	#[inline(always)]
 	/// Create [`Self`] from a [`String`].
@@ -12,6 +12,9 @@ crate::common::impl_macro!(Postcard, "bin");
 ///
 /// File extension is `.bin`.
 ///
+/// This is the same as `Bincode`, so if both are used in the same
+/// directory, give one of them a distinct extension, e.g. `disk::postcard!(..., ext = "postcard")`.
+///
 /// ## Safety
 /// When manually implementing, you are **promising** that the `PATH`'s manually specified are correct.
 pub unsafe trait Postcard: serde::Serialize + serde::de::DeserializeOwned {
@@ -32,16 +35,80 @@ pub unsafe trait Postcard: serde::Serialize + serde::de::DeserializeOwned {
 	#[inline(always)]
 	/// Create [`Self`] from bytes.
 	fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+		crate::limit::ensure_len(bytes.len() as u64)?;
 		common::convert_error(postcard::from_bytes(bytes))
 	}
 
 	#[inline(always)]
 	/// Convert [`Self`] to bytes.
 	fn to_bytes(&self) -> Result<Vec<u8>, anyhow::Error> {
-		let vec = common::convert_error(postcard::to_stdvec(self))?;
+		Self::__to_bytes(self)
+	}
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. Serialize any `value` the same way as [`Self::to_bytes()`].
+	fn __to_bytes<V: serde::Serialize>(value: &V) -> Result<Vec<u8>, anyhow::Error> {
+		let vec = common::convert_error(postcard::to_stdvec(value))?;
 		Ok(vec)
 	}
 
+	#[inline(always)]
+	/// Convert [`Self`] directly to the given [`slice`].
+	///
+	/// The amount of bytes written is returned on success.
+	///
+	/// ## Error
+	/// The slice must be large enough to hold the resulting serialized bytes.
+	fn to_slice(&self, slice: &mut [u8]) -> Result<usize, anyhow::Error> {
+		let used = common::convert_error(postcard::to_slice(self, slice))?;
+		Ok(used.len())
+	}
+
+	// COBS operations.
+	#[inline(always)]
+	/// Convert [`Self`] to [COBS](https://docs.rs/postcard/latest/postcard/ser_flavors/struct.Cobs.html)-encoded bytes.
+	///
+	/// These are the same frames `postcard` uses over serial links,
+	/// including the trailing `0x00` sentinel byte.
+	fn to_bytes_cobs(&self) -> Result<Vec<u8>, anyhow::Error> {
+		common::convert_error(postcard::to_stdvec_cobs(self))
+	}
+
+	#[inline(always)]
+	/// Create [`Self`] from [COBS](https://docs.rs/postcard/latest/postcard/ser_flavors/struct.Cobs.html)-encoded bytes.
+	///
+	/// COBS decoding happens in-place, so `bytes` are copied first.
+	fn from_bytes_cobs(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+		let mut bytes = bytes.to_vec();
+		common::convert_error(postcard::from_bytes_cobs(&mut bytes))
+	}
+
+	/// Same as [`Self::save`] but the file is COBS-encoded, see [`Self::to_bytes_cobs`].
+	///
+	/// This writes to the same file as [`Self::save`], so it must be read with [`Self::from_file_cobs`].
+	fn save_cobs(&self) -> Result<crate::Metadata, anyhow::Error> {
+		crate::common::assert_writable!();
+		self.__validate()?;
+		Self::save_bytes(&self.to_bytes_cobs()?)
+	}
+
+	/// Same as [`Self::save_atomic`] but the file is COBS-encoded, see [`Self::save_cobs`].
+	fn save_atomic_cobs(&self) -> Result<crate::Metadata, anyhow::Error> {
+		crate::common::assert_writable!();
+		self.__validate()?;
+		Self::save_bytes_atomic(&self.to_bytes_cobs()?)
+	}
+
+	/// Read the COBS-encoded file and deserialize into [`Self`].
+	///
+	/// This should be used on files saved with [`Self::save_cobs`].
+	fn from_file_cobs() -> Result<Self, anyhow::Error> {
+		let mut bytes = Self::read_to_bytes()?;
+		let s: Self = common::convert_error(postcard::from_bytes_cobs(&mut bytes))?;
+		s.__validate()?;
+		Ok(s)
+	}
+
 	// Common data/functions.
 	common::impl_binary!("postcard");
 }
@@ -0,0 +1,59 @@
+//---------------------------------------------------------------------------------------------------- Use
+use anyhow::bail;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+//---------------------------------------------------------------------------------------------------- Profile
+// The current profile, if any.
+static PROFILE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Set the profile of your entire process.
+///
+/// A profile is an extra directory inserted right after the project directory
+/// in _every_ `PATH` used by `disk`, for all types, e.g. with a profile of `dev`:
+/// ```txt
+/// ~/.local/share/myproject/some/dirs/state.toml
+/// ```
+/// turns into:
+/// ```txt
+/// ~/.local/share/myproject/dev/some/dirs/state.toml
+/// ```
+/// This allows multiple builds (`dev`/`staging`/`prod`) to run side-by-side without touching each other's files.
+///
+/// ```rust
+/// disk::set_profile("dev").unwrap();
+/// assert_eq!(disk::profile().as_deref(), Some("dev"));
+///
+/// // Invalid profiles are rejected.
+/// assert!(disk::set_profile("a/b").is_err());
+///
+/// disk::clear_profile();
+/// assert_eq!(disk::profile(), None);
+/// ```
+///
+/// ## Errors
+/// `profile` is checked with the same rules as the macro `PATH` inputs.
+pub fn set_profile(profile: &str) -> Result<(), anyhow::Error> {
+	if let Err(e) = crate::path::validate_profile(profile) {
+		bail!(e);
+	}
+	*PROFILE.write().unwrap_or_else(|e| e.into_inner()) = Some(profile.into());
+	Ok(())
+}
+
+/// Remove the profile set with [`set_profile()`], if any.
+pub fn clear_profile() {
+	*PROFILE.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Returns the profile set with [`set_profile()`], if any.
+pub fn profile() -> Option<String> {
+	PROFILE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+// Append the profile (if any) to a project directory PATH.
+pub(crate) fn push_profile(path: &mut PathBuf) {
+	if let Some(profile) = PROFILE.read().unwrap_or_else(|e| e.into_inner()).as_deref() {
+		path.push(profile);
+	}
+}
@@ -0,0 +1,363 @@
+//---------------------------------------------------------------------------------------------------- Use
+use serde::{Serialize,Deserialize};
+use std::path::PathBuf;
+use crate::{common,Dir,Metadata,VerifyReport};
+
+//---------------------------------------------------------------------------------------------------- project!
+#[macro_export]
+/// Create a [`Project`] out of all the `disk` types of a project.
+///
+/// The input is the project directory (the 3rd argument of the type macros), then the types:
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::toml!(Settings, Dir::Config, "disk_test_project", "", "settings");
+/// disk::json!(State, Dir::Data, "disk_test_project", "", "state");
+/// disk::bincode!(Cache, Dir::Cache, "disk_test_project", "", "cache", [0; 24], 0);
+/// # #[derive(Serialize,Deserialize)] struct Settings { volume: u8 }
+/// # #[derive(Serialize,Deserialize)] struct State { volume: u8 }
+/// # #[derive(Serialize,Deserialize)] struct Cache { volume: u8 }
+///
+/// let project = disk::project!("disk_test_project", [Settings, State, Cache]);
+///
+/// Settings { volume: 1 }.save().unwrap();
+/// State { volume: 2 }.save().unwrap();
+///
+/// let files = project.files().unwrap();
+/// assert_eq!(files.present.len(), 2);
+/// assert_eq!(files.missing, [Cache::absolute_path().unwrap()]);
+///
+/// assert!(project.verify_all().is_ok());
+/// assert!(project.size().unwrap() > 0);
+///
+/// project.rm_all().unwrap();
+/// assert!(project.files().unwrap().present.is_empty());
+/// # Settings::rm_project().unwrap();
+/// # State::rm_project().unwrap();
+/// ```
+///
+/// Every type must be in the project, this fails to compile:
+/// ```rust,compile_fail
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::toml!(Settings, Dir::Config, "OtherProject", "", "settings");
+/// # #[derive(Serialize,Deserialize)] struct Settings { volume: u8 }
+///
+/// let project = disk::project!("MyProject", [Settings]);
+/// ```
+///
+/// The types' `disk` traits must be in scope (e.g. `use disk::*`).
+macro_rules! project {
+	($project_directory:literal, [$($type:ty),* $(,)?]) => {{
+		$(
+			const {
+				::std::assert!(
+					$crate::__str_eq(<$type>::PROJECT_DIRECTORY, $project_directory),
+					::std::concat!("disk: ", ::std::stringify!($type), " is not in project ", $project_directory),
+				)
+			};
+		)*
+		$crate::Project::__new($project_directory, ::std::vec![$(<$type>::__project_entry()),*])
+	}};
+}
+
+//---------------------------------------------------------------------------------------------------- ProjectEntry
+#[doc(hidden)]
+#[derive(Copy,Clone)]
+/// Internal struct. A type of a [`Project`], created by the macros.
+pub struct __ProjectEntry {
+	pub(crate) dir: Dir,
+	pub(crate) path: fn() -> Result<PathBuf, anyhow::Error>,
+	pub(crate) path_gzip: fn() -> Result<PathBuf, anyhow::Error>,
+	pub(crate) load: fn() -> Result<(), anyhow::Error>,
+	pub(crate) resave: Option<fn() -> Result<Metadata, anyhow::Error>>,
+}
+
+//---------------------------------------------------------------------------------------------------- Project
+#[derive(Clone)]
+/// All the `disk` types of a project, created with [`crate::project!`].
+///
+/// This allows project-wide operations without tracking the types yourself.
+///
+/// A type's "files" are its file and the `gzip` variant of it.
+pub struct Project {
+	name: &'static str,
+	entries: Vec<__ProjectEntry>,
+}
+
+impl Project {
+	#[doc(hidden)]
+	/// Internal function, use [`crate::project!`].
+	pub fn __new(name: &'static str, entries: Vec<__ProjectEntry>) -> Self {
+		Self { name, entries }
+	}
+
+	#[inline]
+	/// The project directory name.
+	pub const fn name(&self) -> &'static str {
+		self.name
+	}
+
+	#[inline]
+	/// The amount of types in the project.
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	#[inline]
+	/// Returns `true` if the project has no types.
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Load every existing file, then save it again with `save_atomic()`.
+	///
+	/// This rewrites the files with the current version of the types (new fields,
+	/// line endings, etc), read-only types and missing files are skipped.
+	///
+	/// This stops at the first error.
+	///
+	/// On success, this returns the [`Metadata`] of every file saved.
+	pub fn resave_all(&self) -> Result<Vec<Metadata>, anyhow::Error> {
+		let mut saved = vec![];
+		for entry in &self.entries {
+			let Some(resave) = entry.resave else { continue };
+			if (entry.path)()?.try_exists()? {
+				saved.push(resave()?);
+			}
+		}
+		Ok(saved)
+	}
+
+	/// Load every existing file with `from_file()`.
+	///
+	/// Files that loaded are `ok`, the others are `corrupt`, missing files are skipped (see [`Project::files()`]).
+	///
+	/// This only checks the files of the project's types, see [`crate::verify_project()`] to check the whole directory.
+	pub fn verify_all(&self) -> VerifyReport {
+		let mut report = VerifyReport::default();
+		for entry in &self.entries {
+			let path = match (entry.path)() {
+				Ok(p)  => p,
+				Err(e) => { report.corrupt.push((PathBuf::new(), e.to_string())); continue },
+			};
+			if !path.is_file() {
+				continue;
+			}
+			match (entry.load)() {
+				Ok(())  => report.ok.push(path),
+				Err(e) => report.corrupt.push((path, e.to_string())),
+			}
+		}
+		report.ok.sort();
+		report.corrupt.sort();
+		report
+	}
+
+	/// Returns the total size in bytes of all files of the project's types.
+	pub fn size(&self) -> Result<u64, anyhow::Error> {
+		let mut size = 0;
+		for entry in &self.entries {
+			size += common::filesize(&(entry.path)()?);
+			size += common::filesize(&(entry.path_gzip)()?);
+		}
+		Ok(size)
+	}
+
+	/// Remove all files of the project's types.
+	///
+	/// Read-only types are skipped, other files in the project directories are left as-is.
+	///
+	/// On success, this returns the [`Metadata`] of every file removed.
+	pub fn rm_all(&self) -> Result<Vec<Metadata>, anyhow::Error> {
+		let mut removed = vec![];
+		for entry in &self.entries {
+			if entry.resave.is_none() {
+				continue;
+			}
+			for path in [(entry.path)()?, (entry.path_gzip)()?] {
+				if path.is_file() {
+					let size = common::filesize(&path);
+					std::fs::remove_file(&path)?;
+					removed.push(Metadata::new(size, path));
+				}
+			}
+		}
+		Ok(removed)
+	}
+
+	/// Compare the files the project's types expect against what exists.
+	///
+	/// Every project directory in use (one per [`Dir`]) is searched for other files.
+	pub fn files(&self) -> Result<ProjectFiles, anyhow::Error> {
+		let mut files = ProjectFiles::default();
+		let mut known = vec![];
+		let mut dirs: Vec<Dir> = vec![];
+
+		for entry in &self.entries {
+			let path = (entry.path)()?;
+			match path.is_file() {
+				true  => files.present.push(path.clone()),
+				false => files.missing.push(path.clone()),
+			}
+			known.push(path);
+			known.push((entry.path_gzip)()?);
+			if !dirs.contains(&entry.dir) {
+				dirs.push(entry.dir);
+			}
+		}
+
+		for dir in dirs {
+			let root = common::get_projectdir(&dir, self.name)?;
+			if !root.try_exists()? {
+				continue;
+			}
+			let mut walked = vec![];
+			common::walk(&root, &mut walked)?;
+			files.other.extend(walked.into_iter().filter(|p| !known.contains(p)));
+		}
+
+		files.present.sort();
+		files.missing.sort();
+		files.other.sort();
+		Ok(files)
+	}
+
+	/// Returns everything [`Project::uninstall()`] would remove, without removing anything.
+	///
+	/// This is the project directory of every [`Dir`] (and every [`Dir::Path`] of the project's types)
+	/// that exists, including all versions, profiles, and files not belonging to the project's types.
+	///
+	/// The directories of the [`Dir`]'s in `keep` are skipped, along with any other
+	/// [`Dir`] that is the same directory on this platform (or contains it), e.g. on macOS,
+	/// keeping [`Dir::Config`] also keeps [`Dir::Data`].
+	///
+	/// [`Dir::Project`] is not a directory, it is never included.
+	pub fn uninstall_plan(&self, keep: &[Dir]) -> Result<UninstallPlan, anyhow::Error> {
+		let mut dirs = vec![Dir::Cache, Dir::Config, Dir::Data, Dir::DataLocal, Dir::Preference, Dir::Runtime];
+		for entry in &self.entries {
+			if matches!(entry.dir, Dir::Path(_)) && !dirs.contains(&entry.dir) {
+				dirs.push(entry.dir);
+			}
+		}
+
+		let mut kept = vec![];
+		for dir in keep.iter().filter(|d| **d != Dir::Project) {
+			kept.push(common::get_projectdir_root(dir, self.name)?);
+		}
+
+		let mut plan = UninstallPlan::default();
+		for dir in dirs.into_iter().filter(|d| !keep.contains(d)) {
+			let path = common::get_projectdir_root(&dir, self.name)?;
+			if kept.iter().any(|k| k.starts_with(&path))
+				|| plan.dirs.iter().any(|(_, p)| *p == path)
+				|| !path.try_exists()?
+			{
+				continue;
+			}
+
+			let mut files = vec![];
+			common::walk(&path, &mut files)?;
+			plan.size += files.iter().map(|f| common::filesize(f)).sum::<u64>();
+			plan.files.extend(files);
+			plan.dirs.push((dir, path));
+		}
+
+		plan.files.sort();
+		Ok(plan)
+	}
+
+	/// Remove every trace of the project, except the [`Dir`]'s in `keep`.
+	///
+	/// This removes the directories of [`Project::uninstall_plan()`], e.g. for an uninstaller:
+	/// ```rust
+	/// # use serde::{Serialize,Deserialize};
+	/// # use disk::*;
+	/// disk::toml!(Settings, Dir::Config, "disk_test_uninstall", "", "settings");
+	/// disk::json!(State, Dir::Data, "disk_test_uninstall", "some/dirs", "state");
+	/// # #[derive(Serialize,Deserialize)] struct Settings { volume: u8 }
+	/// # #[derive(Serialize,Deserialize)] struct State { volume: u8 }
+	///
+	/// let project = disk::project!("disk_test_uninstall", [Settings, State]);
+	/// Settings { volume: 1 }.save().unwrap();
+	/// State { volume: 2 }.save().unwrap();
+	///
+	/// // Keep the user's settings.
+	/// let plan = project.uninstall_plan(&[Dir::Config]).unwrap();
+	/// assert_eq!(plan.files, [State::absolute_path().unwrap()]);
+	///
+	/// project.uninstall(&[Dir::Config]).unwrap();
+	/// assert!(!State::project_dir_path().unwrap().exists());
+	/// assert!(Settings::absolute_path().unwrap().exists());
+	/// # Settings::rm_project().unwrap();
+	/// ```
+	///
+	/// On success, this returns what was removed.
+	pub fn uninstall(&self, keep: &[Dir]) -> Result<UninstallPlan, anyhow::Error> {
+		let plan = self.uninstall_plan(keep)?;
+		for (_, path) in &plan.dirs {
+			match std::fs::remove_dir_all(path) {
+				Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+				_ => (),
+			}
+		}
+		Ok(plan)
+	}
+}
+
+impl std::fmt::Debug for Project {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Project")
+			.field("name", &self.name)
+			.field("len", &self.entries.len())
+			.finish()
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- ProjectFiles
+/// The result of [`Project::files()`].
+///
+/// All PATHs are sorted.
+#[derive(Clone,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
+pub struct ProjectFiles {
+	/// Files of the project's types that exist.
+	pub present: Vec<PathBuf>,
+	/// Files of the project's types that do not exist.
+	pub missing: Vec<PathBuf>,
+	/// Other files in the project directories, e.g. `.tmp` or history files, or the files of unlisted types.
+	pub other: Vec<PathBuf>,
+}
+
+//---------------------------------------------------------------------------------------------------- UninstallPlan
+/// The result of [`Project::uninstall_plan()`] and [`Project::uninstall()`].
+///
+/// All PATHs are sorted.
+#[derive(Clone,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize)]
+pub struct UninstallPlan {
+	/// The project directories, along with the [`Dir`] they belong to.
+	///
+	/// If multiple [`Dir`]'s are the same directory, only the first is listed.
+	pub dirs: Vec<(Dir, PathBuf)>,
+	/// Every file in the directories.
+	pub files: Vec<PathBuf>,
+	/// The total size in bytes of the files.
+	pub size: u64,
+}
+
+//---------------------------------------------------------------------------------------------------- Private
+#[doc(hidden)]
+/// Internal function. `const` string equality.
+pub const fn __str_eq(a: &str, b: &str) -> bool {
+	let (a, b) = (a.as_bytes(), b.as_bytes());
+	if a.len() != b.len() {
+		return false;
+	}
+	let mut i = 0;
+	while i < a.len() {
+		if a[i] != b[i] {
+			return false;
+		}
+		i += 1;
+	}
+	true
+}
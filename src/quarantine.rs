@@ -0,0 +1,53 @@
+//---------------------------------------------------------------------------------------------------- Use
+use crate::Metadata;
+
+//---------------------------------------------------------------------------------------------------- Quarantined
+#[derive(Debug)]
+/// Error returned when a file failed to deserialize and was quarantined.
+///
+/// This is returned (wrapped in [`anyhow::Error`]) by `from_file_or_quarantine()`,
+/// and can be retrieved with [`anyhow::Error::downcast_ref`]:
+/// ```rust,ignore
+/// match State::from_file_or_quarantine() {
+///     Ok(state) => println!("{state:?}"),
+///     Err(e) => match e.downcast_ref::<disk::Quarantined>() {
+///         Some(q) => println!("corrupt file moved to: {}", q.metadata()),
+///         None    => println!("other error: {e}"),
+///     },
+/// }
+/// ```
+pub struct Quarantined {
+	metadata: Metadata,
+	error: anyhow::Error,
+}
+
+impl Quarantined {
+	/// Create a new [`Quarantined`].
+	pub(crate) const fn new(metadata: Metadata, error: anyhow::Error) -> Self {
+		Self { metadata, error }
+	}
+
+	/// Returns the [`Metadata`] of the quarantined file (it's new PATH).
+	pub const fn metadata(&self) -> &Metadata {
+		&self.metadata
+	}
+
+	/// Returns the original deserialization error.
+	pub const fn error(&self) -> &anyhow::Error {
+		&self.error
+	}
+
+	/// Consume [`Quarantined`] and returns the inner parts.
+	pub fn into_parts(self) -> (Metadata, anyhow::Error) {
+		(self.metadata, self.error)
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Display
+impl std::fmt::Display for Quarantined {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "file failed to deserialize and was quarantined ({}): {}", self.metadata, self.error)
+	}
+}
+
+impl std::error::Error for Quarantined {}
@@ -0,0 +1,162 @@
+//---------------------------------------------------------------------------------------------------- Use
+use std::collections::HashMap;
+use std::path::{Path,PathBuf};
+use std::sync::{Mutex,RwLock};
+use once_cell::sync::Lazy;
+
+//---------------------------------------------------------------------------------------------------- Quota
+// The quota of each project, in bytes.
+static QUOTAS: Lazy<RwLock<HashMap<String, u64>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+// The last known size of each project directory with a quota, in bytes.
+//
+// This can only be too large (a failed save, a removed file), never too small
+// (except for other processes), so it is measured again before erroring.
+static USAGE: Lazy<Mutex<HashMap<PathBuf, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Set the maximum total size of the project directory `project_directory`, for your entire process.
+///
+/// This is the `PROJECT_DIRECTORY` of your types (the 3rd macro argument),
+/// each [`crate::Dir`] of the project is limited separately.
+///
+/// When set, all `save*()` functions of types in that project that write a whole file
+/// error with [`QuotaExceeded`] (and write nothing) if the project directory
+/// (including all sub-directories) would be larger than `bytes` after saving.
+///
+/// The size of the file being replaced is not counted against the quota.
+///
+/// The project directory is only measured (walked) on the first save after setting the quota,
+/// later saves update that size, and it is measured again before returning [`QuotaExceeded`].
+/// Files written by other processes are not noticed until then, or until the quota is set again.
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::json!(Cache, Dir::Cache, "disk_test_quota", "", "cache");
+/// #[derive(Serialize,Deserialize)]
+/// struct Cache(Vec<u8>);
+///
+/// disk::set_quota("disk_test_quota", 1024);
+/// assert_eq!(disk::quota("disk_test_quota"), Some(1024));
+///
+/// assert!(Cache(vec![0; 16]).save().is_ok());
+///
+/// let e = Cache(vec![0; 4096]).save().unwrap_err();
+/// assert!(e.downcast_ref::<disk::QuotaExceeded>().is_some());
+///
+/// disk::clear_quota("disk_test_quota");
+/// assert!(Cache(vec![0; 4096]).save().is_ok());
+/// # Cache::rm_project().unwrap();
+/// ```
+pub fn set_quota(project_directory: &str, bytes: u64) {
+	USAGE.lock().unwrap_or_else(|e| e.into_inner()).clear();
+	QUOTAS.write().unwrap_or_else(|e| e.into_inner()).insert(project_directory.to_string(), bytes);
+}
+
+/// Remove the quota set with [`set_quota()`] for `project_directory`.
+pub fn clear_quota(project_directory: &str) {
+	USAGE.lock().unwrap_or_else(|e| e.into_inner()).clear();
+	QUOTAS.write().unwrap_or_else(|e| e.into_inner()).remove(project_directory);
+}
+
+/// Returns the quota set with [`set_quota()`] for `project_directory`, if any.
+pub fn quota(project_directory: &str) -> Option<u64> {
+	QUOTAS.read().unwrap_or_else(|e| e.into_inner()).get(project_directory).copied()
+}
+
+//---------------------------------------------------------------------------------------------------- QuotaExceeded
+#[derive(Debug,Clone,PartialEq,Eq)]
+/// Error returned when saving would exceed the quota set with [`set_quota()`].
+///
+/// This is returned (wrapped in [`anyhow::Error`]) by `save*()` functions,
+/// and can be retrieved with [`anyhow::Error::downcast_ref`]:
+/// ```rust,ignore
+/// match Cache::save(&cache) {
+///     Ok(metadata) => println!("{metadata}"),
+///     Err(e) => match e.downcast_ref::<disk::QuotaExceeded>() {
+///         Some(q) => println!("cache is full: {} > {} bytes", q.size(), q.quota()),
+///         None    => println!("other error: {e}"),
+///     },
+/// }
+/// ```
+pub struct QuotaExceeded {
+	project: String,
+	quota: u64,
+	size: u64,
+}
+
+impl QuotaExceeded {
+	/// Returns the project directory name.
+	pub fn project(&self) -> &str {
+		&self.project
+	}
+
+	/// Returns the quota in bytes.
+	pub const fn quota(&self) -> u64 {
+		self.quota
+	}
+
+	/// Returns the size in bytes the project directory would have been after saving.
+	pub const fn size(&self) -> u64 {
+		self.size
+	}
+}
+
+impl std::fmt::Display for QuotaExceeded {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "disk: saving would grow project '{}' to {} bytes, over its quota of {} bytes", self.project, self.size, self.quota)
+	}
+}
+
+impl std::error::Error for QuotaExceeded {}
+
+//---------------------------------------------------------------------------------------------------- Private
+// Returns the total size of all files in `dir`, recursively.
+//
+// A missing `dir` is `0` bytes.
+pub(crate) fn dir_size(dir: &Path) -> Result<u64, anyhow::Error> {
+	if !dir.try_exists()? {
+		return Ok(0);
+	}
+
+	let mut files = vec![];
+	crate::common::walk(dir, &mut files)?;
+	Ok(files.iter().map(|f| crate::common::filesize(f)).sum())
+}
+
+// Error if replacing `path` with `len` bytes would grow the project directory `dir()` over the quota of `project`.
+//
+// `dir()` is only called if `project` has a quota.
+pub(crate) fn ensure(
+	project: &str,
+	dir: fn() -> Result<PathBuf, anyhow::Error>,
+	path: &Path,
+	len: u64,
+) -> Result<(), anyhow::Error> {
+	let Some(quota) = quota(project) else {
+		return Ok(());
+	};
+
+	let dir      = dir()?;
+	let replaced = crate::common::filesize(path);
+
+	let mut usage = USAGE.lock().unwrap_or_else(|e| e.into_inner());
+	let known = match usage.get(&dir) {
+		Some(size) => *size,
+		None => dir_size(&dir)?,
+	};
+
+	let mut size = (known + len).saturating_sub(replaced);
+	if size > quota {
+		// The known size could be stale, measure again.
+		let measured = dir_size(&dir)?;
+		size = (measured + len).saturating_sub(replaced);
+		if size > quota {
+			usage.insert(dir, measured);
+			return Err(QuotaExceeded { project: project.to_string(), quota, size }.into());
+		}
+	}
+
+	usage.insert(dir, size);
+	Ok(())
+}
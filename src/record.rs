@@ -0,0 +1,103 @@
+//---------------------------------------------------------------------------------------------------- Use
+use anyhow::anyhow;
+use std::io::{Read,Seek,SeekFrom};
+use crate::common;
+
+//---------------------------------------------------------------------------------------------------- Record
+// The record framing shared by journals and audit logs:
+//
+// | record length (u32) | record crc32 (u32) | record bytes | record length (u32) |
+//
+// The trailing length allows checking the last record
+// without reading the whole file on every append.
+//
+// All integers are little endian.
+pub(crate) const OVERHEAD: u64 = 12;
+
+// Frame `bytes` into a record.
+pub(crate) fn encode(bytes: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+	let len = u32::try_from(bytes.len()).map_err(|_| anyhow!("record of {} bytes is too large", bytes.len()))?;
+
+	let mut frame = Vec::with_capacity(OVERHEAD as usize + bytes.len());
+	frame.extend_from_slice(&len.to_le_bytes());
+	frame.extend_from_slice(&common::crc32(bytes).to_le_bytes());
+	frame.extend_from_slice(bytes);
+	frame.extend_from_slice(&len.to_le_bytes());
+	Ok(frame)
+}
+
+// Split a record header into its length and crc32.
+pub(crate) fn split_header(header: [u8; 8]) -> (u32, u32) {
+	(
+		u32::from_le_bytes([header[0], header[1], header[2], header[3]]),
+		u32::from_le_bytes([header[4], header[5], header[6], header[7]]),
+	)
+}
+
+// Decode the first record of `bytes`, returns the record and the bytes after it.
+//
+// `None` if there are no records left, or the record is torn or corrupted.
+pub(crate) fn decode(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+	let header = bytes.get(..8)?;
+	let (len, crc) = split_header([header[0], header[1], header[2], header[3], header[4], header[5], header[6], header[7]]);
+	let end = 8 + len as usize;
+
+	let record  = bytes.get(8..end)?;
+	let trailer = bytes.get(end..end + 4)?;
+	if common::crc32(record) != crc || trailer != len.to_le_bytes() {
+		return None;
+	}
+
+	Some((record, &bytes[end + 4..]))
+}
+
+// If the record in `file` (of length `len`) at `start` is whole and matches its checksum, return its end.
+pub(crate) fn check(file: &mut std::fs::File, start: u64, len: u64) -> Result<Option<u64>, anyhow::Error> {
+	if start + OVERHEAD > len {
+		return Ok(None);
+	}
+
+	let mut header = [0; 8];
+	file.seek(SeekFrom::Start(start))?;
+	file.read_exact(&mut header)?;
+	let (record_len, crc) = split_header(header);
+	let end = start + OVERHEAD + u64::from(record_len);
+	if end > len {
+		return Ok(None);
+	}
+
+	let mut bytes = vec![0; record_len as usize + 4];
+	file.read_exact(&mut bytes)?;
+	let trailer = bytes.split_off(record_len as usize);
+	match common::crc32(&bytes) == crc && trailer == record_len.to_le_bytes() {
+		true  => Ok(Some(end)),
+		false => Ok(None),
+	}
+}
+
+// The end of the last whole record in `file` (of length `len`), the first record is at `first`.
+pub(crate) fn valid_end(file: &mut std::fs::File, first: u64, len: u64) -> Result<u64, anyhow::Error> {
+	if len <= first {
+		return Ok(first);
+	}
+
+	// Fast path, check only the last record via its trailing length.
+	if len >= first + OVERHEAD {
+		let mut trailer = [0; 4];
+		file.seek(SeekFrom::Start(len - 4))?;
+		file.read_exact(&mut trailer)?;
+		let record_len = u64::from(u32::from_le_bytes(trailer));
+		if let Some(start) = len.checked_sub(OVERHEAD + record_len) {
+			if start >= first && check(file, start, len)? == Some(len) {
+				return Ok(len);
+			}
+		}
+	}
+
+	// The last record is torn, find the end of the last whole one.
+	let mut offset = first;
+	while let Some(end) = check(file, offset, len)? {
+		offset = end;
+	}
+	Ok(offset)
+}
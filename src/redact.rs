@@ -0,0 +1,55 @@
+//---------------------------------------------------------------------------------------------------- Redact
+/// Keep sensitive fields out of saved files.
+///
+/// If your type implements this trait _and_ one of the `disk` traits via macro:
+/// - `save_redacted()` saves a copy of [`Self`] after [`Redact::redact()`]
+/// - `from_file_redacted()` loads the file, then calls [`Redact::fill()`]
+///
+/// The in-memory data is never modified by `save_redacted()`.
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::json!(Account, Dir::Data, "disk_test", "redact", "account");
+/// #[derive(Serialize,Deserialize,Clone)]
+/// struct Account {
+///     user: String,
+///     password: String,
+/// }
+///
+/// impl disk::Redact for Account {
+///     fn redact(&mut self) {
+///         self.password = "<redacted>".into();
+///     }
+///
+///     fn fill(&mut self) -> Result<(), disk::Error> {
+///         // e.g. from the OS keyring.
+///         self.password = "hunter2".into();
+///         Ok(())
+///     }
+/// }
+///
+/// let account = Account { user: "alice".into(), password: "hunter2".into() };
+/// account.save_redacted().unwrap();
+///
+/// // The file never contains the password.
+/// let file = std::fs::read_to_string(Account::absolute_path().unwrap()).unwrap();
+/// assert!(!file.contains("hunter2"));
+/// assert_eq!(account.password, "hunter2");
+///
+/// // It is filled back in on load.
+/// assert_eq!(Account::from_file_redacted().unwrap().password, "hunter2");
+/// assert_eq!(Account::from_file().unwrap().password, "<redacted>");
+/// # Account::rm_sub().unwrap();
+/// ```
+pub trait Redact: Clone {
+	/// Replace the sensitive fields, this is called on a copy of [`Self`] before saving.
+	fn redact(&mut self);
+
+	/// Fill in the redacted fields after loading.
+	///
+	/// The default implementation does nothing.
+	fn fill(&mut self) -> Result<(), anyhow::Error> {
+		Ok(())
+	}
+}
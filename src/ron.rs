@@ -8,27 +8,114 @@ use std::io::{
 };
 
 //---------------------------------------------------------------------------------------------------- Ron
-crate::common::impl_macro!(Ron, "ron");
+crate::common::impl_macro!(Ron, "ron", {
+	($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, pretty = $pretty:expr, options = $options:expr) => {
+		$crate::ron!($data, $dir, $project_directory, $sub_directories, $file_name, ext = "ron", pretty = $pretty, options = $options);
+	};
+	($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, ext = $ext:expr, pretty = $pretty:expr, options = $options:expr) => {
+		$crate::assert_str!($project_directory, $sub_directories, $file_name, $ext);
+		$crate::assert_dir!($dir);
+		$crate::assert_ext!($ext);
+
+		// SAFETY: The input to this `Ron` implementation was verified and sanity-checked via macro.
+		unsafe impl $crate::Ron for $data {
+			const OS_DIRECTORY:       $crate::Dir  = $dir;
+			const PROJECT_DIRECTORY:  &'static str = $project_directory;
+			const SUB_DIRECTORIES:    &'static str = $sub_directories;
+			const FILE:               &'static str = $file_name;
+			const FILE_EXT:           &'static str = $ext;
+			const FILE_NAME:          &'static str = $crate::const_format!("{}.{}", $file_name, $ext);
+			const FILE_NAME_GZIP:     &'static str = $crate::const_format!("{}.{}.gz", $file_name, $ext);
+			const FILE_NAME_TMP:      &'static str = $crate::const_format!("{}.{}.tmp", $file_name, $ext);
+			const FILE_NAME_GZIP_TMP: &'static str = $crate::const_format!("{}.{}.gz.tmp", $file_name, $ext);
+			const READ_ONLY:          bool         = { #[allow(unused_imports)] use $crate::__ReadOnlyNo; $crate::__ReadOnlyProbe::<Self>::YES };
+			const WRITE_ONCE:         bool         = { #[allow(unused_imports)] use $crate::__WriteOnceNo; $crate::__WriteOnceProbe::<Self>::YES };
+			$crate::__text_options!(Ron);
+
+			#[inline(always)]
+			fn __validate(&self) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__ValidateYes,__ValidateNo};
+				(&$crate::__ValidateProbe(self)).__disk_validate()
+			}
+
+			#[inline(always)]
+			fn __journal(&mut self) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__JournalYes,__JournalNo};
+				(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal(self, Self::absolute_path)
+			}
+
+			#[inline(always)]
+			fn __journal_reset(metadata: &$crate::Metadata) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__JournalYes,__JournalNo};
+				(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal_reset(metadata, Self::absolute_path)
+			}
+
+			#[inline(always)]
+			fn __pretty_config() -> $crate::__ron::ser::PrettyConfig {
+				$pretty
+			}
+
+			#[inline(always)]
+			fn __options() -> $crate::__ron::Options {
+				$options
+			}
+		}
+	};
+});
 
 /// [`RON`](https://docs.rs/ron) file format
 ///
 /// The encoding options used is:
 /// ```rust
 /// ron::ser::PrettyConfig::new();
+/// ron::Options::default();
 /// ```
 ///
+/// These can be changed per type with trailing `pretty = ...` and `options = ...` arguments to [`crate::ron!`]:
+/// ```rust,ignore
+/// use ron::{extensions::Extensions, ser::PrettyConfig, Options};
+///
+/// disk::ron!(
+///     State, disk::Dir::Data, "MyProject", "", "state",
+///     // Write `State(..)` instead of `(..)`.
+///     pretty  = PrettyConfig::new().struct_names(true),
+///     // Read and write `Some(x)` as `x` without a `#![enable(...)]` header.
+///     options = Options::default().with_default_extension(Extensions::IMPLICIT_SOME | Extensions::UNWRAP_NEWTYPES)
+/// );
+/// ```
+///
+/// Extensions set with `PrettyConfig::extensions()` are written as a `#![enable(...)]` header
+/// instead, which is always read back, even without `options`.
+///
 /// File extension is `.ron`.
 ///
 /// ## Safety
 /// When manually implementing, you are **promising** that the `PATH`'s manually specified are correct.
 pub unsafe trait Ron: serde::Serialize + serde::de::DeserializeOwned {
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. The config used when serializing.
+	fn __pretty_config() -> ron::ser::PrettyConfig {
+		ron::ser::PrettyConfig::new()
+	}
+
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. The options used when serializing and deserializing.
+	fn __options() -> ron::Options {
+		ron::Options::default()
+	}
+
 	#[doc(hidden)]
 	#[inline(always)]
 	/// Internal function. Most efficient `from_file()` impl.
 	fn __from_file() -> Result <Self, anyhow::Error> {
 		let path = Self::absolute_path()?;
 		let file = std::fs::File::open(path)?;
-		Ok(ron::de::from_reader(BufReader::new(file))?)
+		Ok(Self::__options().from_reader(BufReader::new(file))?)
 	}
 
 	#[doc(hidden)]
@@ -36,7 +123,7 @@ pub unsafe trait Ron: serde::Serialize + serde::de::DeserializeOwned {
 	/// Internal function. Most efficient `from_path()` impl.
 	fn __from_path(path: &std::path::Path) -> Result<Self, anyhow::Error> {
 		let file = std::fs::File::open(path)?;
-		Ok(ron::de::from_reader(BufReader::new(file))?)
+		Ok(Self::__options().from_reader(BufReader::new(file))?)
 	}
 
 	// Required functions for generic-ness.
@@ -46,13 +133,22 @@ pub unsafe trait Ron: serde::Serialize + serde::de::DeserializeOwned {
 	/// This uses [`ron::ser::to_writer_pretty`];
 	fn to_bytes(&self) -> Result<Vec<u8>, anyhow::Error> {
 		let mut vec = vec![];
-		ron::ser::to_writer_pretty(&mut vec, self, ron::ser::PrettyConfig::new())?;
+		Self::__options().to_writer_pretty(&mut vec, self, Self::__pretty_config())?;
 		Ok(vec)
 	}
 	#[inline(always)]
 	/// Create [`Self`] from bytes.
 	fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
-		common::convert_error(ron::de::from_bytes(bytes))
+		common::convert_error(Self::__options().from_bytes(bytes))
+	}
+
+	#[inline(always)]
+	/// Same as [`Self::from_bytes`] but errors on unknown fields.
+	fn from_bytes_strict(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+		let mut de = ron::Deserializer::from_bytes_with_options(bytes, Self::__options())?;
+		let s = common::deserialize_strict(&mut de)?;
+		de.end()?;
+		Ok(s)
 	}
 
 	// JSON operations.
@@ -61,16 +157,23 @@ pub unsafe trait Ron: serde::Serialize + serde::de::DeserializeOwned {
 	///
 	/// This uses [`ron::ser::to_string_pretty`];
 	fn to_string(&self) -> Result<String, anyhow::Error> {
-		common::convert_error(ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new()))
+		Self::__to_string(self)
+	}
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. Serialize any `value` the same way as [`Self::to_string()`].
+	fn __to_string<V: serde::Serialize>(value: &V) -> Result<String, anyhow::Error> {
+		common::convert_error(Self::__options().to_string_pretty(value, Self::__pretty_config()))
 	}
 	#[inline(always)]
 	/// Create [`Self`] from a [`String`].
 	fn from_string(string: &str) -> Result<Self, anyhow::Error> {
-		common::convert_error(ron::de::from_str(string))
+		common::convert_error(Self::__options().from_str(string))
 	}
 
 	// Common functions.
-	common::impl_string!("ron");
+	common::impl_strict!();
+	common::impl_string!("ron", "//");
 }
 
 //---------------------------------------------------------------------------------------------------- TESTS
@@ -0,0 +1,187 @@
+//---------------------------------------------------------------------------------------------------- Use
+use serde::Serialize;
+use serde::ser::{
+	Serializer,SerializeSeq,SerializeTuple,SerializeTupleStruct,SerializeTupleVariant,
+	SerializeMap,SerializeStruct,SerializeStructVariant,
+};
+
+//---------------------------------------------------------------------------------------------------- Shape
+// Which parts of a serialized value are struct fields.
+//
+// Text formats serialize both structs and maps (`HashMap`, `BTreeMap`) as
+// maps, so filling in missing keys from `Default` must know which is which:
+// missing struct fields are filled in, but the entries of a map are the user's data,
+// an entry deleted from a map must stay deleted.
+//
+// Newtype structs and `Some` are transparent (like in `serde_json`), everything
+// else (maps, sequences, enums, `#[serde(flatten)]` structs) is `Other`.
+#[derive(Debug)]
+pub(crate) enum Shape {
+	// A struct and the shape of each (serialized) field, by (renamed) name.
+	Struct(Vec<(&'static str, Shape)>),
+	// Anything else.
+	Other,
+}
+
+impl Shape {
+	// The shape of `value`.
+	pub(crate) fn of<T: Serialize + ?Sized>(value: &T) -> Self {
+		value.serialize(ShapeSerializer).unwrap_or(Self::Other)
+	}
+
+	// The shape of the field `key`, if this is a struct with that field.
+	pub(crate) fn field(&self, key: &str) -> Option<&Self> {
+		match self {
+			Self::Struct(fields) => fields.iter().find(|(k, _)| *k == key).map(|(_, s)| s),
+			Self::Other => None,
+		}
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Error
+#[derive(Debug)]
+pub(crate) struct Error;
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("shape error")
+	}
+}
+
+impl std::error::Error for Error {}
+
+impl serde::ser::Error for Error {
+	fn custom<T: std::fmt::Display>(_: T) -> Self {
+		Self
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Serializer
+struct ShapeSerializer;
+
+// Serializes structs into their `Shape`.
+struct StructShape(Vec<(&'static str, Shape)>);
+
+// Ignores everything, the shape is `Other`.
+struct OtherShape;
+
+macro_rules! other {
+	($($fn:ident($($ty:ty),*)),* $(,)?) => {
+		$(
+			fn $fn(self, $(_: $ty),*) -> Result<Shape, Error> {
+				Ok(Shape::Other)
+			}
+		)*
+	};
+}
+
+impl Serializer for ShapeSerializer {
+	type Ok                     = Shape;
+	type Error                  = Error;
+	type SerializeSeq           = OtherShape;
+	type SerializeTuple         = OtherShape;
+	type SerializeTupleStruct   = OtherShape;
+	type SerializeTupleVariant  = OtherShape;
+	type SerializeMap           = OtherShape;
+	type SerializeStruct        = StructShape;
+	type SerializeStructVariant = OtherShape;
+
+	other! {
+		serialize_bool(bool),
+		serialize_i8(i8), serialize_i16(i16), serialize_i32(i32), serialize_i64(i64), serialize_i128(i128),
+		serialize_u8(u8), serialize_u16(u16), serialize_u32(u32), serialize_u64(u64), serialize_u128(u128),
+		serialize_f32(f32), serialize_f64(f64),
+		serialize_char(char),
+		serialize_str(&str),
+		serialize_bytes(&[u8]),
+		serialize_none(),
+		serialize_unit(),
+		serialize_unit_struct(&'static str),
+		serialize_unit_variant(&'static str, u32, &'static str),
+	}
+
+	fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Shape, Error> {
+		value.serialize(self)
+	}
+
+	fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _: &'static str, value: &T) -> Result<Shape, Error> {
+		value.serialize(self)
+	}
+
+	fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> Result<Shape, Error> {
+		Ok(Shape::Other)
+	}
+
+	fn serialize_seq(self, _: Option<usize>) -> Result<OtherShape, Error> {
+		Ok(OtherShape)
+	}
+
+	fn serialize_tuple(self, _: usize) -> Result<OtherShape, Error> {
+		Ok(OtherShape)
+	}
+
+	fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<OtherShape, Error> {
+		Ok(OtherShape)
+	}
+
+	fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<OtherShape, Error> {
+		Ok(OtherShape)
+	}
+
+	fn serialize_map(self, _: Option<usize>) -> Result<OtherShape, Error> {
+		Ok(OtherShape)
+	}
+
+	fn serialize_struct(self, _: &'static str, len: usize) -> Result<StructShape, Error> {
+		Ok(StructShape(Vec::with_capacity(len)))
+	}
+
+	fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<OtherShape, Error> {
+		Ok(OtherShape)
+	}
+}
+
+impl SerializeStruct for StructShape {
+	type Ok    = Shape;
+	type Error = Error;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+		self.0.push((key, Shape::of(value)));
+		Ok(())
+	}
+
+	fn end(self) -> Result<Shape, Error> {
+		Ok(Shape::Struct(self.0))
+	}
+}
+
+// Implement all the compound traits for `OtherShape`, ignoring the elements.
+macro_rules! impl_other {
+	($($trait:ident { $($fn:ident($($arg:ident: $ty:ty),*)),* }),* $(,)?) => {
+		$(
+			impl $trait for OtherShape {
+				type Ok    = Shape;
+				type Error = Error;
+
+				$(
+					fn $fn<T: Serialize + ?Sized>(&mut self, $(_: $ty),*) -> Result<(), Error> {
+						Ok(())
+					}
+				)*
+
+				fn end(self) -> Result<Shape, Error> {
+					Ok(Shape::Other)
+				}
+			}
+		)*
+	};
+}
+
+impl_other! {
+	SerializeSeq           { serialize_element(value: &T) },
+	SerializeTuple         { serialize_element(value: &T) },
+	SerializeTupleStruct   { serialize_field(value: &T) },
+	SerializeTupleVariant  { serialize_field(value: &T) },
+	SerializeMap           { serialize_key(key: &T), serialize_value(value: &T) },
+	SerializeStructVariant { serialize_field(key: &'static str, value: &T) },
+}
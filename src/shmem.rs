@@ -0,0 +1,119 @@
+//---------------------------------------------------------------------------------------------------- Use
+use anyhow::bail;
+use std::path::{Path,PathBuf};
+use std::sync::atomic::{AtomicU64,Ordering};
+use crate::{common,Dir};
+
+//---------------------------------------------------------------------------------------------------- Shared memory
+// Shared memory regions are POSIX shared memory objects on Linux, which
+// are files in the `/dev/shm` tmpfs (this is what `shm_open()` uses), e.g:
+//
+// /dev/shm/disk-0123456789abcdef // <- `shm_open("/disk-0123456789abcdef")`
+//
+// The name is a hash of the file's normal PATH, so it is unique per file and user.
+//
+// Elsewhere (or without `/dev/shm`) they are normal files in `Dir::Runtime`.
+//
+// Regions are replaced with a rename, never modified in-place, so
+// readers (and existing `mmap()`s) never see a partially written region.
+
+#[cfg(target_os = "linux")]
+// The directory of POSIX shared memory objects.
+const SHM: &str = "/dev/shm";
+
+// The shared memory region PATH of the file at `absolute`
+// (in `project`/`sub_directories`, named `file_name`).
+pub(crate) fn shmem_path(project: &str, sub_directories: &str, file_name: &str, absolute: &Path) -> Result<PathBuf, anyhow::Error> {
+	#[cfg(target_os = "linux")]
+	if Path::new(SHM).is_dir() {
+		let hash = fnv1a(absolute.as_os_str().as_encoded_bytes());
+		return Ok(Path::new(SHM).join(format!("disk-{hash:016x}")));
+	}
+	#[cfg(not(target_os = "linux"))]
+	let _ = absolute;
+
+	let mut path = common::get_projectdir(&Dir::Runtime, project)?;
+	sub_directories
+		.split_terminator(&['/', '\\'][..])
+		.filter(|dir| !dir.is_empty())
+		.for_each(|dir| path.push(dir));
+	path.push(file_name);
+	Ok(path)
+}
+
+// Temporary regions of this process are numbered, so threads don't share one.
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// Atomically replace the region at `path` with `bytes`.
+//
+// The region is only readable by the current user.
+pub(crate) fn save(path: &Path, bytes: &[u8]) -> Result<(), anyhow::Error> {
+	use std::io::Write;
+
+	if let Some(parent) = path.parent() {
+		std::fs::create_dir_all(parent)?;
+	}
+
+	// Unique per process and call, other processes and threads may be saving at the same time.
+	let mut tmp = path.to_path_buf().into_os_string();
+	tmp.push(format!(".{}.{}.tmp", std::process::id(), TMP_COUNTER.fetch_add(1, Ordering::Relaxed)));
+	let tmp = PathBuf::from(tmp);
+
+	// Never open an existing file, it may belong to (and be readable by) someone else.
+	let mut options = std::fs::OpenOptions::new();
+	options.write(true).create_new(true);
+	#[cfg(unix)]
+	std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+	// Write to TMP.
+	let mut file = options.open(&tmp)?;
+	if let Err(e) = file.write_all(bytes) {
+		std::fs::remove_file(&tmp)?;
+		bail!(e);
+	}
+	drop(file);
+
+	// Rename TMP to normal.
+	if let Err(e) = std::fs::rename(&tmp, path) {
+		std::fs::remove_file(&tmp)?;
+		bail!(e);
+	}
+
+	Ok(())
+}
+
+// Open the region at `path` for reading.
+//
+// On Unix, this errors if the region is not owned by the current user
+// or is writable by others, since `/dev/shm` is shared by all users.
+pub(crate) fn open(path: &Path) -> Result<std::fs::File, anyhow::Error> {
+	let file = std::fs::File::open(path)?;
+
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::MetadataExt;
+		let metadata = file.metadata()?;
+		// SAFETY: `geteuid()` has no preconditions and always succeeds.
+		let uid = unsafe { libc::geteuid() };
+		if metadata.uid() != uid {
+			bail!("disk: shared memory region {path:?} is owned by another user ({}), refusing to read it", metadata.uid());
+		}
+		if metadata.mode() & 0o022 != 0 {
+			bail!("disk: shared memory region {path:?} is writable by other users, refusing to read it");
+		}
+	}
+
+	Ok(file)
+}
+
+#[cfg(target_os = "linux")]
+// 64-bit FNV-1a, stable across Rust versions unlike `DefaultHasher`,
+// so that different builds of the same app find the same region.
+fn fnv1a(bytes: &[u8]) -> u64 {
+	let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+	for b in bytes {
+		hash ^= u64::from(*b);
+		hash = hash.wrapping_mul(0x0100_0000_01b3);
+	}
+	hash
+}
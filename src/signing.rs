@@ -0,0 +1,60 @@
+//---------------------------------------------------------------------------------------------------- Use
+use anyhow::{anyhow,bail};
+use ed25519_dalek::{Signature,Signer,SigningKey,VerifyingKey,SIGNATURE_LENGTH};
+use std::path::{Path,PathBuf};
+
+//---------------------------------------------------------------------------------------------------- Signing
+// Signatures are detached, stored in a sidecar file next to the
+// file with a `.sig` suffix, e.g. `preset.toml` -> `preset.toml.sig`.
+//
+// The sidecar is the raw 64 byte `ed25519` signature of the file's exact bytes.
+
+// The signature PATH of the file at `path`.
+pub(crate) fn signature_path(path: &Path) -> PathBuf {
+	let mut sig = path.to_path_buf().into_os_string();
+	sig.push(".sig");
+	PathBuf::from(sig)
+}
+
+// Sign `bytes` and save the signature for the file at `path` atomically.
+pub(crate) fn save_signature(path: &Path, bytes: &[u8], key: &SigningKey) -> Result<(), anyhow::Error> {
+	use std::io::Write;
+
+	let signature = key.sign(bytes).to_bytes();
+	let sig = signature_path(path);
+
+	let mut tmp = sig.clone().into_os_string();
+	tmp.push(".tmp");
+	let tmp = PathBuf::from(tmp);
+
+	// Write to TMP.
+	if let Err(e) = crate::common::file_bufw!(&tmp, SIGNATURE_LENGTH).write_all(&signature) {
+		std::fs::remove_file(&tmp)?;
+		bail!(e);
+	}
+
+	// Rename TMP to normal.
+	if let Err(e) = std::fs::rename(&tmp, &sig) {
+		std::fs::remove_file(&tmp)?;
+		bail!(e);
+	}
+
+	Ok(())
+}
+
+// Verify `bytes` (read from `path`) against its signature file.
+pub(crate) fn verify(path: &Path, bytes: &[u8], key: &VerifyingKey) -> Result<(), anyhow::Error> {
+	let sig = signature_path(path);
+	let signature = match std::fs::read(&sig) {
+		Ok(s) => s,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => bail!("{:?} is not signed, {:?} does not exist", path, sig),
+		Err(e) => return Err(e.into()),
+	};
+
+	let signature: [u8; SIGNATURE_LENGTH] = signature
+		.try_into()
+		.map_err(|_| anyhow!("{:?} is not a valid signature", sig))?;
+
+	key.verify_strict(bytes, &Signature::from_bytes(&signature))
+		.map_err(|_| anyhow!("{:?} failed signature verification", path))
+}
@@ -0,0 +1,21 @@
+//---------------------------------------------------------------------------------------------------- Use
+use serde::{Serialize,Deserialize};
+
+//---------------------------------------------------------------------------------------------------- Source
+/// Which file some data was read from.
+///
+/// This is returned by functions that try multiple files, e.g. `from_file_resilient()`.
+#[derive(Copy,Clone,Debug,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
+pub enum Source {
+	/// The associated file, e.g: `state.toml`
+	File,
+	/// The leftover temporary file from an atomic save, e.g: `state.toml.tmp`
+	Tmp,
+	/// The `gzip` variant, e.g: `state.toml.gz`
+	Gzip,
+	/// A historical version from `snapshot()` or `save_rotated()` (see `list_history()`),
+	/// and the time in its file name, e.g: `state-2024-06-01T12-00-00.toml`
+	History(std::time::SystemTime),
+	/// The backup file, e.g: `state.toml.bak`
+	Backup,
+}
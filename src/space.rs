@@ -0,0 +1,170 @@
+//---------------------------------------------------------------------------------------------------- Use
+use std::path::{Path,PathBuf};
+use std::sync::RwLock;
+
+//---------------------------------------------------------------------------------------------------- Free space
+/// Returns the amount of bytes available to this process on the filesystem containing `path`.
+///
+/// This is `statvfs()` on Unix and `GetDiskFreeSpaceExW()` on Windows,
+/// other platforms error.
+///
+/// ```rust
+/// let free = disk::free_space(&std::env::temp_dir()).unwrap();
+/// assert!(free > 0);
+/// ```
+pub fn free_space(path: &Path) -> Result<u64, anyhow::Error> {
+	#[cfg(unix)]
+	{
+		use std::os::unix::ffi::OsStrExt;
+		let path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+
+		// SAFETY: `path` is a valid C string, `stat` is only read if the call succeeded.
+		let stat = unsafe {
+			let mut stat: libc::statvfs = std::mem::zeroed();
+			if libc::statvfs(path.as_ptr(), &mut stat) != 0 {
+				return Err(std::io::Error::last_os_error().into());
+			}
+			stat
+		};
+
+		#[allow(clippy::unnecessary_cast)]
+		Ok((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+	}
+
+	#[cfg(windows)]
+	{
+		use std::os::windows::ffi::OsStrExt;
+		let path: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+
+		let mut available = 0_u64;
+		// SAFETY: `path` is a valid null-terminated wide string, the other outputs are optional.
+		if unsafe {
+			windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW(
+				path.as_ptr(),
+				&mut available,
+				std::ptr::null_mut(),
+				std::ptr::null_mut(),
+			)
+		} == 0 {
+			return Err(std::io::Error::last_os_error().into());
+		}
+		Ok(available)
+	}
+
+	#[cfg(not(any(unix, windows)))]
+	anyhow::bail!("disk: free space of {path:?} is unknown on this platform")
+}
+
+//---------------------------------------------------------------------------------------------------- Minimum free space
+// The minimum free space to leave when saving, if any.
+static MIN_FREE_SPACE: RwLock<Option<u64>> = RwLock::new(None);
+
+/// Make all `save*()` functions check the free disk space before writing, for your entire process.
+///
+/// When set, saving errors with [`InsufficientSpace`] (and writes nothing) if it
+/// would leave less than `bytes` free on the filesystem, instead of failing
+/// mid-write with `ENOSPC` (or leaving a half-written `.tmp` file).
+///
+/// `0` only checks that the data itself fits.
+///
+/// Platforms where the free space is unknown are never checked.
+///
+/// ```rust
+/// // Always leave 64 MiB free.
+/// disk::set_min_free_space(64 * 1024 * 1024);
+/// assert_eq!(disk::min_free_space(), Some(64 * 1024 * 1024));
+///
+/// disk::clear_min_free_space();
+/// assert_eq!(disk::min_free_space(), None);
+/// ```
+pub fn set_min_free_space(bytes: u64) {
+	*MIN_FREE_SPACE.write().unwrap_or_else(|e| e.into_inner()) = Some(bytes);
+}
+
+/// Opt-out of the check set with [`set_min_free_space()`].
+pub fn clear_min_free_space() {
+	*MIN_FREE_SPACE.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Returns the minimum set with [`set_min_free_space()`], if any.
+pub fn min_free_space() -> Option<u64> {
+	*MIN_FREE_SPACE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+//---------------------------------------------------------------------------------------------------- InsufficientSpace
+#[derive(Debug,Clone,PartialEq,Eq)]
+/// Error returned when there is not enough free disk space to save.
+///
+/// This is returned (wrapped in [`anyhow::Error`]) by `check_free_space()`,
+/// and by `save*()` functions if [`set_min_free_space()`] is set.
+/// It can be retrieved with [`anyhow::Error::downcast_ref`]:
+/// ```rust,ignore
+/// match State::save(&state) {
+///     Ok(metadata) => println!("{metadata}"),
+///     Err(e) => match e.downcast_ref::<disk::InsufficientSpace>() {
+///         Some(s) => println!("need {} bytes, only {} free", s.required(), s.available()),
+///         None    => println!("other error: {e}"),
+///     },
+/// }
+/// ```
+pub struct InsufficientSpace {
+	path: PathBuf,
+	required: u64,
+	available: u64,
+}
+
+impl InsufficientSpace {
+	/// Returns the PATH that was being written to.
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Returns the amount of bytes that needed to be free.
+	pub const fn required(&self) -> u64 {
+		self.required
+	}
+
+	/// Returns the amount of bytes that were free.
+	pub const fn available(&self) -> u64 {
+		self.available
+	}
+}
+
+impl std::fmt::Display for InsufficientSpace {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "disk: not enough free space for {:?}, {} bytes required, {} bytes available", self.path, self.required, self.available)
+	}
+}
+
+impl std::error::Error for InsufficientSpace {}
+
+//---------------------------------------------------------------------------------------------------- Private
+// Error with `InsufficientSpace` if less than `required` bytes are free for `path`.
+//
+// `path` (or its parent) must exist.
+pub(crate) fn check(path: &Path, required: u64) -> Result<u64, anyhow::Error> {
+	let dir = match path.is_dir() {
+		true  => path,
+		false => path.parent().unwrap_or(path),
+	};
+
+	let available = free_space(dir)?;
+	if available < required {
+		return Err(InsufficientSpace { path: path.to_path_buf(), required, available }.into());
+	}
+	Ok(available)
+}
+
+// Check `set_min_free_space()` before writing `len` bytes to `path`.
+pub(crate) fn ensure(path: &Path, len: u64) -> Result<(), anyhow::Error> {
+	let Some(min) = min_free_space() else {
+		return Ok(());
+	};
+
+	match check(path, len.saturating_add(min)) {
+		Ok(_) => Ok(()),
+		Err(e) if e.is::<InsufficientSpace>() => Err(e),
+		// The free space is unknown.
+		Err(_) => Ok(()),
+	}
+}
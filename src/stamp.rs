@@ -0,0 +1,105 @@
+//---------------------------------------------------------------------------------------------------- Use
+use std::time::{SystemTime,UNIX_EPOCH};
+
+//---------------------------------------------------------------------------------------------------- Write stamp
+// What comes after the comment prefix in a stamp.
+const WRITTEN_BY: &str = " written by ";
+
+/// Append a provenance comment to a type's text file when saving.
+///
+/// If your type implements this trait _and_ one of the text `disk` traits via macro,
+/// its `WRITE_STAMP` constant is `Some(`[`WriteStamp::WRITER`]`)`, [`None`] by default.
+///
+/// Saving a format that supports comments ([`crate::Toml`], [`crate::Yaml`], [`crate::Ron`])
+/// then appends a comment line like:
+/// ```text
+/// # written by MyApp 1.4.2 at 2024-06-01T12:00Z
+/// ```
+/// The time is UTC. A stamp already at the end of the file is replaced, not repeated.
+///
+/// Formats without comments ([`crate::Json`], binary formats, etc) are never stamped.
+///
+/// Stamps are comments, so loading ignores them, and `diff_file()` does not report them.
+///
+/// Line breaks in the writer are replaced with spaces.
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::toml!(Settings, Dir::Data, "disk_test", "stamp", "settings");
+/// #[derive(Serialize,Deserialize)]
+/// struct Settings {
+///     volume: u8,
+/// }
+///
+/// impl disk::WriteStamp for Settings {
+///     const WRITER: &'static str = concat!("MyApp ", "1.4.2");
+/// }
+///
+/// assert_eq!(Settings::WRITE_STAMP, Some("MyApp 1.4.2"));
+///
+/// Settings { volume: 0 }.save().unwrap();
+/// assert!(Settings::read_to_string().unwrap().starts_with("volume = 0\n# written by MyApp 1.4.2 at "));
+/// assert_eq!(Settings::from_file().unwrap().volume, 0);
+/// # Settings::rm_sub().unwrap();
+/// ```
+///
+/// Manual `unsafe impl`'s must set `const WRITE_STAMP: Option<&'static str>` themselves.
+pub trait WriteStamp {
+	/// Who is writing the file, e.g. the application's name and version.
+	const WRITER: &'static str;
+}
+
+// Same as `__ReadOnlyProbe`, `__WriteStampProbe::<T>::WRITE_STAMP` resolves
+// to `Some(WriteStamp::WRITER)` if implemented, else `__WriteStampNo::WRITE_STAMP` (`None`).
+#[doc(hidden)]
+pub struct __WriteStampProbe<T>(std::marker::PhantomData<T>);
+
+impl<T: WriteStamp> __WriteStampProbe<T> {
+	pub const WRITE_STAMP: Option<&'static str> = Some(T::WRITER);
+}
+
+#[doc(hidden)]
+pub trait __WriteStampNo {
+	const WRITE_STAMP: Option<&'static str> = None;
+}
+
+impl<T> __WriteStampNo for __WriteStampProbe<T> {}
+
+// Append a stamp by `writer` to `string` (replacing any existing one), if there is a `writer`.
+//
+// `comment` is the format's line comment prefix, e.g. `#`.
+pub(crate) fn apply(comment: &str, writer: Option<&str>, string: String) -> String {
+	let Some(writer) = writer else {
+		return string;
+	};
+	let writer = writer.replace(['\r', '\n'], " ");
+
+	let mut string = strip(comment, &string).to_string();
+	if !string.is_empty() && !string.ends_with('\n') {
+		string.push('\n');
+	}
+	string.push_str(&format!("{comment}{WRITTEN_BY}{writer} at {}\n", timestamp(SystemTime::now())));
+	string
+}
+
+// Remove a trailing stamp from `string`, if any.
+pub(crate) fn strip<'a>(comment: &str, string: &'a str) -> &'a str {
+	let body  = string.trim_end_matches(['\r', '\n']);
+	let start = body.rfind('\n').map_or(0, |i| i + 1);
+
+	match body[start..].strip_prefix(comment) {
+		Some(rest) if rest.starts_with(WRITTEN_BY) => &string[..start],
+		_ => string,
+	}
+}
+
+// Format `time` as `YYYY-MM-DDTHH:MMZ` (UTC).
+fn timestamp(time: SystemTime) -> String {
+	let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+	let days = (secs / 86400) as i64;
+	let secs = secs % 86400;
+
+	let (y, m, d) = crate::history::civil_from_days(days);
+	format!("{y:04}-{m:02}-{d:02}T{:02}:{:02}Z", secs / 3600, secs % 3600 / 60)
+}
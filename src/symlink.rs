@@ -0,0 +1,180 @@
+//---------------------------------------------------------------------------------------------------- Use
+use anyhow::bail;
+use std::path::{Path,PathBuf};
+use std::sync::RwLock;
+
+//---------------------------------------------------------------------------------------------------- SymlinkPolicy
+// The current symlink policy, if any.
+static SYMLINK_POLICY: RwLock<Option<SymlinkPolicy>> = RwLock::new(None);
+
+/// What saves and removals do when the file itself is a symlink.
+///
+/// This is common with dotfile managers (`stow`, `chezmoi`, etc) that
+/// symlink `~/.config/myproject/config.toml` to a file in a git repository.
+///
+/// Without a policy (the default), `disk` does not check for symlinks, which means:
+/// - `save()` writes _through_ the symlink, to its target
+/// - `save_atomic()` renames over the symlink, replacing it with a regular file
+/// - `rm()` removes the symlink, not its target
+///
+/// Only the final component (the file) is affected, symlinked directories are always followed.
+///
+/// ```rust
+/// use disk::SymlinkPolicy;
+///
+/// disk::set_symlink_policy(SymlinkPolicy::Follow);
+/// assert_eq!(disk::symlink_policy(), Some(SymlinkPolicy::Follow));
+///
+/// disk::clear_symlink_policy();
+/// assert_eq!(disk::symlink_policy(), None);
+/// ```
+#[derive(Copy,Clone,Debug,Hash,PartialEq,Eq,PartialOrd,Ord)]
+pub enum SymlinkPolicy {
+	/// Save and remove the symlink's target, the symlink is kept.
+	///
+	/// Atomic saves write the `.tmp` file next to the target, so the rename never breaks the symlink.
+	///
+	/// After `rm()` the symlink is left dangling, the next save creates the target again:
+	/// ```rust
+	/// # #[cfg(unix)] {
+	/// # use serde::{Serialize,Deserialize};
+	/// # use disk::*;
+	/// disk::toml!(Linked, Dir::Data, "disk_test", "symlink_follow", "linked");
+	/// #[derive(Serialize,Deserialize)]
+	/// struct Linked {
+	///     volume: u8,
+	/// }
+	///
+	/// // `linked.toml` -> `target.toml`
+	/// let link   = Linked::absolute_path().unwrap();
+	/// let target = link.with_file_name("target.toml");
+	/// # let _ = Linked::rm_sub();
+	/// Linked::mkdir().unwrap();
+	/// std::os::unix::fs::symlink(&target, &link).unwrap();
+	///
+	/// disk::set_symlink_policy(SymlinkPolicy::Follow);
+	/// Linked { volume: 0 }.save().unwrap();
+	/// assert!(target.exists());
+	///
+	/// Linked::rm().unwrap();
+	/// assert!(!target.exists());
+	/// assert!(std::fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+	///
+	/// Linked { volume: 1 }.save().unwrap();
+	/// assert_eq!(Linked::from_path(&target).unwrap().volume, 1);
+	/// # Linked::rm_sub().unwrap();
+	/// # }
+	/// ```
+	Follow,
+	/// Replace or remove the symlink itself, the target is never touched.
+	Replace,
+	/// Return an error instead of saving or removing.
+	Deny,
+}
+
+/// Set the [`SymlinkPolicy`] of your entire process.
+pub fn set_symlink_policy(policy: SymlinkPolicy) {
+	*SYMLINK_POLICY.write().unwrap_or_else(|e| e.into_inner()) = Some(policy);
+}
+
+/// Opt-out of the [`SymlinkPolicy`] set with [`set_symlink_policy()`].
+pub fn clear_symlink_policy() {
+	*SYMLINK_POLICY.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Returns the [`SymlinkPolicy`] set with [`set_symlink_policy()`], if any.
+pub fn symlink_policy() -> Option<SymlinkPolicy> {
+	*SYMLINK_POLICY.read().unwrap_or_else(|e| e.into_inner())
+}
+
+//---------------------------------------------------------------------------------------------------- Private
+// Symlinks are followed up to this many times before erroring (the Linux limit).
+const MAX_HOPS: usize = 40;
+
+// If `path` is a symlink, return what the current policy says to do with it.
+fn resolve(path: &Path) -> Result<Option<(SymlinkPolicy, PathBuf)>, anyhow::Error> {
+	let Some(policy) = symlink_policy() else { return Ok(None) };
+	if !is_symlink(path) {
+		return Ok(None);
+	}
+
+	match policy {
+		SymlinkPolicy::Deny => bail!("disk: {path:?} is a symlink, denied by the symlink policy"),
+		SymlinkPolicy::Replace => Ok(Some((policy, path.to_path_buf()))),
+		SymlinkPolicy::Follow => {
+			// Follow the chain, the final target may not exist yet.
+			let mut target = path.to_path_buf();
+			for _ in 0..MAX_HOPS {
+				let link = std::fs::read_link(&target)?;
+				target = match target.parent() {
+					Some(parent) => parent.join(link),
+					None => link,
+				};
+				if !is_symlink(&target) {
+					return Ok(Some((policy, target)));
+				}
+			}
+			bail!("disk: {path:?} has too many levels of symlinks")
+		},
+	}
+}
+
+#[inline]
+// Returns `true` if `path` itself is a symlink.
+pub(crate) fn is_symlink(path: &Path) -> bool {
+	std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink())
+}
+
+// The PATH a (non-atomic) save to `path` should write to.
+pub(crate) fn save_path(path: PathBuf) -> Result<PathBuf, anyhow::Error> {
+	match resolve(&path)? {
+		None => Ok(path),
+		Some((SymlinkPolicy::Follow, target)) => Ok(target),
+		// Writing would go through the symlink, remove it first.
+		Some(_) => { std::fs::remove_file(&path)?; Ok(path) },
+	}
+}
+
+// The (PATH, TMP PATH) an atomic save to `path` should use.
+pub(crate) fn save_path_atomic(path: PathBuf, tmp: PathBuf) -> Result<(PathBuf, PathBuf), anyhow::Error> {
+	match resolve(&path)? {
+		Some((SymlinkPolicy::Follow, target)) => {
+			let mut tmp = target.clone().into_os_string();
+			tmp.push(".tmp");
+			Ok((target, PathBuf::from(tmp)))
+		},
+		// Renaming already replaces the symlink.
+		_ => Ok((path, tmp)),
+	}
+}
+
+// The PATH a removal of `path` should remove.
+//
+// If this is not `path`, `path` is a followed symlink, which is kept.
+pub(crate) fn rm_path(path: &Path) -> Result<PathBuf, anyhow::Error> {
+	match resolve(path)? {
+		Some((SymlinkPolicy::Follow, target)) => Ok(target),
+		_ => Ok(path.to_path_buf()),
+	}
+}
+
+// All symlinks in `path`, starting from (and including) `root`.
+pub(crate) fn symlinks(root: &Path, path: &Path) -> Vec<PathBuf> {
+	let mut vec = vec![];
+
+	let mut current = root.to_path_buf();
+	if is_symlink(&current) {
+		vec.push(current.clone());
+	}
+
+	if let Ok(rest) = path.strip_prefix(root) {
+		for component in rest.components() {
+			current.push(component);
+			if is_symlink(&current) {
+				vec.push(current.clone());
+			}
+		}
+	}
+
+	vec
+}
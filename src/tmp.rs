@@ -0,0 +1,207 @@
+//---------------------------------------------------------------------------------------------------- Use
+use serde::{Serialize,Deserialize};
+use std::path::{Path,PathBuf};
+
+//---------------------------------------------------------------------------------------------------- TmpFile
+/// What happened to a single `.tmp` file in [`RmTmpReport`].
+#[derive(Clone,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
+pub struct TmpFile {
+	/// The PATH of the `.tmp` file.
+	pub path: PathBuf,
+	/// If the file existed.
+	pub existed: bool,
+	/// The file size in bytes, `0` if it did not exist.
+	pub size: u64,
+	/// If the file was removed.
+	pub removed: bool,
+	/// The error if the file existed but could not be removed.
+	pub error: Option<String>,
+}
+
+impl TmpFile {
+	// Remove the `.tmp` file at `path` (if it exists) and record what happened.
+	pub(crate) fn remove(path: PathBuf) -> Self {
+		let size = match std::fs::metadata(&path) {
+			Ok(m) => m.len(),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self { path, ..Default::default() },
+			Err(e) => return Self { error: Some(e.to_string()), existed: true, path, ..Default::default() },
+		};
+
+		match std::fs::remove_file(&path) {
+			Ok(()) => Self { path, existed: true, size, removed: true, error: None },
+			// Removed by someone else in the meantime.
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self { path, existed: true, size, removed: false, error: None },
+			Err(e) => Self { path, existed: true, size, removed: false, error: Some(e.to_string()) },
+		}
+	}
+
+	#[inline]
+	/// Returns `true` if the file does not exist anymore.
+	pub fn is_ok(&self) -> bool {
+		self.error.is_none()
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- RmTmpReport
+/// The result of `rm_tmp_report()`.
+///
+/// Each `.tmp` file is handled independently,
+/// failing to remove one does not stop the others from being removed.
+#[derive(Clone,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
+pub struct RmTmpReport {
+	/// The `.tmp` file of `save_atomic()`.
+	pub tmp: TmpFile,
+	/// The `.gz.tmp` file of `save_atomic_gzip()`.
+	pub tmp_gzip: TmpFile,
+	/// The `.PID-NANOS.tmp` files left behind if `save_atomic()` or
+	/// `save_atomic_gzip()` were interrupted while using an `O_TMPFILE` on Linux.
+	#[serde(default)]
+	pub unnamed: Vec<TmpFile>,
+}
+
+impl RmTmpReport {
+	// Remove both `.tmp` files and the `unnamed` ones.
+	pub(crate) fn remove(tmp: PathBuf, tmp_gzip: PathBuf, unnamed: Vec<PathBuf>) -> Self {
+		Self {
+			tmp: TmpFile::remove(tmp),
+			tmp_gzip: TmpFile::remove(tmp_gzip),
+			unnamed: unnamed.into_iter().map(TmpFile::remove).collect(),
+		}
+	}
+
+	// All the files, in order.
+	fn files(&self) -> impl Iterator<Item = &TmpFile> {
+		[&self.tmp, &self.tmp_gzip].into_iter().chain(&self.unnamed)
+	}
+
+	#[inline]
+	/// Returns `true` if none of the `.tmp` files exist anymore.
+	pub fn is_ok(&self) -> bool {
+		self.files().all(TmpFile::is_ok)
+	}
+
+	/// The total amount of bytes removed.
+	pub fn removed_size(&self) -> u64 {
+		self.files()
+			.filter(|f| f.removed)
+			.map(|f| f.size)
+			.sum()
+	}
+
+	/// The PATHs that were removed.
+	pub fn removed(&self) -> Vec<&Path> {
+		self.files()
+			.filter(|f| f.removed)
+			.map(|f| f.path.as_path())
+			.collect()
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- O_TMPFILE
+// Atomically replace `path` with `bytes`, using an unnamed `O_TMPFILE` in its directory.
+//
+// The file only gets a name once it is fully written:
+// - If `path` doesn't exist, it is linked directly as `path`
+// - Else it is linked as a unique `path.PID-NANOS.tmp` name, then renamed over `path`
+//
+// so there is never a partially written `.tmp` file, and nothing is left behind on errors.
+// An interrupt between the link and rename leaves a (complete) `path.PID-NANOS.tmp`
+// file behind though, see `unnamed_tmp()`, `rm_tmp_report()` removes these.
+//
+// This returns `Ok(false)` (and nothing is written) if `O_TMPFILE` or
+// `/proc` are not supported, the caller should fall back to a normal `.tmp` file.
+#[cfg(target_os = "linux")]
+pub(crate) fn write_unnamed(path: &Path, bytes: &[u8]) -> Result<bool, anyhow::Error> {
+	use std::io::Write;
+	use std::os::unix::ffi::OsStrExt;
+	use std::os::unix::fs::OpenOptionsExt;
+	use std::os::unix::io::AsRawFd;
+
+	let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+		return Ok(false);
+	};
+
+	let mut file = match std::fs::OpenOptions::new()
+		.write(true)
+		.mode(0o666)
+		.custom_flags(libc::O_TMPFILE)
+		.open(dir)
+	{
+		Ok(file) => file,
+		// Unsupported kernel (`EISDIR`) or filesystem (`EOPNOTSUPP`).
+		Err(e) if matches!(e.raw_os_error(), Some(libc::EISDIR | libc::EOPNOTSUPP | libc::EINVAL)) => return Ok(false),
+		Err(e) => return Err(e.into()),
+	};
+	crate::common::preallocate(&file, bytes.len() as u64)?;
+	file.write_all(bytes)?;
+
+	// Give the file a name.
+	let fd = std::ffi::CString::new(format!("/proc/self/fd/{}", file.as_raw_fd()))?;
+	let link = |to: &Path| -> std::io::Result<()> {
+		let to = std::ffi::CString::new(to.as_os_str().as_bytes())?;
+		// SAFETY: both are valid C strings for the duration of the call.
+		match unsafe { libc::linkat(libc::AT_FDCWD, fd.as_ptr(), libc::AT_FDCWD, to.as_ptr(), libc::AT_SYMLINK_FOLLOW) } {
+			0 => Ok(()),
+			_ => Err(std::io::Error::last_os_error()),
+		}
+	};
+
+	match link(path) {
+		Ok(()) => return Ok(true),
+		Err(e) if e.raw_os_error() == Some(libc::EEXIST) => (),
+		// No `/proc`.
+		Err(e) if e.raw_os_error() == Some(libc::ENOENT) && dir.is_dir() => return Ok(false),
+		Err(e) => return Err(e.into()),
+	}
+
+	// `path` exists, link to a unique name and rename over it.
+	let nanos = std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.map_or(0, |d| d.subsec_nanos());
+	let mut tmp = name.to_os_string();
+	tmp.push(format!(".{}-{nanos}.tmp", std::process::id()));
+	let tmp = dir.join(tmp);
+
+	link(&tmp)?;
+	if let Err(e) = std::fs::rename(&tmp, path) {
+		std::fs::remove_file(&tmp)?;
+		return Err(e.into());
+	}
+
+	Ok(true)
+}
+
+// Is `name` a `file.PID-NANOS.tmp` name from `write_unnamed()`?
+fn is_unnamed_tmp(name: &str, file: &str) -> bool {
+	let Some(rest) = name.strip_prefix(file).and_then(|r| r.strip_prefix('.')).and_then(|r| r.strip_suffix(".tmp")) else {
+		return false;
+	};
+	let Some((pid, nanos)) = rest.split_once('-') else {
+		return false;
+	};
+	[pid, nanos].iter().all(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+// The `write_unnamed()` leftovers of any of `files` in `dir`.
+//
+// A missing `dir` is not an error.
+pub(crate) fn unnamed_tmp(dir: &Path, files: &[&str]) -> Result<Vec<PathBuf>, anyhow::Error> {
+	let read_dir = match std::fs::read_dir(dir) {
+		Ok(r) => r,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+		Err(e) => return Err(e.into()),
+	};
+
+	let mut paths = vec![];
+	for entry in read_dir {
+		let entry = entry?;
+		let name = entry.file_name();
+		let Some(name) = name.to_str() else { continue };
+		if files.iter().any(|file| is_unnamed_tmp(name, file)) {
+			paths.push(entry.path());
+		}
+	}
+
+	paths.sort();
+	Ok(paths)
+}
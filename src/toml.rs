@@ -6,15 +6,68 @@ use crate::common;
 //use serde::{Serialize,Deserialize};
 
 //---------------------------------------------------------------------------------------------------- Toml
-crate::common::impl_macro!(Toml, "toml");
+crate::common::impl_macro!(Toml, "toml", {
+	($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, style = $style:expr) => {
+		$crate::toml!($data, $dir, $project_directory, $sub_directories, $file_name, ext = "toml", style = $style);
+	};
+	($data:ty, $dir:expr, $project_directory:expr, $sub_directories:expr, $file_name:expr, ext = $ext:expr, style = $style:expr) => {
+		$crate::assert_str!($project_directory, $sub_directories, $file_name, $ext);
+		$crate::assert_dir!($dir);
+		$crate::assert_ext!($ext);
+
+		// SAFETY: The input to this `Toml` implementation was verified and sanity-checked via macro.
+		unsafe impl $crate::Toml for $data {
+			const OS_DIRECTORY:       $crate::Dir       = $dir;
+			const PROJECT_DIRECTORY:  &'static str      = $project_directory;
+			const SUB_DIRECTORIES:    &'static str      = $sub_directories;
+			const FILE:               &'static str      = $file_name;
+			const FILE_EXT:           &'static str      = $ext;
+			const FILE_NAME:          &'static str      = $crate::const_format!("{}.{}", $file_name, $ext);
+			const FILE_NAME_GZIP:     &'static str      = $crate::const_format!("{}.{}.gz", $file_name, $ext);
+			const FILE_NAME_TMP:      &'static str      = $crate::const_format!("{}.{}.tmp", $file_name, $ext);
+			const FILE_NAME_GZIP_TMP: &'static str      = $crate::const_format!("{}.{}.gz.tmp", $file_name, $ext);
+			const READ_ONLY:          bool              = { #[allow(unused_imports)] use $crate::__ReadOnlyNo; $crate::__ReadOnlyProbe::<Self>::YES };
+			const WRITE_ONCE:         bool              = { #[allow(unused_imports)] use $crate::__WriteOnceNo; $crate::__WriteOnceProbe::<Self>::YES };
+			const TOML_STYLE:         $crate::TomlStyle = $style;
+			$crate::__text_options!(Toml);
+
+			#[inline(always)]
+			fn __validate(&self) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__ValidateYes,__ValidateNo};
+				(&$crate::__ValidateProbe(self)).__disk_validate()
+			}
+
+			#[inline(always)]
+			fn __journal(&mut self) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__JournalYes,__JournalNo};
+				(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal(self, Self::absolute_path)
+			}
+
+			#[inline(always)]
+			fn __journal_reset(metadata: &$crate::Metadata) -> ::std::result::Result<(), $crate::Error> {
+				#[allow(unused_imports)]
+				use $crate::{__JournalYes,__JournalNo};
+				(&$crate::__JournalProbe::<Self>(::std::marker::PhantomData)).__disk_journal_reset(metadata, Self::absolute_path)
+			}
+		}
+	};
+});
 
 /// [`TOML`](https://docs.rs/toml_edit) file format
 ///
 /// File extension is `.toml`.
 ///
+/// ## Style
+/// How the file is written can be changed per type with a trailing `style = ...` argument to [`crate::toml!`], see [`TomlStyle`].
+///
 /// ## Safety
 /// When manually implementing, you are **promising** that the `PATH`'s manually specified are correct.
 pub unsafe trait Toml: serde::Serialize + serde::de::DeserializeOwned {
+	/// How [`Self`] is written, [`TomlStyle::PRETTY`] by default.
+	const TOML_STYLE: TomlStyle = TomlStyle::PRETTY;
+
 	#[doc(hidden)]
 	#[inline(always)]
 	/// Internal function. Most efficient `from_file()` impl.
@@ -38,25 +91,184 @@ pub unsafe trait Toml: serde::Serialize + serde::de::DeserializeOwned {
 	#[inline(always)]
 	/// Create [`Self`] from bytes.
 	fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
-		common::convert_error(toml_edit::de::from_slice(bytes))
+		if Self::TOML_STYLE.literal_datetimes {
+			let mut doc: toml_edit::Document = std::str::from_utf8(&common::decode_text(bytes)?)?.parse()?;
+			datetimes_to_strings(&mut doc);
+			return Ok(toml_edit::de::from_document(doc)?);
+		}
+		common::convert_error(toml_edit::de::from_slice(&common::decode_text(bytes)?))
+	}
+
+	#[inline(always)]
+	/// Same as [`Self::from_bytes`] but errors on unknown fields.
+	fn from_bytes_strict(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+		let bytes  = common::decode_text(bytes)?;
+		let mut doc: toml_edit::Document = std::str::from_utf8(&bytes)?.parse()?;
+		if Self::TOML_STYLE.literal_datetimes {
+			datetimes_to_strings(&mut doc);
+		}
+		common::deserialize_strict(toml_edit::de::Deserializer::new(doc))
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	/// Internal function. Create [`Self`] from bytes, filling missing fields from [`Default`].
+	///
+	/// Returns `true` if any fields were missing.
+	fn __from_bytes_upgraded(bytes: &[u8]) -> Result<(Self, bool), anyhow::Error>
+	where
+		Self: Default,
+	{
+		let mut doc: toml_edit::Document = std::str::from_utf8(&common::decode_text(bytes)?)?.parse()?;
+		if Self::TOML_STYLE.literal_datetimes {
+			datetimes_to_strings(&mut doc);
+		}
+		let default  = Self::default();
+		let shape    = crate::shape::Shape::of(&default);
+		let default  = toml_edit::ser::to_document(&default)?;
+		let upgraded = common::merge_toml(doc.as_table_mut(), default.as_table(), &shape);
+		Ok((toml_edit::de::from_document(doc)?, upgraded))
 	}
 
 	// TOML operations.
 	#[inline(always)]
 	/// Convert [`Self`] to a [`String`].
 	///
-	/// This uses [`toml_edit::ser::to_string_pretty`];
+	/// With the default [`Self::TOML_STYLE`], this uses [`toml_edit::ser::to_string_pretty`].
 	fn to_string(&self) -> Result<String, anyhow::Error> {
-		common::convert_error(toml_edit::ser::to_string_pretty(self))
+		Self::__to_string(self)
+	}
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. Serialize any `value` the same way as [`Self::to_string()`].
+	fn __to_string<V: serde::Serialize>(value: &V) -> Result<String, anyhow::Error> {
+		if Self::TOML_STYLE == TomlStyle::PRETTY {
+			return common::convert_error(toml_edit::ser::to_string_pretty(value));
+		}
+
+		let mut doc   = toml_edit::ser::to_document(value)?;
+		let mut style = Self::TOML_STYLE;
+		toml_edit::visit_mut::VisitMut::visit_document_mut(&mut style, &mut doc);
+		Ok(doc.to_string())
 	}
 	#[inline(always)]
 	/// Create [`Self`] from [`String`].
 	fn from_string(string: &str) -> Result<Self, anyhow::Error> {
-		common::convert_error(toml_edit::de::from_str(string))
+		Self::from_bytes(common::strip_bom(string).as_bytes())
 	}
 
 	// Common data/functions.
-	common::impl_string!("toml");
+	common::impl_strict!();
+	common::impl_upgrade!();
+	common::impl_merge!();
+	#[cfg(feature = "schemars")]
+	common::impl_schema!();
+	common::impl_string!("toml", "#");
+}
+
+//---------------------------------------------------------------------------------------------------- TomlStyle
+/// How a [`Toml`] type is written.
+///
+/// This only affects formatting, all styles read back the same, with the exception of [`TomlStyle::literal_datetimes`].
+///
+/// ```rust,ignore
+/// use disk::TomlStyle;
+///
+/// // Keep nested tables inline and in field order, like the sample config.
+/// disk::toml!(
+///     State, disk::Dir::Config, "MyProject", "", "state",
+///     style = TomlStyle { tables: false, multiline_arrays: false, ..TomlStyle::PRETTY }
+/// );
+/// ```
+#[derive(Copy,Clone,Debug,Hash,PartialEq,Eq,PartialOrd,Ord)]
+pub struct TomlStyle {
+	/// Write nested structs and maps as `[table]`s, after all plain values.
+	///
+	/// If `false`, they are written as `{ inline = "tables" }`, in field order.
+	pub tables: bool,
+	/// Write arrays with more than 1 element one element per line.
+	pub multiline_arrays: bool,
+	/// Write strings that are valid TOML datetimes (e.g. `1979-05-27T07:32:00Z`) as datetime literals instead of quoted strings.
+	///
+	/// Datetime literals are read back as strings, so these types should use
+	/// [`String`] (or types that (de)serialize as one, e.g. `chrono`) instead of `toml_datetime::Datetime`.
+	pub literal_datetimes: bool,
+}
+
+impl TomlStyle {
+	/// The default, the same output as [`toml_edit::ser::to_string_pretty`].
+	pub const PRETTY: Self = Self {
+		tables: true,
+		multiline_arrays: true,
+		literal_datetimes: false,
+	};
+}
+
+impl Default for TomlStyle {
+	fn default() -> Self {
+		Self::PRETTY
+	}
+}
+
+// Mirrors `toml_edit`'s pretty printer, with each part optional.
+impl toml_edit::visit_mut::VisitMut for TomlStyle {
+	fn visit_item_mut(&mut self, node: &mut toml_edit::Item) {
+		if self.tables {
+			let item = std::mem::take(node);
+			let item = item.into_table().map(toml_edit::Item::Table).unwrap_or_else(|i| i);
+			*node = item.into_array_of_tables().map(toml_edit::Item::ArrayOfTables).unwrap_or_else(|i| i);
+		}
+		toml_edit::visit_mut::visit_item_mut(self, node);
+	}
+
+	fn visit_table_mut(&mut self, node: &mut toml_edit::Table) {
+		node.decor_mut().clear();
+		// Empty tables could be meaningful, so they are kept explicit.
+		if !node.is_empty() {
+			node.set_implicit(true);
+		}
+		toml_edit::visit_mut::visit_table_mut(self, node);
+	}
+
+	fn visit_value_mut(&mut self, node: &mut toml_edit::Value) {
+		node.decor_mut().clear();
+		if self.literal_datetimes {
+			if let toml_edit::Value::String(s) = node {
+				if let Ok(datetime) = s.value().parse::<toml_edit::Datetime>() {
+					*node = toml_edit::Value::from(datetime);
+				}
+			}
+		}
+		toml_edit::visit_mut::visit_value_mut(self, node);
+	}
+
+	fn visit_array_mut(&mut self, node: &mut toml_edit::Array) {
+		toml_edit::visit_mut::visit_array_mut(self, node);
+		if !self.multiline_arrays || node.len() <= 1 {
+			node.set_trailing("");
+			node.set_trailing_comma(false);
+		} else {
+			for item in node.iter_mut() {
+				item.decor_mut().set_prefix("\n    ");
+			}
+			node.set_trailing("\n");
+			node.set_trailing_comma(true);
+		}
+	}
+}
+
+// Turn all datetime literals in `doc` into strings, see `TomlStyle::literal_datetimes`.
+fn datetimes_to_strings(doc: &mut toml_edit::Document) {
+	struct Strings;
+	impl toml_edit::visit_mut::VisitMut for Strings {
+		fn visit_value_mut(&mut self, node: &mut toml_edit::Value) {
+			if let toml_edit::Value::Datetime(datetime) = node {
+				*node = toml_edit::Value::from(datetime.value().to_string());
+			}
+			toml_edit::visit_mut::visit_value_mut(self, node);
+		}
+	}
+	toml_edit::visit_mut::VisitMut::visit_document_mut(&mut Strings, doc);
 }
 
 //---------------------------------------------------------------------------------------------------- TESTS
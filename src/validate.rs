@@ -0,0 +1,71 @@
+//---------------------------------------------------------------------------------------------------- Validate
+/// Validate data before saving and after loading.
+///
+/// If your type implements this trait _and_ one of the `disk` traits via macro,
+/// [`Validate::validate()`] will automatically be called:
+/// - By all `save*()` functions, before writing
+/// - By all `from_file*()` and `from_path*()` functions, after deserializing
+///
+/// If validation fails, the error is returned and nothing is written/returned.
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::toml!(Config, Dir::Data, "disk_test", "validate", "config");
+/// #[derive(Serialize,Deserialize)]
+/// struct Config {
+///     volume: u8,
+/// }
+///
+/// impl disk::Validate for Config {
+///     fn validate(&self) -> Result<(), disk::Error> {
+///         if self.volume > 100 {
+///             anyhow::bail!("volume must be 0-100, found: {}", self.volume);
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// // This errors before writing.
+/// assert!(Config { volume: 200 }.save().is_err());
+/// assert!(!Config::absolute_path().unwrap().exists());
+/// ```
+///
+/// Manual `unsafe impl`'s do not call [`Validate::validate()`] automatically.
+pub trait Validate {
+	/// Return an error if [`Self`] is invalid.
+	fn validate(&self) -> Result<(), anyhow::Error>;
+}
+
+//---------------------------------------------------------------------------------------------------- Internals
+// Autoref specialization so that the macros can call
+// `Validate::validate()` only if the type implements it.
+//
+// `(&ValidateProbe(data)).__disk_validate()` resolves to
+// `__ValidateYes` if `Validate` is implemented, else `__ValidateNo`.
+#[doc(hidden)]
+pub struct __ValidateProbe<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub trait __ValidateYes {
+	fn __disk_validate(&self) -> Result<(), anyhow::Error>;
+}
+
+impl<T: Validate> __ValidateYes for __ValidateProbe<'_, T> {
+	#[inline(always)]
+	fn __disk_validate(&self) -> Result<(), anyhow::Error> {
+		self.0.validate()
+	}
+}
+
+#[doc(hidden)]
+pub trait __ValidateNo {
+	fn __disk_validate(&self) -> Result<(), anyhow::Error>;
+}
+
+impl<T> __ValidateNo for &__ValidateProbe<'_, T> {
+	#[inline(always)]
+	fn __disk_validate(&self) -> Result<(), anyhow::Error> {
+		Ok(())
+	}
+}
@@ -0,0 +1,179 @@
+//---------------------------------------------------------------------------------------------------- Use
+use serde::{Serialize,Deserialize};
+use std::path::{Path,PathBuf};
+use std::sync::RwLock;
+use crate::{common,Dir,Format};
+
+//---------------------------------------------------------------------------------------------------- Registry
+// A type registered with `register_verify()`.
+#[derive(Copy,Clone)]
+pub(crate) struct Registered {
+	pub(crate) type_name: &'static str,
+	pub(crate) project: &'static str,
+	pub(crate) path: fn() -> Result<PathBuf, anyhow::Error>,
+	pub(crate) path_gzip: fn() -> Result<PathBuf, anyhow::Error>,
+	pub(crate) load: fn() -> Result<(), anyhow::Error>,
+	pub(crate) load_gzip: fn() -> Result<(), anyhow::Error>,
+}
+
+// All registered types.
+static REGISTERED: RwLock<Vec<Registered>> = RwLock::new(Vec::new());
+
+// Register a type, registering the same type twice does nothing.
+pub(crate) fn register(registered: Registered) {
+	let mut vec = REGISTERED.write().unwrap_or_else(std::sync::PoisonError::into_inner);
+	if !vec.iter().any(|r| r.type_name == registered.type_name) {
+		vec.push(registered);
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- VerifyReport
+/// The result of [`verify_project()`].
+///
+/// All PATHs are sorted.
+#[derive(Clone,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
+pub struct VerifyReport {
+	/// Files that loaded (registered types) or parsed (other files) successfully.
+	pub ok: Vec<PathBuf>,
+	/// Files that failed to load or parse, along with the error.
+	pub corrupt: Vec<(PathBuf, String)>,
+	/// Leftover `.tmp` files from an interrupted atomic save.
+	pub orphaned_tmp: Vec<PathBuf>,
+	/// Files previously quarantined by `from_file_or_quarantine()`.
+	pub quarantined: Vec<PathBuf>,
+	/// Files that could not be checked, e.g. `bincode` files of unregistered types.
+	pub unknown: Vec<PathBuf>,
+}
+
+impl VerifyReport {
+	#[inline]
+	/// Returns `true` if there are no corrupt files and no orphaned `.tmp` files.
+	pub fn is_ok(&self) -> bool {
+		self.corrupt.is_empty() && self.orphaned_tmp.is_empty()
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- verify_project
+/// Check every file in a project directory.
+///
+/// This walks the project directory (the same one `project_dir_path()` returns) and checks each file:
+///
+/// | File | Check | Reported as |
+/// |------|-------|-------------|
+/// | The file of a type registered with `register_verify()` | `from_file()` (headers, [`crate::Validate`], etc) | `ok` or `corrupt`
+/// | Any other `.toml`, `.json`, `.yml`, `.ron`, `.pickle`, `.messagepack`, `.bson` file (or `.gz` of it) | Parsed without a type | `ok` or `corrupt`
+/// | `*.tmp` | | `orphaned_tmp`
+/// | `*.corrupt-*` | | `quarantined`
+/// | Anything else | | `unknown`
+///
+/// Nothing is modified, this is meant for diagnosing ("my app lost my settings").
+///
+/// ```rust
+/// # use serde::{Serialize,Deserialize};
+/// # use disk::*;
+/// disk::bincode!(State, Dir::Data, "disk_test_verify", "", "state", [0; 24], 0);
+/// #[derive(Serialize,Deserialize)]
+/// struct State(u8);
+///
+/// State::register_verify();
+/// State(0).save().unwrap();
+/// std::fs::write(State::base_path().unwrap().join("config.json"), "{ broken").unwrap();
+///
+/// let report = disk::verify_project(Dir::Data, "disk_test_verify").unwrap();
+/// assert_eq!(report.ok, [State::absolute_path().unwrap()]);
+/// assert_eq!(report.corrupt.len(), 1);
+/// assert!(!report.is_ok());
+/// # State::rm_project().unwrap();
+/// ```
+///
+/// ## Errors
+/// This only errors if the directory itself could not be read,
+/// individual file errors are part of the [`VerifyReport`].
+pub fn verify_project(dir: Dir, project_directory: &str) -> Result<VerifyReport, anyhow::Error> {
+	let root = common::get_projectdir(&dir, project_directory)?;
+	let mut report = VerifyReport::default();
+
+	// Resolve the registered types of this project.
+	let registered = REGISTERED.read().unwrap_or_else(std::sync::PoisonError::into_inner).clone();
+	let mut known = vec![];
+	for r in registered.iter().filter(|r| r.project == project_directory) {
+		known.push(((r.path)()?, r.load));
+		known.push(((r.path_gzip)()?, r.load_gzip));
+	}
+
+	let mut files = vec![];
+	common::walk(&root, &mut files)?;
+	files.sort();
+
+	for path in files {
+		let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+		if name.ends_with(".tmp") {
+			report.orphaned_tmp.push(path);
+		} else if name.contains(".corrupt-") {
+			report.quarantined.push(path);
+		} else if let Some((_, load)) = known.iter().find(|(p, _)| *p == path) {
+			match load() {
+				Ok(())  => report.ok.push(path),
+				Err(e) => report.corrupt.push((path, e.to_string())),
+			}
+		} else {
+			match parse(&path) {
+				Some(Ok(())) => report.ok.push(path),
+				Some(Err(e)) => report.corrupt.push((path, e.to_string())),
+				None         => report.unknown.push(path),
+			}
+		}
+	}
+
+	Ok(report)
+}
+
+//---------------------------------------------------------------------------------------------------- Private
+// Parse a file without knowing its type, based on its extension.
+//
+// Returns `None` if the format can't be checked without a type.
+fn parse(path: &Path) -> Option<Result<(), anyhow::Error>> {
+	let name = path.file_name()?.to_str()?;
+	let (name, gzip) = match name.strip_suffix(".gz") {
+		Some(name) => (name, true),
+		None => (name, false),
+	};
+
+	let format = self_describing(name.rsplit_once('.')?.1)?;
+
+	let check = || -> Result<(), anyhow::Error> {
+		let bytes = std::fs::read(path)?;
+		let bytes = match gzip {
+			true  => common::decompress(bytes.as_slice())?,
+			false => bytes,
+		};
+		format.from_bytes::<serde::de::IgnoredAny>(&bytes)?;
+		Ok(())
+	};
+
+	Some(check())
+}
+
+// The self-describing format of a file extension, if any.
+//
+// Other formats (`bincode`, `postcard`, etc) can't be parsed without the type.
+fn self_describing(ext: &str) -> Option<Format> {
+	match ext {
+		#[cfg(feature = "toml")]
+		"toml" => Some(Format::Toml),
+		#[cfg(feature = "json")]
+		"json" => Some(Format::Json),
+		#[cfg(feature = "yaml")]
+		"yml" | "yaml" => Some(Format::Yaml),
+		#[cfg(feature = "ron")]
+		"ron" => Some(Format::Ron),
+		#[cfg(feature = "pickle")]
+		"pickle" => Some(Format::Pickle),
+		#[cfg(feature = "messagepack")]
+		"messagepack" => Some(Format::MessagePack),
+		#[cfg(feature = "bson")]
+		"bson" => Some(Format::Bson),
+		_ => None,
+	}
+}
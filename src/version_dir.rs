@@ -0,0 +1,150 @@
+//---------------------------------------------------------------------------------------------------- Use
+use anyhow::{anyhow,bail};
+use std::path::{Path,PathBuf};
+use std::sync::RwLock;
+
+//---------------------------------------------------------------------------------------------------- Version directory
+// The current version directory, if any.
+static VERSION_DIR: RwLock<Option<String>> = RwLock::new(None);
+
+/// Opt-in to per-version directories for your entire process.
+///
+/// `version` is a `MAJOR.MINOR[.PATCH]` version string, usually your crate's:
+/// ```rust
+/// disk::set_version_dir(env!("CARGO_PKG_VERSION")).unwrap();
+/// ```
+/// After this, a `vMAJOR.MINOR` directory is inserted right after the project
+/// directory (and profile, if any) in _every_ `PATH` used by `disk`, for example:
+/// ```txt
+/// ~/.local/share/myproject/some/dirs/state.toml
+/// ```
+/// turns into:
+/// ```txt
+/// ~/.local/share/myproject/v1.4/some/dirs/state.toml
+/// ```
+/// The `PATCH` version is ignored, so only `MAJOR.MINOR` releases get a new directory.
+///
+/// This is useful when the on-disk format changes incompatibly between versions,
+/// the files of older versions can be found with `version_dirs()` and
+/// carried over with `migrate_from_version()`/`migrate_from_latest_version()`.
+///
+/// ```rust
+/// disk::set_version_dir("1.4.2").unwrap();
+/// assert_eq!(disk::version_dir().as_deref(), Some("v1.4"));
+///
+/// // Invalid versions are rejected.
+/// assert!(disk::set_version_dir("one").is_err());
+///
+/// disk::clear_version_dir();
+/// assert_eq!(disk::version_dir(), None);
+/// ```
+///
+/// ## Errors
+/// This errors if `version` does not start with `MAJOR.MINOR` numbers.
+pub fn set_version_dir(version: &str) -> Result<(), anyhow::Error> {
+	let Some((major, minor)) = parse(version) else {
+		bail!("disk: invalid version '{version}', expected 'MAJOR.MINOR[.PATCH]'");
+	};
+	*VERSION_DIR.write().unwrap_or_else(|e| e.into_inner()) = Some(format!("v{major}.{minor}"));
+	Ok(())
+}
+
+/// Opt-out of per-version directories set with [`set_version_dir()`].
+pub fn clear_version_dir() {
+	*VERSION_DIR.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Returns the version directory set with [`set_version_dir()`], if any, e.g. `v1.4`.
+pub fn version_dir() -> Option<String> {
+	VERSION_DIR.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+// Parse `MAJOR.MINOR` out of `[v]MAJOR.MINOR[.PATCH][-PRE][+BUILD]`.
+fn parse(version: &str) -> Option<(u64, u64)> {
+	let version = version.strip_prefix('v').unwrap_or(version);
+	let mut iter = version.split(['.', '-', '+']);
+	let major = iter.next()?.parse().ok()?;
+	let minor = iter.next()?.parse().ok()?;
+	Some((major, minor))
+}
+
+// Check a caller-provided version directory name, it must be exactly
+// `vMAJOR.MINOR` (what `version_dirs()` returns), so it can't be a PATH like `../..`.
+pub(crate) fn validate_name(version: &str) -> Result<(), anyhow::Error> {
+	match parse(version) {
+		Some((major, minor)) if version == format!("v{major}.{minor}") => Ok(()),
+		_ => bail!("disk: invalid version directory '{version}', expected 'vMAJOR.MINOR'"),
+	}
+}
+
+// Append the version directory (if any) to a project directory PATH.
+pub(crate) fn push_version_dir(path: &mut PathBuf) {
+	if let Some(dir) = VERSION_DIR.read().unwrap_or_else(|e| e.into_inner()).as_deref() {
+		path.push(dir);
+	}
+}
+
+// All `vMAJOR.MINOR` directories within `root`, oldest first.
+pub(crate) fn version_dirs(root: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+	let mut dirs = vec![];
+
+	let read_dir = match std::fs::read_dir(root) {
+		Ok(r) => r,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+		Err(e) => return Err(e.into()),
+	};
+
+	for entry in read_dir {
+		let entry = entry?;
+		if !entry.file_type()?.is_dir() {
+			continue;
+		}
+		let name = entry.file_name();
+		let Some(name) = name.to_str() else { continue };
+		if !name.starts_with('v') {
+			continue;
+		}
+		if let Some(version) = parse(name) {
+			dirs.push((version, entry.path()));
+		}
+	}
+
+	dirs.sort_by_key(|(version, _)| *version);
+	Ok(dirs.into_iter().map(|(_, path)| path).collect())
+}
+
+// The newest version directory within `root` that contains `relative`.
+//
+// If a version directory is set, only older versions are considered.
+pub(crate) fn latest_containing(root: &Path, relative: &Path) -> Result<Option<PathBuf>, anyhow::Error> {
+	let current = version_dir().as_deref().and_then(parse);
+
+	for dir in version_dirs(root)?.into_iter().rev() {
+		let version = dir.file_name().and_then(|n| n.to_str()).and_then(parse);
+		if let (Some(current), Some(version)) = (current, version) {
+			if version >= current {
+				continue;
+			}
+		}
+		if dir.join(relative).exists() {
+			return Ok(Some(dir));
+		}
+	}
+
+	Ok(None)
+}
+
+// Copy `relative` from the version directory `from` into `to`.
+pub(crate) fn migrate(from: &Path, relative: &Path, to: &Path) -> Result<u64, anyhow::Error> {
+	let src = from.join(relative);
+	if !src.exists() {
+		return Err(anyhow!("{:?} does not exist", src));
+	}
+	if src == to {
+		bail!("{:?} is the current version", from);
+	}
+	if let Some(parent) = to.parent() {
+		std::fs::create_dir_all(parent)?;
+	}
+	Ok(std::fs::copy(&src, to)?)
+}
@@ -0,0 +1,108 @@
+//---------------------------------------------------------------------------------------------------- Use
+use serde::{Serialize,Deserialize};
+
+//---------------------------------------------------------------------------------------------------- VersionFallback
+/// Which constructors are attempted by `from_versions_with()`, after the first one fails.
+#[derive(Copy,Clone,Debug,Default,Hash,PartialEq,Eq,PartialOrd,Ord,Serialize,Deserialize)]
+pub enum VersionFallback {
+	/// Only attempt the first constructor matching the file's version.
+	First,
+	#[default]
+	/// Attempt every constructor matching the file's version, in order.
+	///
+	/// This is what `from_versions()` uses.
+	Matching,
+	/// Attempt every constructor matching the file's version, in order,
+	/// then every other constructor, in order.
+	All,
+}
+
+//---------------------------------------------------------------------------------------------------- VersionsFailed
+#[derive(Debug)]
+/// Error returned when no constructor of `from_versions()` succeeded.
+///
+/// This is returned (wrapped in [`anyhow::Error`]) by `from_versions()`, `from_versions_with()` and
+/// `from_versions_semver()`, and can be retrieved with [`anyhow::Error::downcast_ref`]:
+/// ```rust,ignore
+/// match Data::from_versions(TABLE) {
+///     Ok((version, data)) => println!("{version}: {data:?}"),
+///     Err(e) => match e.downcast_ref::<disk::VersionsFailed>() {
+///         Some(f) => for (version, error) in f.errors() {
+///             println!("constructor for {version} failed: {error}");
+///         },
+///         None => println!("could not read the file's version: {e}"),
+///     },
+/// }
+/// ```
+pub struct VersionsFailed {
+	version: String,
+	errors: Vec<(String, anyhow::Error)>,
+}
+
+impl VersionsFailed {
+	/// Returns the file's version, e.g. `3`, or `1.2.0` for `from_versions_semver()`.
+	pub fn version(&self) -> &str {
+		&self.version
+	}
+
+	/// Returns the version (or requirement) and error of each constructor attempted, in order.
+	///
+	/// This is empty if no constructor matched the file's version.
+	pub fn errors(&self) -> &[(String, anyhow::Error)] {
+		&self.errors
+	}
+
+	/// Consume [`VersionsFailed`] and returns the errors.
+	pub fn into_errors(self) -> Vec<(String, anyhow::Error)> {
+		self.errors
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Display
+impl std::fmt::Display for VersionsFailed {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if self.errors.is_empty() {
+			return write!(f, "no constructor matched file version {}", self.version);
+		}
+
+		write!(f, "all constructors for file version {} failed ({} attempted)", self.version, self.errors.len())?;
+		for (version, error) in &self.errors {
+			write!(f, "\n{version}: {error}")?;
+		}
+		Ok(())
+	}
+}
+
+impl std::error::Error for VersionsFailed {}
+
+//---------------------------------------------------------------------------------------------------- Private
+// A constructor of `from_versions()`, its version (or requirement), and if it matches the file's version.
+pub(crate) type Candidate<T> = (String, bool, fn() -> Result<T, anyhow::Error>);
+
+// Attempt the `candidates` according to `fallback`.
+//
+// On success, this returns the index of the constructor that succeeded.
+pub(crate) fn attempt<T>(
+	version: String,
+	candidates: &[Candidate<T>],
+	fallback: VersionFallback,
+) -> Result<(usize, T), anyhow::Error> {
+	let matching = candidates.iter().enumerate().filter(|(_, (_, matches, _))| *matches);
+	let others   = candidates.iter().enumerate().filter(|(_, (_, matches, _))| !*matches);
+
+	let order: Vec<_> = match fallback {
+		VersionFallback::First    => matching.take(1).collect(),
+		VersionFallback::Matching => matching.collect(),
+		VersionFallback::All      => matching.chain(others).collect(),
+	};
+
+	let mut errors = vec![];
+	for (i, (v, _, constructor)) in order {
+		match constructor() {
+			Ok(data) => return Ok((i, data)),
+			Err(e)   => errors.push((v.clone(), e)),
+		}
+	}
+
+	Err(VersionsFailed { version, errors }.into())
+}
@@ -0,0 +1,260 @@
+//---------------------------------------------------------------------------------------------------- Use
+use anyhow::{anyhow,bail};
+use std::path::{Path,PathBuf};
+
+//---------------------------------------------------------------------------------------------------- Extended attributes
+// Attributes are stored natively where possible:
+// - Unix: extended attributes named `user.disk.$KEY`
+// - Windows: NTFS alternate data streams named `$FILE:disk.$KEY`
+//
+// If the filesystem does not support them, they are stored in a
+// sidecar file next to the file instead, e.g. `config.toml.xattr`,
+// one `$KEY\t$HEX_VALUE` line per attribute.
+
+// The extension of sidecar files.
+pub(crate) const EXT: &str = "xattr";
+
+// The prefix of native attribute names.
+const PREFIX: &str = "disk.";
+
+// The sidecar PATH for the file at `path`.
+pub(crate) fn sidecar_path(path: &Path) -> PathBuf {
+	let mut sidecar = path.to_path_buf().into_os_string();
+	sidecar.push(".");
+	sidecar.push(EXT);
+	PathBuf::from(sidecar)
+}
+
+// Keys must be usable as an attribute name, a stream name, and a sidecar line.
+fn check_key(key: &str) -> Result<(), anyhow::Error> {
+	if key.is_empty() {
+		bail!("disk: attribute key is empty");
+	}
+	if key.len() > 200 {
+		bail!("disk: attribute key {key:?} is longer than 200 bytes");
+	}
+	if let Some(c) = key.chars().find(|c| c.is_control() || matches!(c, ':' | '/' | '\\')) {
+		bail!("disk: attribute key {key:?} contains {c:?}");
+	}
+	Ok(())
+}
+
+// Set the attribute `key` on the existing file at `path`.
+pub(crate) fn set(path: &Path, key: &str, value: &[u8]) -> Result<(), anyhow::Error> {
+	check_key(key)?;
+	if !path.is_file() {
+		bail!("disk: {path:?} does not exist");
+	}
+
+	match native::set(path, key, value) {
+		Ok(true) => {
+			// Don't let an old sidecar value shadow the new one.
+			if sidecar_path(path).exists() {
+				set_sidecar(path, key, None)?;
+			}
+			Ok(())
+		},
+		Ok(false) => set_sidecar(path, key, Some(value)),
+		Err(e) => Err(e),
+	}
+}
+
+// Get the attribute `key` of the file at `path`, if set.
+pub(crate) fn get(path: &Path, key: &str) -> Result<Option<Vec<u8>>, anyhow::Error> {
+	check_key(key)?;
+	if !path.is_file() {
+		bail!("disk: {path:?} does not exist");
+	}
+
+	match native::get(path, key)? {
+		Some(value) => Ok(Some(value)),
+		None => Ok(read_sidecar(path)?.into_iter().find(|(k, _)| k == key).map(|(_, v)| v)),
+	}
+}
+
+//---------------------------------------------------------------------------------------------------- Sidecar
+fn read_sidecar(path: &Path) -> Result<Vec<(String, Vec<u8>)>, anyhow::Error> {
+	let sidecar = sidecar_path(path);
+	let string = match std::fs::read_to_string(&sidecar) {
+		Ok(s) => s,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+		Err(e) => return Err(e.into()),
+	};
+
+	string.lines().map(|line| {
+		let (key, hex) = line.split_once('\t').ok_or_else(|| anyhow!("disk: malformed attribute in {sidecar:?}: {line:?}"))?;
+		let value = decode_hex(hex).ok_or_else(|| anyhow!("disk: malformed attribute value in {sidecar:?}: {line:?}"))?;
+		Ok((key.to_string(), value))
+	}).collect()
+}
+
+// Set (or with `None`, remove) `key` in the sidecar of `path`.
+fn set_sidecar(path: &Path, key: &str, value: Option<&[u8]>) -> Result<(), anyhow::Error> {
+	let mut attrs = read_sidecar(path)?;
+	attrs.retain(|(k, _)| k != key);
+	if let Some(value) = value {
+		attrs.push((key.to_string(), value.to_vec()));
+	}
+
+	let sidecar = sidecar_path(path);
+	if attrs.is_empty() {
+		return match std::fs::remove_file(&sidecar) {
+			Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+			_ => Ok(()),
+		};
+	}
+
+	let mut string = String::new();
+	for (k, v) in attrs {
+		string.push_str(&k);
+		string.push('\t');
+		for b in v {
+			string.push_str(&format!("{b:02x}"));
+		}
+		string.push('\n');
+	}
+	let mut tmp = sidecar.clone().into_os_string();
+	tmp.push(".tmp");
+	std::fs::write(&tmp, string)?;
+	std::fs::rename(&tmp, &sidecar)?;
+	Ok(())
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+	hex.as_bytes()
+		.chunks(2)
+		.map(|b| std::str::from_utf8(b).ok().filter(|b| b.len() == 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+		.collect()
+}
+
+//---------------------------------------------------------------------------------------------------- Native
+// `Ok(false)` means the filesystem (or platform) does not support attributes.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod native {
+	use std::ffi::CString;
+	use std::os::unix::ffi::OsStrExt;
+	use std::path::Path;
+
+	fn c_strings(path: &Path, key: &str) -> Result<(CString, CString), anyhow::Error> {
+		let path = CString::new(path.as_os_str().as_bytes())?;
+		#[cfg(target_os = "linux")]
+		let name = CString::new(format!("user.{}{key}", super::PREFIX))?;
+		#[cfg(target_os = "macos")]
+		let name = CString::new(format!("{}{key}", super::PREFIX))?;
+		Ok((path, name))
+	}
+
+	fn unsupported(e: &std::io::Error) -> bool {
+		// These are the same on Linux.
+		let code = e.raw_os_error();
+		code == Some(libc::ENOTSUP) || code == Some(libc::EOPNOTSUPP) || code == Some(libc::EPERM)
+	}
+
+	pub(super) fn set(path: &Path, key: &str, value: &[u8]) -> Result<bool, anyhow::Error> {
+		let (path, name) = c_strings(path, key)?;
+
+		// SAFETY: `path` and `name` are valid C strings, `value` is valid for its length.
+		#[cfg(target_os = "linux")]
+		let ret = unsafe { libc::setxattr(path.as_ptr(), name.as_ptr(), value.as_ptr().cast(), value.len(), 0) };
+		#[cfg(target_os = "macos")]
+		let ret = unsafe { libc::setxattr(path.as_ptr(), name.as_ptr(), value.as_ptr().cast(), value.len(), 0, 0) };
+
+		if ret == 0 {
+			return Ok(true);
+		}
+		let e = std::io::Error::last_os_error();
+		if unsupported(&e) {
+			Ok(false)
+		} else {
+			Err(e.into())
+		}
+	}
+
+	pub(super) fn get(path: &Path, key: &str) -> Result<Option<Vec<u8>>, anyhow::Error> {
+		let (path, name) = c_strings(path, key)?;
+
+		// The value may change between asking for its size and reading it, so retry.
+		loop {
+			// SAFETY: `path` and `name` are valid C strings, `buf` is valid for its length.
+			let call = |buf: &mut [u8]| unsafe {
+				#[cfg(target_os = "linux")]
+				{ libc::getxattr(path.as_ptr(), name.as_ptr(), buf.as_mut_ptr().cast(), buf.len()) }
+				#[cfg(target_os = "macos")]
+				{ libc::getxattr(path.as_ptr(), name.as_ptr(), buf.as_mut_ptr().cast(), buf.len(), 0, 0) }
+			};
+
+			let len = call(&mut []);
+			if len < 0 {
+				let e = std::io::Error::last_os_error();
+				#[cfg(target_os = "linux")]
+				let missing = e.raw_os_error() == Some(libc::ENODATA);
+				#[cfg(target_os = "macos")]
+				let missing = e.raw_os_error() == Some(libc::ENOATTR);
+				if missing || unsupported(&e) {
+					return Ok(None);
+				}
+				return Err(e.into());
+			}
+
+			#[allow(clippy::cast_sign_loss)]
+			let mut buf = vec![0; len as usize];
+			let read = call(&mut buf);
+			if read < 0 {
+				let e = std::io::Error::last_os_error();
+				if e.raw_os_error() == Some(libc::ERANGE) {
+					continue;
+				}
+				return Err(e.into());
+			}
+
+			#[allow(clippy::cast_sign_loss)]
+			buf.truncate(read as usize);
+			return Ok(Some(buf));
+		}
+	}
+}
+
+#[cfg(windows)]
+mod native {
+	use std::path::{Path,PathBuf};
+
+	fn stream_path(path: &Path, key: &str) -> PathBuf {
+		let mut stream = path.to_path_buf().into_os_string();
+		stream.push(":");
+		stream.push(super::PREFIX);
+		stream.push(key);
+		PathBuf::from(stream)
+	}
+
+	pub(super) fn set(path: &Path, key: &str, value: &[u8]) -> Result<bool, anyhow::Error> {
+		match std::fs::write(stream_path(path, key), value) {
+			Ok(()) => Ok(true),
+			// Non-NTFS filesystems reject the stream name.
+			Err(e) if matches!(e.kind(), std::io::ErrorKind::NotFound | std::io::ErrorKind::InvalidInput) => Ok(false),
+			Err(e) if e.raw_os_error() == Some(123) => Ok(false), // ERROR_INVALID_NAME
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	pub(super) fn get(path: &Path, key: &str) -> Result<Option<Vec<u8>>, anyhow::Error> {
+		match std::fs::read(stream_path(path, key)) {
+			Ok(value) => Ok(Some(value)),
+			Err(e) if matches!(e.kind(), std::io::ErrorKind::NotFound | std::io::ErrorKind::InvalidInput) => Ok(None),
+			Err(e) if e.raw_os_error() == Some(123) => Ok(None), // ERROR_INVALID_NAME
+			Err(e) => Err(e.into()),
+		}
+	}
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod native {
+	use std::path::Path;
+
+	pub(super) fn set(_: &Path, _: &str, _: &[u8]) -> Result<bool, anyhow::Error> {
+		Ok(false)
+	}
+
+	pub(super) fn get(_: &Path, _: &str) -> Result<Option<Vec<u8>>, anyhow::Error> {
+		Ok(None)
+	}
+}
@@ -45,7 +45,28 @@ pub unsafe trait Yaml: serde::Serialize + serde::de::DeserializeOwned {
 	#[inline(always)]
 	/// Create [`Self`] from bytes.
 	fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
-		common::convert_error(serde_yaml::from_slice(bytes))
+		common::convert_error(serde_yaml::from_slice(&common::decode_text(bytes)?))
+	}
+
+	#[inline(always)]
+	/// Same as [`Self::from_bytes`] but errors on unknown fields.
+	fn from_bytes_strict(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+		common::deserialize_strict(serde_yaml::Deserializer::from_slice(&common::decode_text(bytes)?))
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	/// Internal function. Create [`Self`] from bytes, filling missing fields from [`Default`].
+	///
+	/// Returns `true` if any fields were missing.
+	fn __from_bytes_upgraded(bytes: &[u8]) -> Result<(Self, bool), anyhow::Error>
+	where
+		Self: Default,
+	{
+		let mut value: serde_yaml::Value = serde_yaml::from_slice(&common::decode_text(bytes)?)?;
+		let default  = Self::default();
+		let upgraded = common::merge_yaml(&mut value, serde_yaml::to_value(&default)?, &crate::shape::Shape::of(&default));
+		Ok((serde_yaml::from_value(value)?, upgraded))
 	}
 
 	// YAML operations.
@@ -54,16 +75,27 @@ pub unsafe trait Yaml: serde::Serialize + serde::de::DeserializeOwned {
 	///
 	/// This uses [`toml_edit::ser::to_string_pretty`];
 	fn to_string(&self) -> Result<String, anyhow::Error> {
-		common::convert_error(serde_yaml::to_string(self))
+		Self::__to_string(self)
+	}
+	#[doc(hidden)]
+	#[inline(always)]
+	/// Internal function. Serialize any `value` the same way as [`Self::to_string()`].
+	fn __to_string<V: serde::Serialize>(value: &V) -> Result<String, anyhow::Error> {
+		common::convert_error(serde_yaml::to_string(value))
 	}
 	#[inline(always)]
 	/// Create [`Self`] from [`String`].
 	fn from_string(string: &str) -> Result<Self, anyhow::Error> {
-		common::convert_error(serde_yaml::from_str(string))
+		common::convert_error(serde_yaml::from_str(common::strip_bom(string)))
 	}
 
 	// Common data/functions.
-	common::impl_string!("yml");
+	common::impl_strict!();
+	common::impl_upgrade!();
+	common::impl_merge!();
+	#[cfg(feature = "schemars")]
+	common::impl_schema!();
+	common::impl_string!("yml", "#");
 }
 
 //---------------------------------------------------------------------------------------------------- TESTS